@@ -1,9 +1,7 @@
-use std::cmp;
 use std::env;
 
 use criterion::{criterion_group, criterion_main, Criterion};
 
-#[allow(unused_imports)]
 use sort_test_tools::ffi_types::{FFIOneKiloByte, FFIString, F128};
 
 use sort_test_tools::patterns;
@@ -243,49 +241,10 @@ fn bench_patterns<T: Ord + std::fmt::Debug>(
     }
 }
 
-fn shift_i32_to_u32(val: i32) -> u32 {
-    (val as i64 + (i32::MAX as i64 + 1)) as u32
-}
-
-#[allow(unused)]
-fn compress_i32(values: &[i32], compression_range: f64) -> impl Iterator<Item = u32> + '_ {
-    // (val & u8::MAX as i32) as u8
-
-    let mut min_val = u32::MAX;
-    let mut max_val = u32::MIN;
-
-    for elem in values {
-        let elem_as_u32 = shift_i32_to_u32(*elem);
-
-        min_val = cmp::min(min_val, elem_as_u32);
-        max_val = cmp::max(max_val, elem_as_u32);
-    }
-
-    let range = max_val - min_val;
-    let mult = (compression_range - 1.0) / (range as f64);
-
-    values
-        .iter()
-        .map(move |val| (((shift_i32_to_u32(*val) - min_val) as f64 * mult).round() as u32))
-}
-
-fn extend_i32_to_u64(val: i32) -> u64 {
-    // Extends the value into the 64 bit range,
-    // while preserving input order.
-    (shift_i32_to_u32(val) as u64) * i32::MAX as u64
-}
-
-#[allow(unused)]
-fn extend_i32_to_u128(val: i32) -> u128 {
-    // Extends the value into the 64 bit range,
-    // while preserving input order.
-    (shift_i32_to_u32(val) as u128) * i64::MAX as u128
-}
-
 fn ensure_true_random() {
     // Ensure that random vecs are actually different.
-    let random_vec_a = patterns::random(5);
-    let random_vec_b = patterns::random(5);
+    let random_vec_a = patterns::random::<i32>(5);
+    let random_vec_b = patterns::random::<i32>(5);
 
     // I had a bug, where the test logic for fixed seeds, made the benchmarks always use the same
     // numbers, and random wasn't random at all anymore.
@@ -308,72 +267,42 @@ fn criterion_benchmark(c: &mut Criterion) {
 
         // Common type for usize on 64-bit machines.
         // Sorting indices is very common.
-        bench_patterns(c, test_len, "u64", |values| -> Vec<u64> {
-            values.into_iter().map(extend_i32_to_u64).collect()
-        });
+        bench_patterns(c, test_len, "u64", patterns::convert_i32::<u64>);
 
         // Larger type that is not Copy and does heap access.
         // FFI String
-        bench_patterns(c, test_len, "string", |values| {
-            values
-                .iter()
-                .map(|val| FFIString::new(format!("{:010}", val.saturating_abs())))
-                .collect()
-        });
+        bench_patterns(c, test_len, "string", patterns::convert_i32::<FFIString>);
 
         // Very large stack value.
-        bench_patterns(c, test_len, "1k", |values| {
-            values.iter().map(|val| FFIOneKiloByte::new(*val)).collect()
-        });
+        bench_patterns(c, test_len, "1k", patterns::convert_i32::<FFIOneKiloByte>);
 
         // 16 byte stack value that is Copy but has a relatively expensive cmp implementation.
-        bench_patterns(c, test_len, "f128", |values| {
-            values.iter().map(|val| F128::new(*val)).collect()
-        });
+        bench_patterns(c, test_len, "f128", patterns::convert_i32::<F128>);
 
         #[cfg(feature = "bench_type_rust_string")]
         {
-            bench_patterns(c, test_len, "rust_string", |values| {
-                // Strings are compared lexicographically, so we zero extend them to maintain the input
-                // order.
-                // See: https://godbolt.org/z/M38zTK6nv and https://godbolt.org/z/G18Yb7zoE
-                values
-                    .iter()
-                    .map(|val| format!("{:010}", val.saturating_abs()))
-                    .collect()
-            });
+            // See: https://godbolt.org/z/M38zTK6nv and https://godbolt.org/z/G18Yb7zoE
+            bench_patterns(c, test_len, "rust_string", patterns::convert_i32::<String>);
         }
 
         #[cfg(feature = "bench_type_u8")]
         {
-            bench_patterns(c, test_len, "u8", |values| -> Vec<u8> {
-                compress_i32(&values, 2u32.pow(u8::BITS) as f64)
-                    .map(|val| val as u8)
-                    .collect()
-            });
+            bench_patterns(c, test_len, "u8", patterns::convert_i32::<u8>);
         }
 
         #[cfg(feature = "bench_type_u16")]
         {
-            bench_patterns(c, test_len, "u16", |values| -> Vec<u16> {
-                compress_i32(&values, 2u32.pow(u16::BITS) as f64)
-                    .map(|val| val as u16)
-                    .collect()
-            });
+            bench_patterns(c, test_len, "u16", patterns::convert_i32::<u16>);
         }
 
         #[cfg(feature = "bench_type_u32")]
         {
-            bench_patterns(c, test_len, "u32", |values| -> Vec<u32> {
-                values.into_iter().map(shift_i32_to_u32).collect()
-            });
+            bench_patterns(c, test_len, "u32", patterns::convert_i32::<u32>);
         }
 
         #[cfg(feature = "bench_type_u128")]
         {
-            bench_patterns(c, test_len, "u128", |values| -> Vec<u128> {
-                values.into_iter().map(extend_i32_to_u128).collect()
-            });
+            bench_patterns(c, test_len, "u128", patterns::convert_i32::<u128>);
         }
 
         #[cfg(feature = "bench_type_val_with_mutex")]
@@ -0,0 +1,95 @@
+use criterion::{black_box, Criterion};
+
+use sort_comp::other::argsort::{self, Argsort};
+
+use crate::modules::util::bench_fn;
+
+#[inline(never)]
+fn bench_impl<T: Ord + std::fmt::Debug, A: Argsort>(
+    c: &mut Criterion,
+    test_len: usize,
+    transform_name: &str,
+    transform: &fn(Vec<i32>) -> Vec<T>,
+    pattern_name: &str,
+    pattern_provider: &fn(usize) -> Vec<i32>,
+    _argsort_impl: A,
+) {
+    // Only computing the permutation, which leaves the elements where they are.
+    bench_fn(
+        c,
+        test_len,
+        transform_name,
+        transform,
+        pattern_name,
+        pattern_provider,
+        &A::name(),
+        |v: &mut [T]| {
+            black_box(A::argsort(v));
+        },
+    );
+
+    // Computing the permutation and applying it, which moves every element only once. Compare
+    // with sorting the elements directly, for types that are expensive to move.
+    bench_fn(
+        c,
+        test_len,
+        transform_name,
+        transform,
+        pattern_name,
+        pattern_provider,
+        &format!("{}_apply", A::name()),
+        |v: &mut [T]| {
+            let mut perm = A::argsort(v);
+            ipnsort::apply_permutation_in_place(v, &mut perm);
+        },
+    );
+}
+
+pub fn bench<T: Ord + std::fmt::Debug>(
+    c: &mut Criterion,
+    test_len: usize,
+    transform_name: &str,
+    transform: &fn(Vec<i32>) -> Vec<T>,
+    pattern_name: &str,
+    pattern_provider: &fn(usize) -> Vec<i32>,
+) {
+    bench_impl(
+        c,
+        test_len,
+        transform_name,
+        transform,
+        pattern_name,
+        pattern_provider,
+        argsort::rust_ipnsort::ArgsortImpl,
+    );
+
+    bench_impl(
+        c,
+        test_len,
+        transform_name,
+        transform,
+        pattern_name,
+        pattern_provider,
+        argsort::rust_ipnsort_stable::ArgsortImpl,
+    );
+
+    bench_impl(
+        c,
+        test_len,
+        transform_name,
+        transform,
+        pattern_name,
+        pattern_provider,
+        argsort::rust_std::ArgsortImpl,
+    );
+
+    bench_impl(
+        c,
+        test_len,
+        transform_name,
+        transform,
+        pattern_name,
+        pattern_provider,
+        argsort::rust_std_stable::ArgsortImpl,
+    );
+}
@@ -10,6 +10,15 @@ pub mod partition_point;
 #[cfg(feature = "partition")]
 pub mod partition;
 
+#[cfg(feature = "partial_sort")]
+pub mod partial_sort;
+
+#[cfg(feature = "argsort")]
+pub mod argsort;
+
+#[cfg(feature = "sort_zip")]
+pub mod sort_zip;
+
 #[allow(unused)]
 pub fn bench_len_type_pattern_combo<T: Ord + std::fmt::Debug>(
     c: &mut Criterion,
@@ -43,6 +52,39 @@ pub fn bench_len_type_pattern_combo<T: Ord + std::fmt::Debug>(
                     pattern_provider,
                 );
             }
+            #[cfg(feature = "partial_sort")]
+            "partial_sort" => {
+                partial_sort::bench(
+                    c,
+                    test_len,
+                    transform_name,
+                    transform,
+                    pattern_name,
+                    pattern_provider,
+                );
+            }
+            #[cfg(feature = "argsort")]
+            "argsort" => {
+                argsort::bench(
+                    c,
+                    test_len,
+                    transform_name,
+                    transform,
+                    pattern_name,
+                    pattern_provider,
+                );
+            }
+            #[cfg(feature = "sort_zip")]
+            "sort_zip" => {
+                sort_zip::bench(
+                    c,
+                    test_len,
+                    transform_name,
+                    transform,
+                    pattern_name,
+                    pattern_provider,
+                );
+            }
             _ => panic!(
                 "Unknown BENCH_OTHER value: '{}'. Make sure the feature is enabled.",
                 env_val
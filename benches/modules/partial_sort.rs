@@ -0,0 +1,97 @@
+use criterion::Criterion;
+
+use sort_comp::other::partial_sort::{self, PartialSort};
+
+use crate::modules::util::bench_fn;
+
+/// The `k` values to benchmark, as `(name, k)`. Typical uses of partial sort ask for a small
+/// fixed number of elements, e.g. the top 10 results, or for a fraction of the input.
+fn k_variants(test_len: usize) -> Vec<(&'static str, usize)> {
+    vec![
+        ("k_10", 10),
+        ("k_sqrt", (test_len as f64).sqrt() as usize),
+        ("k_1p", test_len / 100),
+        ("k_10p", test_len / 10),
+        ("k_50p", test_len / 2),
+    ]
+    .into_iter()
+    .map(|(k_name, k)| (k_name, k.min(test_len)))
+    .collect()
+}
+
+#[inline(never)]
+fn bench_impl<T: Ord + std::fmt::Debug, P: PartialSort>(
+    c: &mut Criterion,
+    test_len: usize,
+    transform_name: &str,
+    transform: &fn(Vec<i32>) -> Vec<T>,
+    pattern_name: &str,
+    pattern_provider: &fn(usize) -> Vec<i32>,
+    _partial_sort_impl: P,
+) {
+    for (k_name, k) in k_variants(test_len) {
+        let bench_name = format!("{}_{k_name}", P::name());
+
+        bench_fn(
+            c,
+            test_len,
+            transform_name,
+            transform,
+            pattern_name,
+            pattern_provider,
+            &bench_name,
+            |v: &mut [T]| P::partial_sort(v, k),
+        );
+    }
+}
+
+pub fn bench<T: Ord + std::fmt::Debug>(
+    c: &mut Criterion,
+    test_len: usize,
+    transform_name: &str,
+    transform: &fn(Vec<i32>) -> Vec<T>,
+    pattern_name: &str,
+    pattern_provider: &fn(usize) -> Vec<i32>,
+) {
+    bench_impl(
+        c,
+        test_len,
+        transform_name,
+        transform,
+        pattern_name,
+        pattern_provider,
+        partial_sort::rust_ipnsort::PartialSortImpl,
+    );
+
+    bench_impl(
+        c,
+        test_len,
+        transform_name,
+        transform,
+        pattern_name,
+        pattern_provider,
+        partial_sort::rust_std::PartialSortImpl,
+    );
+
+    #[cfg(feature = "cpp_std_sys")]
+    bench_impl(
+        c,
+        test_len,
+        transform_name,
+        transform,
+        pattern_name,
+        pattern_provider,
+        partial_sort::cpp_std_sys::PartialSortImpl,
+    );
+
+    #[cfg(feature = "cpp_std_libcxx")]
+    bench_impl(
+        c,
+        test_len,
+        transform_name,
+        transform,
+        pattern_name,
+        pattern_provider,
+        partial_sort::cpp_std_libcxx::PartialSortImpl,
+    );
+}
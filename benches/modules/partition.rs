@@ -7,7 +7,7 @@ use std::time;
 
 use criterion::{black_box, Criterion};
 
-use sort_comp::other::partition::{self, Partition};
+use sort_comp::other::partition::{self, Partition, Partition3};
 
 use crate::modules::util::{cpu_max_freq_hz, pin_thread_to_core, should_run_benchmark};
 
@@ -24,6 +24,46 @@ fn bench_partition_impl<T: Ord + std::fmt::Debug, P: Partition>(
     pattern_name: &str,
     pattern_provider: &fn(usize) -> Vec<i32>,
     _partition_impl: P,
+) {
+    bench_partition_fn(
+        &P::name(),
+        test_len,
+        transform_name,
+        transform,
+        pattern_name,
+        pattern_provider,
+        P::partition,
+    );
+}
+
+fn bench_partition3_impl<T: Ord + std::fmt::Debug, P: Partition3>(
+    test_len: usize,
+    transform_name: &str,
+    transform: &fn(Vec<i32>) -> Vec<T>,
+    pattern_name: &str,
+    pattern_provider: &fn(usize) -> Vec<i32>,
+    _partition3_impl: P,
+) {
+    // Only the lt part is used to place the pivot afterwards, the eq part stays where it is.
+    bench_partition_fn(
+        &P::name(),
+        test_len,
+        transform_name,
+        transform,
+        pattern_name,
+        pattern_provider,
+        |v, pivot| P::partition3(v, pivot).0,
+    );
+}
+
+fn bench_partition_fn<T: Ord + std::fmt::Debug>(
+    impl_name: &str,
+    test_len: usize,
+    transform_name: &str,
+    transform: &fn(Vec<i32>) -> Vec<T>,
+    pattern_name: &str,
+    pattern_provider: &fn(usize) -> Vec<i32>,
+    partition_fn: impl Fn(&mut [T], &T) -> usize,
 ) {
     // Pin the benchmark to the same core to improve repeatability. Doing it this way allows
     // criterion to do other stuff with other threads, which greatly impacts overall benchmark
@@ -32,10 +72,7 @@ fn bench_partition_impl<T: Ord + std::fmt::Debug, P: Partition>(
 
     let bench_name = format!(
         "{}-{}-{}-{}",
-        P::name(),
-        transform_name,
-        pattern_name,
-        test_len
+        impl_name, transform_name, pattern_name, test_len
     );
 
     if !should_run_benchmark(&bench_name) {
@@ -93,7 +130,7 @@ fn bench_partition_impl<T: Ord + std::fmt::Debug, P: Partition>(
             test_input.swap(0, *pivot_pos);
 
             let pivot = unsafe { mem::ManuallyDrop::new(ptr::read(&test_input[0])) };
-            let swap_idx = black_box(P::partition(
+            let swap_idx = black_box(partition_fn(
                 black_box(&mut test_input[1..]),
                 black_box(&pivot),
             ));
@@ -302,8 +339,14 @@ pub fn bench<T: Ord + std::fmt::Debug>(
     bench_inst!(hoare_block);
     bench_inst!(hoare_branchy_cyclic);
     bench_inst!(hoare_branchy);
-    bench_inst!(hoare_crumsort_opt);
-    bench_inst!(hoare_crumsort_rs);
+    // hoare_crumsort_opt asserts inputs of at least twice its rotation buffer length, and
+    // hoare_crumsort_rs only handles 8 byte types.
+    if test_len >= 32 {
+        bench_inst!(hoare_crumsort_opt);
+    }
+    if mem::size_of::<T>() == 8 {
+        bench_inst!(hoare_crumsort_rs);
+    }
     bench_inst!(hoare_crumsort);
     bench_inst!(hybrid_bitset_partition);
     bench_inst!(hybrid_block_partition);
@@ -314,7 +357,31 @@ pub fn bench<T: Ord + std::fmt::Debug>(
     bench_inst!(lomuto_branchless);
     bench_inst!(lomuto_branchy);
     bench_inst!(lomuto_iterleaved);
+    #[cfg(feature = "partition_simd")]
+    bench_inst!(simd_compress);
     bench_inst!(small_partition);
     bench_inst!(stable_2side_fill);
     bench_inst!(sum_is_less);
+
+    // Three-way partitions are only interesting with many equal elements.
+    if !(pattern_name.starts_with("random_d") || pattern_name.starts_with("random_p")) {
+        return;
+    }
+
+    macro_rules! bench_inst3 {
+        ($partition3_impl:ident) => {
+            bench_partition3_impl(
+                test_len,
+                transform_name,
+                transform,
+                pattern_name,
+                pattern_provider,
+                partition::$partition3_impl::Partition3Impl,
+            );
+        };
+    }
+
+    bench_inst3!(bentley_mcilroy_3way);
+    bench_inst3!(lomuto_branchless_3way);
+    bench_inst3!(two_pass_3way);
 }
@@ -107,6 +107,8 @@ pub fn bench<T: Ord + std::fmt::Debug>(
 
     bench_inst!(stable::rust_std);
 
+    bench_inst!(stable::rust_ipnsort);
+
     #[cfg(feature = "cpp_std_sys")]
     bench_inst!(stable::cpp_std_sys);
 
@@ -143,6 +145,9 @@ pub fn bench<T: Ord + std::fmt::Debug>(
 
     bench_inst!(unstable::rust_std);
 
+    #[cfg(feature = "rust_ipnsort_parallel")]
+    bench_inst!(unstable::rust_ipnsort_parallel);
+
     #[cfg(feature = "rust_dmsort")]
     bench_inst!(unstable::rust_dmsort);
 
@@ -0,0 +1,102 @@
+use std::ptr;
+
+use criterion::Criterion;
+
+use sort_comp::{stable, unstable};
+
+use crate::modules::util::bench_fn;
+
+/// Moves the keys and the payload into a `Vec` of tuples, sorts it with `sort_fn` and moves them
+/// back. This is what sorting separate columns looks like without [`ipnsort::sort_zip`].
+fn sort_zipped_tuples<T>(
+    keys: &mut [T],
+    payload: &mut [u64],
+    sort_fn: impl FnOnce(&mut [(T, u64)]),
+) {
+    // SAFETY: Every key is read exactly once here and written back exactly once below. The
+    // benchmarked sorts don't panic for the benchmarked types, which would drop keys twice.
+    let mut zipped = keys
+        .iter()
+        .zip(payload.iter())
+        .map(|(key, &val)| (unsafe { ptr::read(key) }, val))
+        .collect::<Vec<_>>();
+
+    sort_fn(&mut zipped);
+
+    for (i, (key, val)) in zipped.into_iter().enumerate() {
+        unsafe { ptr::write(&mut keys[i], key) };
+        payload[i] = val;
+    }
+}
+
+pub fn bench<T: Ord + std::fmt::Debug>(
+    c: &mut Criterion,
+    test_len: usize,
+    transform_name: &str,
+    transform: &fn(Vec<i32>) -> Vec<T>,
+    pattern_name: &str,
+    pattern_provider: &fn(usize) -> Vec<i32>,
+) {
+    // The payload is a column of row ids, its creation is part of every benchmark.
+    let make_payload = |len: usize| (0..len as u64).collect::<Vec<_>>();
+
+    bench_fn(
+        c,
+        test_len,
+        transform_name,
+        transform,
+        pattern_name,
+        pattern_provider,
+        "rust_ipnsort_sort_zip_unstable",
+        |v: &mut [T]| {
+            let mut payload = make_payload(v.len());
+            ipnsort::sort_zip(v, &mut payload[..]);
+        },
+    );
+
+    bench_fn(
+        c,
+        test_len,
+        transform_name,
+        transform,
+        pattern_name,
+        pattern_provider,
+        "rust_ipnsort_sort_zip_stable",
+        |v: &mut [T]| {
+            let mut payload = make_payload(v.len());
+            ipnsort::stable::sort_zip(v, &mut payload[..]);
+        },
+    );
+
+    bench_fn(
+        c,
+        test_len,
+        transform_name,
+        transform,
+        pattern_name,
+        pattern_provider,
+        "rust_ipnsort_zip_tuples_unstable",
+        |v: &mut [T]| {
+            let mut payload = make_payload(v.len());
+            sort_zipped_tuples(v, &mut payload, |zipped| {
+                unstable::rust_ipnsort::sort_by(zipped, |a, b| a.0.cmp(&b.0))
+            });
+        },
+    );
+
+    bench_fn(
+        c,
+        test_len,
+        transform_name,
+        transform,
+        pattern_name,
+        pattern_provider,
+        "rust_std_zip_tuples_stable",
+        |v: &mut [T]| {
+            let mut payload = make_payload(v.len());
+            sort_zipped_tuples(v, &mut payload, |zipped| {
+                stable::rust_std::sort_by(zipped, |a, b| a.0.cmp(&b.0))
+            });
+        },
+    );
+}
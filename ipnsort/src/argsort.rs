@@ -0,0 +1,156 @@
+//! Sorting indices instead of elements, see [`crate::argsort`].
+
+use alloc::vec::Vec;
+use core::cell::Cell;
+use core::cmp::Ordering;
+use core::mem;
+
+/// Returns the indices `0..len`, which get sorted by the argsort functions.
+pub(crate) fn identity(len: usize) -> Vec<usize> {
+    (0..len).collect()
+}
+
+/// Turns `is_less` for elements of `v` into `is_less` for indices into `v`.
+///
+/// Only valid indices may be compared. That holds for the indices returned by [`identity`] as long
+/// as they are only reordered, which the sorts guarantee even if `is_less` panics or doesn't
+/// implement a total order.
+#[inline(always)]
+pub(crate) fn index_is_less<'a, T, F>(
+    v: &'a [T],
+    mut is_less: F,
+) -> impl FnMut(&usize, &usize) -> bool + 'a
+where
+    F: FnMut(&T, &T) -> bool + 'a,
+{
+    move |&a, &b| {
+        debug_assert!(a < v.len() && b < v.len());
+        // SAFETY: See the function comment.
+        unsafe { is_less(v.get_unchecked(a), v.get_unchecked(b)) }
+    }
+}
+
+/// Sorts `v` with the fallible `compare`, see [`crate::try_sort_by`] and
+/// [`crate::stable::try_sort_by`]. With `STABLE` equal elements are ordered by their index.
+///
+/// The indices are sorted instead of the elements, and the permutation is only applied if no
+/// error occurred. Once `compare` returned an error it isn't called again, and every further
+/// comparison of the index sort is answered with `false`. The results are no longer consistent
+/// then, which only the small-sorts that merge detect, by panicking. Those require `Freeze` types,
+/// so the indices are sorted as `Cell`s to avoid them.
+pub(crate) fn try_sort_by<T, E, F, const STABLE: bool>(v: &mut [T], mut compare: F) -> Result<(), E>
+where
+    F: FnMut(&T, &T) -> Result<Ordering, E>,
+{
+    // Sorting has no meaningful behavior on zero-sized types.
+    if mem::size_of::<T>() == 0 || v.len() < 2 {
+        return Ok(());
+    }
+
+    let mut perm = (0..v.len()).map(Cell::new).collect::<Vec<_>>();
+    let mut error = None;
+
+    // The `check_ord` checks are skipped on purpose, they would report the answers given after an
+    // error as violations.
+    crate::insertion_sort_or_quicksort(&mut perm, |a: &Cell<usize>, b: &Cell<usize>| {
+        if error.is_some() {
+            return false;
+        }
+
+        let (a, b) = (a.get(), b.get());
+        match compare(&v[a], &v[b]) {
+            Ok(Ordering::Less) => true,
+            Ok(Ordering::Equal) => STABLE && a < b,
+            Ok(Ordering::Greater) => false,
+            Err(err) => {
+                error = Some(err);
+                false
+            }
+        }
+    });
+
+    if let Some(err) = error {
+        return Err(err);
+    }
+
+    let mut perm = perm.into_iter().map(Cell::into_inner).collect::<Vec<_>>();
+    apply_permutation_in_place(v, &mut perm);
+    Ok(())
+}
+
+/// See [`crate::apply_permutation_in_place`].
+pub(crate) fn apply_permutation_in_place<T>(v: &mut [T], perm: &mut [usize]) {
+    assert_eq!(
+        perm.len(),
+        v.len(),
+        "the permutation has to be as long as the slice"
+    );
+
+    apply_permutation(perm, |a, b| v.swap(a, b));
+}
+
+/// Applies `perm` to everything `swap` swaps, such that the element at `i` afterwards is the one
+/// that was at `perm[i]`. `perm` is restored before returning, even if it turns out not to be a
+/// permutation of `0..perm.len()`, which panics.
+pub(crate) fn apply_permutation<F>(perm: &mut [usize], mut swap: F)
+where
+    F: FnMut(usize, usize),
+{
+    let len = perm.len();
+
+    // Visited entries of `perm` are marked by flipping all bits. Valid indices are smaller than
+    // `len <= isize::MAX`, so marked entries are the ones with the highest bit set.
+    const MARK_BIT: usize = !(isize::MAX as usize);
+    let is_marked = |i: usize| i & MARK_BIT != 0;
+
+    struct UnmarkOnDrop<'a> {
+        perm: &'a mut [usize],
+    }
+
+    impl Drop for UnmarkOnDrop<'_> {
+        fn drop(&mut self) {
+            for i in self.perm.iter_mut() {
+                if *i & MARK_BIT != 0 {
+                    *i = !*i;
+                }
+            }
+        }
+    }
+
+    // Out of range entries with the highest bit set would look like visited ones, so all entries
+    // are checked before any of them gets marked.
+    assert!(
+        perm.iter().all(|&i| i < len),
+        "perm is not a permutation of 0..{len}"
+    );
+
+    let guard = UnmarkOnDrop { perm };
+    let perm = &mut *guard.perm;
+
+    for start in 0..len {
+        if is_marked(perm[start]) {
+            continue;
+        }
+
+        // Walk the cycle containing `start`. Each step moves the element that belongs at `pos` to
+        // `pos`, the element from `start` travels along until the cycle closes.
+        let mut pos = start;
+        loop {
+            let next = perm[pos];
+            perm[pos] = !next;
+
+            if next == start {
+                break;
+            }
+
+            // Reaching a visited entry means some index appears twice.
+            assert!(
+                !is_marked(perm[next]),
+                "perm is not a permutation of 0..{len}"
+            );
+
+            swap(pos, next);
+            pos = next;
+        }
+    }
+}
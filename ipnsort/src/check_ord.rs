@@ -0,0 +1,109 @@
+//! Comparator consistency checks, used by the sorts with the `check_ord` feature.
+//!
+//! Elements move around while they are sorted, which makes it impossible to tell which elements a
+//! comparison was about. So instead the sorts sort the indices of the elements, like
+//! [`crate::argsort`], and apply the resulting permutation afterwards. Every comparison is then
+//! about two fixed indices into the unmodified slice. That allows checking the results of some of
+//! the comparisons against earlier ones, and reporting violations with the indices of the
+//! elements involved.
+//!
+//! Only some comparisons are checked, each against the other elements compared recently, so a
+//! broken `Ord` implementation can go unnoticed. But one that regularly produces inconsistent
+//! results will not.
+
+use crate::argsort;
+
+// Every `CHECK_INTERVAL`-th comparison is checked, has to be a power of two.
+const CHECK_INTERVAL: usize = 16;
+
+// The number of recently compared elements a checked comparison is combined with, to find
+// violations of transitivity. Each costs up to four extra comparisons.
+const RECENT_LEN: usize = 8;
+
+/// Sorts `v` by sorting its indices with `sort_indices`, with a checked version of `is_less`, and
+/// applying the resulting permutation. Panics on the first violation found, in which case `v` is
+/// left unmodified.
+pub(crate) fn sort<T, F, S>(v: &mut [T], is_less: F, sort_indices: S)
+where
+    F: FnMut(&T, &T) -> bool,
+    S: FnOnce(&mut [usize], &mut dyn FnMut(&usize, &usize) -> bool),
+{
+    let mut perm = argsort::identity(v.len());
+
+    let mut checker = OrdChecker {
+        v,
+        is_less,
+        compare_count: 0,
+        recent: [0; RECENT_LEN],
+    };
+    sort_indices(&mut perm, &mut |&a, &b| checker.is_less(a, b));
+
+    argsort::apply_permutation_in_place(v, &mut perm);
+}
+
+struct OrdChecker<'a, T, F> {
+    v: &'a [T],
+    is_less: F,
+    compare_count: usize,
+    // Ring buffer of the indices of recently compared elements.
+    recent: [usize; RECENT_LEN],
+}
+
+impl<T, F> OrdChecker<'_, T, F>
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    fn is_less(&mut self, a: usize, b: usize) -> bool {
+        let a_lt_b = (self.is_less)(&self.v[a], &self.v[b]);
+
+        self.compare_count += 1;
+        if a_lt_b && self.compare_count & (CHECK_INTERVAL - 1) == 0 {
+            self.check_less(a, b);
+        }
+
+        self.recent[self.compare_count % RECENT_LEN] = a;
+
+        a_lt_b
+    }
+
+    /// Checks that `is_less(v[a], v[b]) == true` is consistent with `is_less` for the same and the
+    /// recently compared elements.
+    #[cold]
+    fn check_less(&mut self, a: usize, b: usize) {
+        if a == b {
+            panic!("Ord violation: is_less(v[{a}], v[{a}]) is true");
+        }
+
+        if (self.is_less)(&self.v[b], &self.v[a]) {
+            panic!(
+                "Ord violation: is_less(v[{a}], v[{b}]) and is_less(v[{b}], v[{a}]) are both true"
+            );
+        }
+
+        let recent_len = self.compare_count.min(RECENT_LEN);
+        for i in 0..recent_len {
+            let c = self.recent[i];
+            if c == a || c == b {
+                continue;
+            }
+
+            // v[a] < v[b] < v[c] requires v[a] < v[c].
+            if (self.is_less)(&self.v[b], &self.v[c]) && !(self.is_less)(&self.v[a], &self.v[c]) {
+                panic_on_intransitive(a, b, c);
+            }
+
+            // v[c] < v[a] < v[b] requires v[c] < v[b].
+            if (self.is_less)(&self.v[c], &self.v[a]) && !(self.is_less)(&self.v[c], &self.v[b]) {
+                panic_on_intransitive(c, a, b);
+            }
+        }
+    }
+}
+
+#[cold]
+fn panic_on_intransitive(a: usize, b: usize, c: usize) -> ! {
+    panic!(
+        "Ord violation: is_less(v[{a}], v[{b}]) and is_less(v[{b}], v[{c}]) are true, but \
+         is_less(v[{a}], v[{c}]) is false"
+    );
+}
@@ -0,0 +1,176 @@
+//! Sorting and removing duplicates in the same pass, see [`crate::sort_dedup`].
+
+use core::mem;
+
+use crate::quicksort::partition;
+use crate::smallsort::{insertion_sort_shift_left, SmallSortImpl};
+
+// Slices of up to this length get sorted using insertion sort.
+const MAX_LEN_ALWAYS_INSERTION_SORT: usize = 20;
+
+/// Sorts `v` and moves one element of every group of equal elements to the front. Returns the
+/// number of unique elements `n`, `v[..n]` is sorted and contains no duplicates, the order of the
+/// remaining elements is unspecified.
+pub(crate) fn unstable_sort_dedup<T, F>(v: &mut [T], mut is_less: F) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let len = v.len();
+
+    if len < 2 {
+        return len;
+    }
+
+    // Zero-sized types don't need to be sorted, but they may still compare equal.
+    if mem::size_of::<T>() == 0 {
+        return dedup_sorted(v, None, &mut is_less);
+    }
+
+    if len <= MAX_LEN_ALWAYS_INSERTION_SORT {
+        insertion_sort_shift_left(v, 1, &mut is_less);
+        return dedup_sorted(v, None, &mut is_less);
+    }
+
+    let (streak_end, was_reversed) = crate::find_streak(v, &mut is_less);
+    if streak_end == len {
+        if was_reversed {
+            // A strictly descending slice can't contain duplicates.
+            v.reverse();
+            return len;
+        }
+
+        return dedup_sorted(v, None, &mut is_less);
+    }
+
+    // Limit the number of imbalanced partitions to `2 * floor(log2(len))`.
+    // The binary OR by one is used to eliminate the zero-check in the logarithm.
+    let limit = 2 * (len | 1).ilog2();
+
+    quicksort_dedup(v, &mut is_less, None, limit)
+}
+
+/// Sorts and deduplicates `v` recursively, see [`unstable_sort_dedup`] for the return value.
+///
+/// Unlike [`crate::quicksort::quicksort`] both sides are handled recursively, because the unique
+/// elements of the right side have to be moved next to the ones of the left side afterwards. The
+/// recursion depth is still bounded by `limit`.
+///
+/// If the slice had a predecessor in the original array, it is specified as `ancestor_pivot`. All
+/// elements of `v` are greater than or equal to it, the ones equal to it are duplicates.
+fn quicksort_dedup<T, F>(
+    v: &mut [T],
+    is_less: &mut F,
+    ancestor_pivot: Option<&T>,
+    limit: u32,
+) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    if v.len() <= T::SMALL_SORT_THRESHOLD {
+        report!(small_sort(v.len()));
+        T::small_sort(v, is_less);
+        return dedup_sorted(v, ancestor_pivot, is_less);
+    }
+
+    // If too many bad pivot choices were made, simply fall back to heapsort in order to
+    // guarantee `O(n * log(n))` worst-case.
+    if limit == 0 {
+        // SAFETY: We assume the `small_sort` threshold is at least 1.
+        unsafe {
+            crate::heapsort::heapsort(v, is_less);
+        }
+        return dedup_sorted(v, ancestor_pivot, is_less);
+    }
+
+    let limit = limit - 1;
+
+    // Choose a pivot and try guessing whether the slice is already sorted.
+    let pivot_pos = crate::pivot::choose_pivot(v, is_less);
+
+    // If the chosen pivot is equal to the predecessor, then it's the smallest element in the
+    // slice. Partition the slice into elements equal to and elements greater than the pivot. All
+    // elements equal to the pivot are duplicates of the predecessor and can be discarded right
+    // away.
+    if let Some(p) = ancestor_pivot {
+        // SAFETY: We assume choose_pivot yields an in-bounds position.
+        if !is_less(p, unsafe { v.get_unchecked(pivot_pos) }) {
+            let mid = partition(v, pivot_pos, &mut |a, b| !is_less(b, a));
+            report!(equal_partition(v.len(), mid + 1));
+
+            let num_equal = mid + 1;
+            let num_unique = quicksort_dedup(&mut v[num_equal..], is_less, None, limit);
+            move_to_front(v, num_equal, num_unique);
+
+            return num_unique;
+        }
+    }
+
+    // Partition the slice.
+    let mid = partition(v, pivot_pos, is_less);
+
+    // Split the slice into `left`, `pivot`, and `right`.
+    let (left, right) = v.split_at_mut(mid);
+    let (pivot, right) = right.split_at_mut(1);
+    let pivot = &pivot[0];
+
+    let num_unique_left = quicksort_dedup(left, is_less, ancestor_pivot, limit);
+    let num_unique_right = quicksort_dedup(right, is_less, Some(pivot), limit);
+
+    // Close the gaps left by the duplicates, the pivot follows the unique elements of the left
+    // side, and is in turn followed by the unique elements of the right side.
+    v.swap(num_unique_left, mid);
+    move_to_front(
+        &mut v[(num_unique_left + 1)..],
+        mid - num_unique_left,
+        num_unique_right,
+    );
+
+    num_unique_left + 1 + num_unique_right
+}
+
+/// Moves one element of every group of equal elements in the sorted slice `v` to the front, and
+/// returns their number. Elements equal to `ancestor_pivot` are discarded as well.
+fn dedup_sorted<T, F>(v: &mut [T], ancestor_pivot: Option<&T>, is_less: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let len = v.len();
+
+    // The slice is sorted and greater than or equal to `ancestor_pivot`, so any elements equal to
+    // it are at the start.
+    let start = match ancestor_pivot {
+        Some(p) => v.iter().take_while(|elem| !is_less(p, elem)).count(),
+        None => 0,
+    };
+
+    if start == len {
+        return 0;
+    }
+
+    v.swap(0, start);
+    let mut num_unique = 1;
+
+    for i in (start + 1)..len {
+        // SAFETY: `num_unique <= i < len`.
+        let is_unique = unsafe { is_less(v.get_unchecked(num_unique - 1), v.get_unchecked(i)) };
+
+        if is_unique {
+            v.swap(num_unique, i);
+            num_unique += 1;
+        }
+    }
+
+    num_unique
+}
+
+/// Moves the `count` elements starting at `offset` to the front of `v`, preserving their order.
+/// The order of the elements they displace is unspecified afterwards.
+fn move_to_front<T>(v: &mut [T], offset: usize, count: usize) {
+    if offset == 0 {
+        return;
+    }
+
+    for i in 0..count {
+        v.swap(i, offset + i);
+    }
+}
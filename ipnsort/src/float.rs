@@ -0,0 +1,59 @@
+//! Sorting floats by their IEEE 754 total order, see [`crate::sort_floats`].
+//!
+//! The total order of floats matches the order of their bits interpreted as signed integers, after
+//! flipping all bits except the sign bit of negative values. The floats are mapped in-place to
+//! those integers, sorted as integers and mapped back, which lets them use the integer specific
+//! small-sorts and partitions.
+
+use core::slice;
+
+/// A floating point type that can be sorted with [`crate::sort_floats`]. Implemented for [`f32`]
+/// and [`f64`].
+pub trait Float: private::Sealed {}
+
+impl Float for f32 {}
+impl Float for f64 {}
+
+/// Sorts `v` by the total order of `T`.
+pub(crate) fn sort_total_order<T: Float>(v: &mut [T]) {
+    T::sort_total_order(v);
+}
+
+mod private {
+    pub trait Sealed: Sized {
+        fn sort_total_order(v: &mut [Self]);
+    }
+}
+
+macro_rules! float_impl {
+    ($t:ty, $int:ty, $uint:ty) => {
+        impl private::Sealed for $t {
+            fn sort_total_order(v: &mut [Self]) {
+                let len = v.len();
+
+                // SAFETY: The float and integer types have the same size and alignment, and every
+                // bit pattern is valid for both. `v` is not used again while `ints` is alive.
+                let ints = unsafe { slice::from_raw_parts_mut(v.as_mut_ptr() as *mut $int, len) };
+
+                // Flipping all bits except the sign bit of negative values is its own inverse.
+                #[inline(always)]
+                fn flip(x: $int) -> $int {
+                    x ^ (((x >> (<$int>::BITS - 1)) as $uint) >> 1) as $int
+                }
+
+                for x in ints.iter_mut() {
+                    *x = flip(*x);
+                }
+
+                crate::sort(ints);
+
+                for x in ints.iter_mut() {
+                    *x = flip(*x);
+                }
+            }
+        }
+    };
+}
+
+float_impl!(f32, i32, u32);
+float_impl!(f64, i64, u64);
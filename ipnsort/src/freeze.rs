@@ -0,0 +1,18 @@
+// Lives in its own module, because auto traits and negative impls are rejected by stable Rust even
+// when they are configured out.
+
+// // #[rustc_unsafe_specialization_marker]
+// trait Freeze {}
+
+// Can the type have interior mutability, this is checked by testing if T is Freeze. If the type can
+// have interior mutability it may alter itself during comparison in a way that must be observed
+// after the sort operation concludes. Otherwise a type like Mutex<Option<Box<str>>> could lead to
+// double free.
+pub(crate) unsafe auto trait Freeze {}
+
+impl<T: ?Sized> !Freeze for core::cell::UnsafeCell<T> {}
+unsafe impl<T: ?Sized> Freeze for core::marker::PhantomData<T> {}
+unsafe impl<T: ?Sized> Freeze for *const T {}
+unsafe impl<T: ?Sized> Freeze for *mut T {}
+unsafe impl<T: ?Sized> Freeze for &T {}
+unsafe impl<T: ?Sized> Freeze for &mut T {}
@@ -21,6 +21,8 @@ where
 
     let len = v.len();
 
+    report!(heapsort_fallback(len));
+
     // Build the heap in linear time.
     for i in (0..len / 2).rev() {
         sift_down(v, i, is_less);
@@ -0,0 +1,238 @@
+//! Observe what the sort implementations do internally, e.g. to analyze pivot quality.
+//!
+//! An observer is installed for the current thread with [`observe`], while it is installed the
+//! sort internals report events to it. Without the `instrument` feature the reporting is compiled
+//! out completely.
+//!
+//! ```
+//! let mut v = [5, 4, 1, 3, 2].repeat(100);
+//! let mut stats = ipnsort::instrument::SortStats::default();
+//!
+//! ipnsort::instrument::observe(&mut stats, || ipnsort::sort(&mut v));
+//! assert!(stats.partitions > 0);
+//! ```
+
+use core::cell::Cell;
+use core::fmt;
+
+/// Receives events from the sort internals. All methods do nothing by default, implementations
+/// only need to override the events they are interested in.
+///
+/// Events are reported by every algorithm that uses the respective building block, e.g. the
+/// stable sort reports streaks and `select_nth_unstable` reports partitions.
+pub trait SortObserver {
+    /// The quicksort loop started on a sub-slice of `len` elements. `depth` is the number of
+    /// recursive quicksort calls it is nested in, starting at 0.
+    fn quicksort(&mut self, len: usize, depth: u32) {
+        let _ = (len, depth);
+    }
+
+    /// The pivot at `pivot_pos` was chosen from a sub-slice of `len` elements.
+    fn choose_pivot(&mut self, len: usize, pivot_pos: usize) {
+        let _ = (len, pivot_pos);
+    }
+
+    /// A sub-slice of `len` elements was partitioned, `num_lt` elements ended up on the left side.
+    fn partition(&mut self, len: usize, num_lt: usize) {
+        let _ = (len, num_lt);
+    }
+
+    /// The chosen pivot was equal to the ancestor pivot, so the sub-slice of `len` elements was
+    /// partitioned into elements equal to and greater than the pivot. `num_equal` elements are
+    /// equal to the pivot and are done. This follows the `partition` event of the same sub-slice,
+    /// in which `num_lt` counts the elements equal to the pivot other than the pivot itself.
+    fn equal_partition(&mut self, len: usize, num_equal: usize) {
+        let _ = (len, num_equal);
+    }
+
+    /// Too many imbalanced partitions were made, and the sub-slice of `len` elements is sorted
+    /// with heapsort.
+    fn heapsort_fallback(&mut self, len: usize) {
+        let _ = len;
+    }
+
+    /// A sub-slice of `len` elements was sorted with the small-sort.
+    fn small_sort(&mut self, len: usize) {
+        let _ = len;
+    }
+
+    /// A presorted streak of `streak_len` elements was found at the start of a slice of `len`
+    /// elements. `was_reversed` denotes a strictly descending streak.
+    fn streak(&mut self, len: usize, streak_len: usize, was_reversed: bool) {
+        let _ = (len, streak_len, was_reversed);
+    }
+}
+
+/// Runs `f` with `observer` installed for the current thread, and returns the result of `f`.
+///
+/// Events are only reported for work done on the current thread, e.g. the parts of a `par_sort`
+/// that run on other threads are not observed. If the observer itself sorts, those sorts are not
+/// observed either. Nested calls replace the outer observer for the duration of the inner call.
+pub fn observe<O, R>(observer: &mut O, f: impl FnOnce() -> R) -> R
+where
+    O: SortObserver,
+{
+    let observer: *mut (dyn SortObserver + '_) = observer;
+    // SAFETY: Only the lifetime is erased. The pointer is removed from `OBSERVER` again when
+    // `_guard` is dropped, which happens before `observer` goes out of scope, even if `f` panics.
+    let observer = unsafe {
+        core::mem::transmute::<*mut (dyn SortObserver + '_), *mut (dyn SortObserver + 'static)>(
+            observer,
+        )
+    };
+
+    let _guard = ResetOnDrop {
+        observer: OBSERVER.with(|cell| cell.replace(Some(observer))),
+        depth: DEPTH.with(|cell| cell.replace(0)),
+    };
+
+    f()
+}
+
+/// Summary of the events reported while it was installed as observer.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SortStats {
+    /// Number of quicksort loops that were started.
+    pub quicksort_calls: usize,
+    /// Deepest nesting of recursive quicksort calls.
+    pub max_depth: u32,
+    /// Number of partitions, including equal partitions.
+    pub partitions: usize,
+    /// Number of elements that were partitioned, summed over all partitions.
+    pub partitioned_elements: usize,
+    /// Sum of the imbalance of each partition, see [`SortStats::mean_partition_imbalance`].
+    pub partition_imbalance_sum: f64,
+    /// Number of partitions into elements equal to and greater than the ancestor pivot.
+    pub equal_partitions: usize,
+    /// Number of times heapsort was used as fallback.
+    pub heapsort_fallbacks: usize,
+    /// Number of sub-slices that were sorted with the small-sort.
+    pub small_sorts: usize,
+    /// Number of elements that were sorted with the small-sort.
+    pub small_sorted_elements: usize,
+    /// Number of streak detections.
+    pub streaks: usize,
+    /// Number of streak detections that found the whole slice to be presorted.
+    pub full_streaks: usize,
+}
+
+impl SortStats {
+    /// Returns how far the partitions are from the ideal of halving the sub-slice on average. A
+    /// partition that puts `num_lt` of `len` elements on the left side has an imbalance of
+    /// `(len / 2) / min(num_lt, len - num_lt)`, where 1.0 is a perfect split.
+    pub fn mean_partition_imbalance(&self) -> f64 {
+        if self.partitions == 0 {
+            return 1.0;
+        }
+
+        self.partition_imbalance_sum / self.partitions as f64
+    }
+}
+
+impl SortObserver for SortStats {
+    fn quicksort(&mut self, _len: usize, depth: u32) {
+        self.quicksort_calls += 1;
+        self.max_depth = self.max_depth.max(depth);
+    }
+
+    fn partition(&mut self, len: usize, num_lt: usize) {
+        let len_div_2 = len as f64 / 2.0;
+        let smaller_side = num_lt.min(len - num_lt).max(1);
+
+        self.partitions += 1;
+        self.partitioned_elements += len;
+        self.partition_imbalance_sum += len_div_2 / smaller_side as f64;
+    }
+
+    fn equal_partition(&mut self, _len: usize, _num_equal: usize) {
+        self.equal_partitions += 1;
+    }
+
+    fn heapsort_fallback(&mut self, _len: usize) {
+        self.heapsort_fallbacks += 1;
+    }
+
+    fn small_sort(&mut self, len: usize) {
+        self.small_sorts += 1;
+        self.small_sorted_elements += len;
+    }
+
+    fn streak(&mut self, len: usize, streak_len: usize, _was_reversed: bool) {
+        self.streaks += 1;
+        self.full_streaks += (streak_len == len) as usize;
+    }
+}
+
+impl fmt::Display for SortStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "quicksort_calls: {}", self.quicksort_calls)?;
+        writeln!(f, "max_depth: {}", self.max_depth)?;
+        writeln!(f, "partitions: {}", self.partitions)?;
+        writeln!(f, "partitioned_elements: {}", self.partitioned_elements)?;
+        writeln!(
+            f,
+            "mean_partition_imbalance: {:.2}",
+            self.mean_partition_imbalance()
+        )?;
+        writeln!(f, "equal_partitions: {}", self.equal_partitions)?;
+        writeln!(f, "heapsort_fallbacks: {}", self.heapsort_fallbacks)?;
+        writeln!(f, "small_sorts: {}", self.small_sorts)?;
+        writeln!(f, "small_sorted_elements: {}", self.small_sorted_elements)?;
+        writeln!(f, "streaks: {}", self.streaks)?;
+        write!(f, "full_streaks: {}", self.full_streaks)
+    }
+}
+
+// --- IMPL ---
+
+std::thread_local! {
+    static OBSERVER: Cell<Option<*mut dyn SortObserver>> = const { Cell::new(None) };
+    static DEPTH: Cell<u32> = const { Cell::new(0) };
+}
+
+struct ResetOnDrop {
+    observer: Option<*mut dyn SortObserver>,
+    depth: u32,
+}
+
+impl Drop for ResetOnDrop {
+    fn drop(&mut self) {
+        OBSERVER.with(|cell| cell.set(self.observer));
+        DEPTH.with(|cell| cell.set(self.depth));
+    }
+}
+
+/// Calls `f` with the observer installed for the current thread, if there is one.
+pub(crate) fn report(f: impl FnOnce(&mut dyn SortObserver)) {
+    // The observer is taken out for the duration of the call, so that sorts done by the observer
+    // itself can't create a second mutable reference to it.
+    let Some(observer) = OBSERVER.with(|cell| cell.take()) else {
+        return;
+    };
+
+    let _guard = ResetOnDrop {
+        observer: Some(observer),
+        depth: DEPTH.with(|cell| cell.get()),
+    };
+
+    // SAFETY: `observe` guarantees that the observer outlives its installation, and taking it out
+    // of `OBSERVER` guarantees that this is the only reference to it.
+    f(unsafe { &mut *observer });
+}
+
+/// Reports a `quicksort` event and returns a guard, which tracks the recursion depth until it is
+/// dropped.
+pub(crate) fn enter_quicksort(len: usize) -> impl Drop {
+    let depth = DEPTH.with(|cell| cell.replace(cell.get() + 1));
+    report(|observer| observer.quicksort(len, depth));
+
+    struct LeaveOnDrop;
+
+    impl Drop for LeaveOnDrop {
+        fn drop(&mut self) {
+            DEPTH.with(|cell| cell.set(cell.get().saturating_sub(1)));
+        }
+    }
+
+    LeaveOnDrop
+}
@@ -0,0 +1,34 @@
+//! Stable stand-ins for the `core::intrinsics` used by the crate, see the `nightly` feature.
+
+/// Aborts the process.
+///
+/// `core` offers no way to abort on stable Rust, panicking while already unwinding from a panic
+/// is guaranteed to abort though.
+#[cold]
+#[inline(never)]
+pub(crate) fn abort() -> ! {
+    struct PanicOnDrop;
+
+    impl Drop for PanicOnDrop {
+        fn drop(&mut self) {
+            panic!("ipnsort logic bug");
+        }
+    }
+
+    let _guard = PanicOnDrop;
+    panic!("ipnsort logic bug");
+}
+
+#[inline(always)]
+pub(crate) fn likely(b: bool) -> bool {
+    b
+}
+
+/// SAFETY: The caller has to guarantee that `b` is true.
+#[inline(always)]
+pub(crate) unsafe fn assume(b: bool) {
+    if !b {
+        // SAFETY: See function safety comment.
+        unsafe { core::hint::unreachable_unchecked() };
+    }
+}
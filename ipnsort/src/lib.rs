@@ -1,27 +1,84 @@
 //! Instruction-Parallel-Network Unstable Sort, ipnsort by Lukas Bergdoll
-#![allow(incomplete_features)]
-#![feature(
-    ptr_sub_ptr,
-    maybe_uninit_slice,
-    auto_traits,
-    negative_impls,
-    specialization,
-    const_trait_impl,
-    inline_const,
-    core_intrinsics,
-    sized_type_properties,
-    generic_const_exprs
+//!
+//! With the `nightly` feature the type specific small-sorts and partitions are selected via
+//! specialization. Without it the selection is done based on the size of the type and whether it
+//! needs to be dropped, which is all stable Rust can tell about a generic `T`.
+//!
+//! With the `opt_size` feature the sorts are built for small code size instead of speed. All types
+//! share the simplest small-sort and partition, without the sorting-networks.
+//!
+//! With the `check_ord` feature the sorts check the results of the comparison function for
+//! consistency, and panic with the indices of the elements involved on the first violation found.
+//! That makes them allocate and much slower, so it's meant for debugging only.
+#![no_std]
+#![cfg_attr(feature = "nightly", allow(incomplete_features))]
+#![cfg_attr(
+    feature = "nightly",
+    feature(
+        ptr_sub_ptr,
+        auto_traits,
+        negative_impls,
+        specialization,
+        core_intrinsics,
+        generic_const_exprs
+    )
 )]
+#![cfg_attr(feature = "simd", feature(portable_simd, unboxed_closures, fn_traits))]
 
-use core::cmp::Ordering;
-use core::intrinsics;
-use core::mem::{self, ManuallyDrop, SizedTypeProperties};
+extern crate alloc;
+
+#[cfg(any(feature = "parallel", feature = "instrument"))]
+extern crate std;
+
+use alloc::vec::Vec;
+use core::cmp::{self, Ordering};
+use core::mem::{self, ManuallyDrop};
 use core::ptr;
 
+#[cfg(feature = "nightly")]
+use core::intrinsics;
+
+#[cfg(feature = "nightly")]
+use freeze::Freeze;
+
+/// Reports an event to the observer installed with [`instrument::observe`]. Expands to nothing
+/// without the `instrument` feature.
+macro_rules! report {
+    ($event:ident($($arg:expr),* $(,)?)) => {
+        #[cfg(feature = "instrument")]
+        crate::instrument::report(|observer| observer.$event($($arg),*));
+    };
+}
+
+mod argsort;
+#[cfg(feature = "check_ord")]
+mod check_ord;
+mod dedup;
+mod float;
+#[cfg(feature = "nightly")]
+mod freeze;
 mod heapsort;
+#[cfg(feature = "instrument")]
+pub mod instrument;
+#[cfg(not(feature = "nightly"))]
+mod intrinsics;
+mod network;
+#[cfg(feature = "parallel")]
+mod parallel;
 mod pivot;
 mod quicksort;
+#[cfg(feature = "radix")]
+mod radix;
+mod select;
+#[cfg(feature = "simd")]
+pub mod simd;
 mod smallsort;
+mod zip;
+
+pub mod stable;
+
+pub use float::Float;
+pub use zip::Payloads;
 
 /// Sorts the slice, but might not preserve the order of equal elements.
 ///
@@ -39,6 +96,14 @@ mod smallsort;
 /// It is typically faster than stable sorting, except in a few special cases, e.g., when the
 /// slice consists of several concatenated sorted sequences.
 ///
+/// With the `radix` feature, slices of primitive integers are sorted without comparisons. `u8`
+/// and `i8` use counting sort, wider integers use an in-place radix sort. Slices of at least 65536
+/// `u16` or `i16` use counting sort too, which allocates its count table. Short and fully
+/// presorted slices still use the comparison sort.
+///
+/// With the `simd` feature, slices of `i32`, `u32` and `u64` are partitioned with portable SIMD
+/// instructions, see [`simd::partition`].
+///
 /// # Examples
 ///
 /// ```
@@ -54,7 +119,22 @@ pub fn sort<T>(arr: &mut [T])
 where
     T: Ord,
 {
-    unstable_sort(arr, |a, b| a.lt(b));
+    #[cfg(feature = "radix")]
+    <T as radix::SortImpl>::sort(arr);
+
+    #[cfg(not(feature = "radix"))]
+    unstable_sort(arr, ord_is_less());
+}
+
+/// `is_less` for the natural order of `T`, as used by [`sort`]. With the `simd` feature it can be
+/// told apart from other comparators, which enables the SIMD partition for primitive types.
+#[inline(always)]
+fn ord_is_less<T: Ord>() -> impl FnMut(&T, &T) -> bool {
+    #[cfg(feature = "simd")]
+    return simd::OrdLess;
+
+    #[cfg(not(feature = "simd"))]
+    |a: &T, b: &T| a.lt(b)
 }
 
 /// Sorts the slice with a comparator function, but might not preserve the order of equal
@@ -111,31 +191,889 @@ where
     unstable_sort(arr, |a, b| compare(a, b) == Ordering::Less);
 }
 
+/// Sorts the slice with a key extraction function, but might not preserve the order of equal
+/// elements.
+///
+/// This sort is unstable (i.e., may reorder equal elements), in-place
+/// (i.e., does not allocate), and *O*(*m* \* *n* \* log(*n*)) worst-case, where the key function is
+/// *O*(*m*).
+///
+/// Due to its key calling strategy, [`sort_by_key`] is likely to be slower than
+/// [`sort_by_cached_key`] in cases where the key function is expensive.
+///
+/// # Current implementation
+///
+/// See [`sort`].
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [-5i32, 4, 1, -3, 2];
+///
+/// ipnsort::sort_by_key(&mut v, |k| k.abs());
+/// assert!(v == [1, 2, -3, 4, -5]);
+/// ```
+#[inline(always)]
+pub fn sort_by_key<T, K, F>(arr: &mut [T], mut f: F)
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    unstable_sort(arr, |a, b| f(a).lt(&f(b)));
+}
+
+/// Sorts the slice with a key extraction function, preserving the order of equal elements.
+///
+/// During sorting, the key function is called at most once per element, by using temporary
+/// storage to remember the results of key evaluation. The order of calls to the key function is
+/// unspecified and may change in future versions of this crate.
+///
+/// This sort is stable (i.e., does not reorder equal elements) and *O*(*m* \* *n* + *n* \*
+/// log(*n*)) worst-case, where the key function is *O*(*m*).
+///
+/// For simple key functions (e.g., functions that are property accesses or basic operations),
+/// [`sort_by_key`] is likely to be faster.
+///
+/// # Current implementation
+///
+/// The keys are computed once into a side buffer of `(key, index)` pairs, which is sorted with
+/// [`sort`]. The indices are unique, so sorting the pairs is stable with respect to the original
+/// slice even though the sort itself is unstable. Finally the original slice is permuted in-place
+/// by following the sorted indices.
+///
+/// In the worst case, the algorithm allocates temporary storage in a `Vec<(K, usize)>` the
+/// length of the slice.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [-5i32, 4, 32, -3, 2];
+///
+/// ipnsort::sort_by_cached_key(&mut v, |k| k.to_string());
+/// assert!(v == [-3, -5, 2, 32, 4]);
+/// ```
+pub fn sort_by_cached_key<T, K, F>(arr: &mut [T], f: F)
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    // Helper macro for indexing our vector by the smallest possible type, to reduce allocation.
+    macro_rules! sort_by_key {
+        ($t:ty, $slice:ident, $f:ident) => {{
+            let mut indices: Vec<_> = $slice
+                .iter()
+                .map($f)
+                .enumerate()
+                .map(|(i, k)| (k, i as $t))
+                .collect();
+            // The elements of `indices` are unique, as they are indexed, so any sort will be
+            // stable with respect to the original slice. We use the unstable sort here because it
+            // requires no additional memory allocation.
+            sort(&mut indices);
+            for i in 0..$slice.len() {
+                let mut index = indices[i].1;
+                while (index as usize) < i {
+                    index = indices[index as usize].1;
+                }
+                indices[i].1 = index;
+                $slice.swap(i, index as usize);
+            }
+        }};
+    }
+
+    let len = arr.len();
+    if len < 2 {
+        return;
+    }
+
+    let sz_u8 = mem::size_of::<(K, u8)>();
+    let sz_u16 = mem::size_of::<(K, u16)>();
+    let sz_u32 = mem::size_of::<(K, u32)>();
+    let sz_usize = mem::size_of::<(K, usize)>();
+
+    if sz_u8 < sz_u16 && len <= (u8::MAX as usize) {
+        return sort_by_key!(u8, arr, f);
+    }
+    if sz_u16 < sz_u32 && len <= (u16::MAX as usize) {
+        return sort_by_key!(u16, arr, f);
+    }
+    if sz_u32 < sz_usize && len <= (u32::MAX as usize) {
+        return sort_by_key!(u32, arr, f);
+    }
+    sort_by_key!(usize, arr, f)
+}
+
+/// Sorts the slice with a fallible comparator function, but might not preserve the order of equal
+/// elements.
+///
+/// Sorting stops at the first error returned by `compare`, `compare` is not called again and the
+/// error is returned. In that case the slice is left in its original order. Otherwise this sorts
+/// like [`sort_by`], see there for the requirements on the comparator function.
+///
+/// This sort is unstable (i.e., may reorder equal elements) and *O*(*n* \* log(*n*)) worst-case.
+/// It allocates `arr.len()` indices.
+///
+/// # Current implementation
+///
+/// The indices of the elements are sorted like with [`argsort_by`], and the resulting permutation
+/// is only applied to the slice once all comparisons succeeded. After an error the remaining
+/// comparisons of the index sort are answered without calling `compare`, which ends it quickly.
+/// The `check_ord` feature does not check this sort.
+///
+/// # Examples
+///
+/// ```
+/// use core::num::ParseIntError;
+///
+/// let by_value = |a: &&str, b: &&str| -> Result<_, ParseIntError> {
+///     Ok(a.parse::<i32>()?.cmp(&b.parse::<i32>()?))
+/// };
+///
+/// let mut v = ["10", "2", "-3"];
+/// assert!(ipnsort::try_sort_by(&mut v, by_value).is_ok());
+/// assert!(v == ["-3", "2", "10"]);
+///
+/// let mut v = ["10", "two", "-3"];
+/// assert!(ipnsort::try_sort_by(&mut v, by_value).is_err());
+/// assert!(v == ["10", "two", "-3"]);
+/// ```
+#[inline]
+pub fn try_sort_by<T, E, F>(arr: &mut [T], compare: F) -> Result<(), E>
+where
+    F: FnMut(&T, &T) -> Result<Ordering, E>,
+{
+    argsort::try_sort_by::<T, E, F, false>(arr, compare)
+}
+
+/// Sorts the fixed-size array, but might not preserve the order of equal elements.
+///
+/// This sort is unstable (i.e., may reorder equal elements), in-place (i.e., does not allocate),
+/// and for arrays of up to 32 elements performs a fixed sequence of branchless compare-and-swap
+/// operations, which makes it a good fit for sorting many small arrays of cheap to compare types.
+///
+/// # Current implementation
+///
+/// Arrays of up to 32 elements are sorted with sorting networks, which perform a fixed sequence of
+/// compare-and-swap operations that only depends on `N`. For most `N` the network with the
+/// smallest known number of comparators is used, for 22 to 26 and 28 elements the used networks
+/// take 1 to 3 comparators more. Longer arrays are sorted with insertion sort.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [-5, 4, 1, -3, 2];
+///
+/// ipnsort::sort_array(&mut v);
+/// assert!(v == [-5, -3, 1, 2, 4]);
+/// ```
+#[inline]
+pub fn sort_array<T, const N: usize>(arr: &mut [T; N])
+where
+    T: Ord,
+{
+    network::sort_array(arr, &mut |a: &T, b: &T| a.lt(b));
+}
+
+/// Sorts the fixed-size array with a comparator function, but might not preserve the order of
+/// equal elements.
+///
+/// See [`sort_by`] for the requirements on the comparator function and [`sort_array`] for the
+/// current implementation.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [5, 4, 1, 3, 2];
+///
+/// ipnsort::sort_array_by(&mut v, |a, b| b.cmp(a));
+/// assert!(v == [5, 4, 3, 2, 1]);
+/// ```
+#[inline]
+pub fn sort_array_by<T, F, const N: usize>(arr: &mut [T; N], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    network::sort_array(arr, &mut |a: &T, b: &T| compare(a, b) == Ordering::Less);
+}
+
+/// Sorts the slice, whose first `sorted_prefix_len` elements are already sorted, but might not
+/// preserve the order of equal elements.
+///
+/// This is meant for appending a batch of new elements to a sorted slice. Only the unsorted tail
+/// is sorted, and then merged with the prefix, which is *O*(*n* + *m* \* log(*m*)) for a tail of
+/// length *m*, instead of *O*(*n* \* log(*n*)). If the prefix isn't actually sorted, the order of
+/// the elements afterwards is unspecified.
+///
+/// # Current implementation
+///
+/// The tail is sorted with [`sort`]. The merge copies the shorter of the two sorted parts into a
+/// buffer, which is bounded to a few megabytes. If it can't hold the shorter part, or the buffer
+/// can't be allocated, the merge falls back to rotating parts of the slice in-place, which is
+/// *O*(*n* \* log(*n*)) worst-case.
+///
+/// # Panics
+///
+/// Panics if `sorted_prefix_len` is greater than the length of the slice.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = vec![1, 3, 5, 7];
+/// v.extend([6, 2]);
+///
+/// ipnsort::sort_appended(&mut v, 4);
+/// assert!(v == [1, 2, 3, 5, 6, 7]);
+/// ```
+#[inline]
+pub fn sort_appended<T>(arr: &mut [T], sorted_prefix_len: usize)
+where
+    T: Ord,
+{
+    unstable_sort_appended(arr, sorted_prefix_len, |a, b| a.lt(b));
+}
+
+/// Sorts the slice with a comparator function, whose first `sorted_prefix_len` elements are
+/// already sorted by it, but might not preserve the order of equal elements.
+///
+/// See [`sort_by`] for the requirements on the comparator function and [`sort_appended`] for the
+/// current implementation.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [7, 5, 3, 1, 2, 6];
+///
+/// ipnsort::sort_appended_by(&mut v, 4, |a, b| b.cmp(a));
+/// assert!(v == [7, 6, 5, 3, 2, 1]);
+/// ```
+#[inline]
+pub fn sort_appended_by<T, F>(arr: &mut [T], sorted_prefix_len: usize, mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    unstable_sort_appended(arr, sorted_prefix_len, |a, b| {
+        compare(a, b) == Ordering::Less
+    });
+}
+
+/// Sorts the slice of floats by their IEEE 754 total order, but might not preserve the order of
+/// equal elements.
+///
+/// The order is the same as [`f64::total_cmp`]:
+///
+/// * negative quiet NaN
+/// * negative signaling NaN
+/// * negative infinity
+/// * negative numbers
+/// * negative subnormal numbers
+/// * negative zero
+/// * positive zero
+/// * positive subnormal numbers
+/// * positive numbers
+/// * positive infinity
+/// * positive signaling NaN
+/// * positive quiet NaN
+///
+/// NaNs are ordered by their sign bit and payload, so a NaN can end up at either end of the slice.
+/// E.g. [`f64::NAN`] is positive, but the sign of NaNs produced by arithmetic depends on the
+/// platform.
+///
+/// This sort is unstable (i.e., may reorder equal elements), in-place
+/// (i.e., does not allocate), and *O*(*n* \* log(*n*)) worst-case.
+///
+/// # Current implementation
+///
+/// The floats are mapped in-place to integers with the same order, sorted with [`sort`], and mapped
+/// back. This is considerably faster than [`sort_by`] with `total_cmp` or `partial_cmp`, because
+/// the integer specific small-sorts and partitions apply.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [5.0, f64::NAN, -0.0, 1.5, f64::NEG_INFINITY, 0.0, -3.0];
+///
+/// ipnsort::sort_floats(&mut v);
+/// assert!(v[..6] == [f64::NEG_INFINITY, -3.0, -0.0, 0.0, 1.5, 5.0]);
+/// assert!(v[0].is_sign_negative() && v[2].is_sign_negative() && v[3].is_sign_positive());
+/// assert!(v[6].is_nan());
+/// ```
+#[inline]
+pub fn sort_floats<T>(arr: &mut [T])
+where
+    T: Float,
+{
+    float::sort_total_order(arr);
+}
+
+/// Returns the permutation that sorts the slice, but might not preserve the order of equal
+/// elements.
+///
+/// The returned indices `perm` list the elements of `arr` in sorted order, i.e. `arr[perm[0]]` is
+/// the smallest element. The slice itself is not modified, which is useful if it has to stay in
+/// its original order or if moving its elements is expensive. See [`apply_permutation_in_place`]
+/// to sort the slice, or other slices of the same length, with the permutation afterwards.
+///
+/// This sort is unstable (i.e., equal elements may be listed in any order), and
+/// *O*(*n* \* log(*n*)) worst-case. It allocates the returned `Vec`.
+///
+/// # Current implementation
+///
+/// The indices are sorted with [`sort_by`], comparing the elements they point to. Indices are
+/// small `Copy` types, so the specialized small-sorts and partitions apply independent of `T`.
+///
+/// # Examples
+///
+/// ```
+/// let v = ["c", "a", "d", "b"];
+///
+/// let perm = ipnsort::argsort(&v);
+/// assert!(perm == [1, 3, 0, 2]);
+/// ```
+#[inline]
+pub fn argsort<T>(arr: &[T]) -> Vec<usize>
+where
+    T: Ord,
+{
+    let mut perm = argsort::identity(arr.len());
+    unstable_sort(
+        &mut perm,
+        argsort::index_is_less(arr, |a: &T, b: &T| a.lt(b)),
+    );
+    perm
+}
+
+/// Returns the permutation that sorts the slice with a comparator function, but might not
+/// preserve the order of equal elements.
+///
+/// See [`sort_by`] for the requirements on the comparator function and [`argsort`] for the
+/// result.
+///
+/// # Examples
+///
+/// ```
+/// let v = [5, 4, 1, 3, 2];
+///
+/// let perm = ipnsort::argsort_by(&v, |a, b| b.cmp(a));
+/// assert!(perm == [0, 1, 3, 4, 2]);
+/// ```
+#[inline]
+pub fn argsort_by<T, F>(arr: &[T], mut compare: F) -> Vec<usize>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut perm = argsort::identity(arr.len());
+    unstable_sort(
+        &mut perm,
+        argsort::index_is_less(arr, |a: &T, b: &T| compare(a, b) == Ordering::Less),
+    );
+    perm
+}
+
+/// Reorders the slice such that the element at `i` afterwards is the one that was at `perm[i]`.
+///
+/// Applying the permutation returned by [`argsort`] sorts the slice. This moves each element only
+/// once, and does not allocate.
+///
+/// `perm` is used to mark visited indices while walking the cycles of the permutation, and holds
+/// its original values again when this function returns.
+///
+/// # Panics
+///
+/// Panics if `perm` and `arr` differ in length, or if `perm` is not a permutation of
+/// `0..arr.len()`. In that case the order of `arr` is unspecified, `perm` is still restored.
+///
+/// # Examples
+///
+/// ```
+/// let keys = [30, 10, 20];
+/// let mut values = ["c", "a", "b"];
+///
+/// let mut perm = ipnsort::argsort(&keys);
+/// ipnsort::apply_permutation_in_place(&mut values, &mut perm);
+/// assert!(values == ["a", "b", "c"]);
+/// ```
+#[inline]
+pub fn apply_permutation_in_place<T>(arr: &mut [T], perm: &mut [usize]) {
+    argsort::apply_permutation_in_place(arr, perm);
+}
+
+/// Sorts `keys` and applies the same reordering to `payloads`, but might not preserve the order
+/// of equal keys.
+///
+/// This sorts columns stored as separate slices, like sorting a slice of `(key, payload)` tuples
+/// by the key, without building such a slice. `payloads` is either a single `&mut [V]` or a tuple
+/// of them, see [`Payloads`].
+///
+/// This sort is unstable (i.e., may reorder equal keys), in-place (i.e., does not allocate), and
+/// *O*(*n* \* log(*n*)) worst-case.
+///
+/// # Current implementation
+///
+/// The same quicksort as used by [`sort`], with its pivot selection, handling of equal keys and
+/// heapsort fallback. Every element is moved by swapping the key together with its payloads, so
+/// the partition is a swapping Hoare partition and small ranges are sorted with insertion sort.
+/// The type specific partitions and small-sorts of [`sort`] are not used.
+///
+/// If the comparison panics, the keys are in an unspecified order, but each of them is still
+/// next to its own payloads.
+///
+/// # Panics
+///
+/// Panics if any of the payloads differs in length from `keys`.
+///
+/// # Examples
+///
+/// ```
+/// let mut keys = [3, 1, 2];
+/// let mut names = ["c", "a", "b"];
+/// let mut scores = [30.0, 10.0, 20.0];
+///
+/// ipnsort::sort_zip(&mut keys, (&mut names[..], &mut scores[..]));
+/// assert!(keys == [1, 2, 3]);
+/// assert!(names == ["a", "b", "c"]);
+/// assert!(scores == [10.0, 20.0, 30.0]);
+/// ```
+#[inline]
+pub fn sort_zip<K, P>(keys: &mut [K], payloads: P)
+where
+    K: Ord,
+    P: Payloads,
+{
+    zip::unstable_sort_zip(keys, payloads, |a: &K, b: &K| a.lt(b));
+}
+
+/// Sorts `keys` with a comparator function and applies the same reordering to `payloads`, but
+/// might not preserve the order of equal keys.
+///
+/// See [`sort_by`] for the requirements on the comparator function and [`sort_zip`] for the
+/// payloads.
+///
+/// # Examples
+///
+/// ```
+/// let mut keys = [1, 3, 2];
+/// let mut values = ['a', 'c', 'b'];
+///
+/// ipnsort::sort_zip_by(&mut keys, &mut values[..], |a, b| b.cmp(a));
+/// assert!(keys == [3, 2, 1]);
+/// assert!(values == ['c', 'b', 'a']);
+/// ```
+#[inline]
+pub fn sort_zip_by<K, P, F>(keys: &mut [K], payloads: P, mut compare: F)
+where
+    P: Payloads,
+    F: FnMut(&K, &K) -> Ordering,
+{
+    zip::unstable_sort_zip(keys, payloads, |a, b| compare(a, b) == Ordering::Less);
+}
+
+/// Sorts the slice in parallel, but might not preserve the order of equal elements.
+///
+/// This sort is unstable (i.e., may reorder equal elements), in-place
+/// (i.e., does not allocate to hold elements), and *O*(*n* \* log(*n*)) worst-case.
+///
+/// # Current implementation
+///
+/// The current algorithm is the same quicksort as used by [`sort`], where the recursion into the
+/// two sides of each partition is distributed across threads. Sub-slices below a fixed length are
+/// sorted sequentially. By default the threads are spawned as scoped threads, limited to the
+/// available parallelism. With the `rayon` feature, the rayon global thread pool is used instead.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [-5, 4, 1, -3, 2];
+///
+/// ipnsort::par_sort(&mut v);
+/// assert!(v == [-5, -3, 1, 2, 4]);
+/// ```
+#[cfg(feature = "parallel")]
+#[inline]
+pub fn par_sort<T>(arr: &mut [T])
+where
+    T: Ord + Send,
+{
+    parallel::par_unstable_sort(arr, &|a: &T, b: &T| a.lt(b));
+}
+
+/// Sorts the slice in parallel with a comparator function, but might not preserve the order of
+/// equal elements.
+///
+/// This sort is unstable (i.e., may reorder equal elements), in-place
+/// (i.e., does not allocate to hold elements), and *O*(*n* \* log(*n*)) worst-case.
+///
+/// The comparator function must define a total ordering for the elements in the slice, see
+/// [`sort_by`]. It may be called concurrently from multiple threads.
+///
+/// # Current implementation
+///
+/// See [`par_sort`].
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [5, 4, 1, 3, 2];
+/// ipnsort::par_sort_by(&mut v, |a, b| a.cmp(b));
+/// assert!(v == [1, 2, 3, 4, 5]);
+///
+/// // reverse sorting
+/// ipnsort::par_sort_by(&mut v, |a, b| b.cmp(a));
+/// assert!(v == [5, 4, 3, 2, 1]);
+/// ```
+#[cfg(feature = "parallel")]
+#[inline]
+pub fn par_sort_by<T, F>(arr: &mut [T], compare: F)
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    parallel::par_unstable_sort(arr, &|a: &T, b: &T| compare(a, b) == Ordering::Less);
+}
+
+/// Partially sorts the slice, such that `v[..k]` holds the `k` smallest elements in sorted order.
+/// The order of the remaining elements `v[k..]` is unspecified.
+///
+/// This sort is unstable (i.e., may reorder equal elements), in-place
+/// (i.e., does not allocate), and *O*(*n* + *k* \* log(*k*)) worst-case.
+///
+/// # Current implementation
+///
+/// The `k` smallest elements are moved to the front with [`select_nth_unstable`], after which
+/// only the prefix is sorted with [`sort`].
+///
+/// # Panics
+///
+/// Panics when `k > len()`.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [-5, 4, 1, -3, 2];
+///
+/// ipnsort::partial_sort(&mut v, 3);
+/// assert!(v[..3] == [-5, -3, 1]);
+/// ```
+#[inline]
+pub fn partial_sort<T>(arr: &mut [T], k: usize)
+where
+    T: Ord,
+{
+    unstable_partial_sort(arr, k, |a, b| a.lt(b));
+}
+
+/// Partially sorts the slice with a comparator function, such that `v[..k]` holds the `k`
+/// smallest elements in sorted order. The order of the remaining elements `v[k..]` is
+/// unspecified.
+///
+/// This sort is unstable (i.e., may reorder equal elements), in-place
+/// (i.e., does not allocate), and *O*(*n* + *k* \* log(*k*)) worst-case.
+///
+/// See [`sort_by`] for the requirements on the comparator function and [`partial_sort`] for the
+/// current implementation.
+///
+/// # Panics
+///
+/// Panics when `k > len()`.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [-5, 4, 1, -3, 2];
+///
+/// // Find the 2 largest elements, by using a reversed comparator.
+/// ipnsort::partial_sort_by(&mut v, 2, |a, b| b.cmp(a));
+/// assert!(v[..2] == [4, 2]);
+/// ```
+#[inline]
+pub fn partial_sort_by<T, F>(arr: &mut [T], k: usize, mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    unstable_partial_sort(arr, k, |a, b| compare(a, b) == Ordering::Less);
+}
+
+/// Sorts the slice and removes consecutive repeated elements, returning the number of unique
+/// elements.
+///
+/// After the call `v[..n]`, with `n` being the returned value, holds one element of each group of
+/// equal elements in sorted order. The order of the remaining elements `v[n..]` is unspecified.
+/// This is equivalent to `v.sort_unstable(); v.dedup();` for a `Vec`, which can be achieved with
+/// `let n = sort_dedup(&mut v); v.truncate(n);`.
+///
+/// This sort is unstable (i.e., may reorder equal elements), in-place
+/// (i.e., does not allocate), and *O*(*n* \* log(*n*)) worst-case.
+///
+/// # Current implementation
+///
+/// The duplicates are discarded while sorting. Whenever the chosen pivot is equal to the pivot of
+/// the parent partition, all elements equal to it are partitioned out and skipped without further
+/// sorting, which makes inputs with few distinct values particularly fast.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = vec![4, -5, 1, 4, -3, 1, 4];
+///
+/// let n = ipnsort::sort_dedup(&mut v);
+/// v.truncate(n);
+/// assert!(v == [-5, -3, 1, 4]);
+/// ```
+#[inline]
+pub fn sort_dedup<T>(arr: &mut [T]) -> usize
+where
+    T: Ord,
+{
+    dedup::unstable_sort_dedup(arr, |a, b| a.lt(b))
+}
+
+/// Sorts the slice with a comparator function and removes consecutive elements that compare
+/// equal, returning the number of unique elements.
+///
+/// See [`sort_by`] for the requirements on the comparator function and [`sort_dedup`] for the
+/// result and current implementation.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [-5i32, 4, 1, -3, 2, 5, -1];
+///
+/// let n = ipnsort::sort_dedup_by(&mut v, |a, b| a.abs().cmp(&b.abs()));
+/// assert!(n == 5);
+/// assert!(v[..n].iter().map(|x| x.abs()).eq([1, 2, 3, 4, 5]));
+/// ```
+#[inline]
+pub fn sort_dedup_by<T, F>(arr: &mut [T], mut compare: F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    dedup::unstable_sort_dedup(arr, |a, b| compare(a, b) == Ordering::Less)
+}
+
+/// Reorder the slice such that the element at `index` is at its final sorted position.
+///
+/// This reordering has the additional property that any value at position `i < index` will be
+/// less than or equal to any value at a position `j > index`. Additionally, this reordering is
+/// unstable (i.e. any number of equal elements may end up at position `index`), in-place
+/// (i.e. does not allocate), and runs in *O*(*n*) time. This function is also known as "kth
+/// element" in other libraries.
+///
+/// It returns a triplet of the following from the reordered slice: the subslice prior to
+/// `index`, the element at `index`, and the subslice after `index`; accordingly, the values in
+/// those two subslices will respectively all be less-than-or-equal-to and
+/// greater-than-or-equal-to the value of the element at `index`.
+///
+/// # Current implementation
+///
+/// The current algorithm is an introselect implementation based on the same quicksort that is
+/// used for [`sort`]. The worst case is *O*(*n*), thanks to a fallback to median of medians
+/// selection, that kicks in after a fixed number of imbalanced partitions.
+///
+/// # Panics
+///
+/// Panics when `index >= len()`, meaning it always panics on empty slices.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [-5i32, 4, 2, -3, 1];
+///
+/// // Find the items less than or equal to the median, the median, and greater than or equal to
+/// // the median.
+/// let (lesser, median, greater) = ipnsort::select_nth_unstable(&mut v, 2);
+///
+/// assert!(lesser == [-3, -5] || lesser == [-5, -3]);
+/// assert_eq!(median, &mut 1);
+/// assert!(greater == [4, 2] || greater == [2, 4]);
+/// ```
+#[inline]
+pub fn select_nth_unstable<T>(arr: &mut [T], index: usize) -> (&mut [T], &mut T, &mut [T])
+where
+    T: Ord,
+{
+    select::partition_at_index(arr, index, |a, b| a.lt(b))
+}
+
+/// Reorder the slice with a comparator function such that the element at `index` is at its
+/// final sorted position.
+///
+/// This reordering has the additional property that any value at position `i < index` will be
+/// less than or equal to any value at a position `j > index` using the comparator function.
+/// Additionally, this reordering is unstable (i.e. any number of equal elements may end up at
+/// position `index`), in-place (i.e. does not allocate), and runs in *O*(*n*) time.
+///
+/// See [`select_nth_unstable`] for the returned sub-slices and the current implementation.
+///
+/// # Panics
+///
+/// Panics when `index >= len()`, meaning it always panics on empty slices.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [-5i32, 4, 2, -3, 1];
+///
+/// // Find the items greater than or equal to the median, the median, and less than or equal to
+/// // the median, by using a reversed comparator.
+/// let (before, median, after) = ipnsort::select_nth_unstable_by(&mut v, 2, |a, b| b.cmp(a));
+///
+/// assert!(before == [4, 2] || before == [2, 4]);
+/// assert_eq!(median, &mut 1);
+/// assert!(after == [-3, -5] || after == [-5, -3]);
+/// ```
+#[inline]
+pub fn select_nth_unstable_by<T, F>(
+    arr: &mut [T],
+    index: usize,
+    mut compare: F,
+) -> (&mut [T], &mut T, &mut [T])
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    select::partition_at_index(arr, index, |a, b| compare(a, b) == Ordering::Less)
+}
+
+/// Reorder the slice with a key extraction function such that the element at `index` is at its
+/// final sorted position.
+///
+/// This reordering has the additional property that any value at position `i < index` will be
+/// less than or equal to any value at a position `j > index` using the key extraction function.
+/// Additionally, this reordering is unstable (i.e. any number of equal elements may end up at
+/// position `index`), in-place (i.e. does not allocate), and runs in *O*(*n*) time.
+///
+/// See [`select_nth_unstable`] for the returned sub-slices and the current implementation.
+///
+/// # Panics
+///
+/// Panics when `index >= len()`, meaning it always panics on empty slices.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [-5i32, 4, 1, -3, 2];
+///
+/// // Find the items less than or equal to the median, the median, and greater than or equal to
+/// // the median as if the slice were sorted according to absolute value.
+/// let (lesser, median, greater) = ipnsort::select_nth_unstable_by_key(&mut v, 2, |a| a.abs());
+///
+/// assert!(lesser == [1, 2] || lesser == [2, 1]);
+/// assert_eq!(median, &mut -3);
+/// assert!(greater == [4, -5] || greater == [-5, 4]);
+/// ```
+#[inline]
+pub fn select_nth_unstable_by_key<T, K, F>(
+    arr: &mut [T],
+    index: usize,
+    mut f: F,
+) -> (&mut [T], &mut T, &mut [T])
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    select::partition_at_index(arr, index, |a, b| f(a).lt(&f(b)))
+}
+
+/// Partitions the slice into the elements less than `pivot`, the elements equal to `pivot` and
+/// the elements greater than `pivot`, in that order.
+///
+/// Returns the number of elements less than and equal to `pivot`, as `(lt_len, eq_len)`. The
+/// elements are reordered arbitrarily within each of the three parts. This is in-place (i.e. does
+/// not allocate), and runs in *O*(*n*) time.
+///
+/// # Current implementation
+///
+/// Two passes of the partition used by [`sort`], the second one only over the elements not less
+/// than `pivot`. That visits the elements greater than `pivot` twice, but each pass only compares
+/// once per element.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [3, 1, 2, 3, 5, 2, 4];
+///
+/// let (lt_len, eq_len) = ipnsort::partition3(&mut v, &3);
+///
+/// assert_eq!((lt_len, eq_len), (3, 2));
+/// assert!(v[..3].iter().all(|&x| x < 3));
+/// assert_eq!(v[3..5], [3, 3]);
+/// assert!(v[5..].iter().all(|&x| x > 3));
+/// ```
+#[inline]
+pub fn partition3<T>(arr: &mut [T], pivot: &T) -> (usize, usize)
+where
+    T: Ord,
+{
+    quicksort::partition3(arr, pivot, &mut ord_is_less())
+}
+
+/// Partitions the slice with a comparator function into the elements less than `pivot`, the
+/// elements equal to `pivot` and the elements greater than `pivot`, in that order.
+///
+/// Returns the number of elements less than and equal to `pivot`, as `(lt_len, eq_len)`.
+///
+/// The comparator function must define a total ordering for the elements in the slice. If
+/// the ordering is not total, the order of the elements and the returned lengths are unspecified,
+/// but all original elements will remain in the slice.
+///
+/// See [`partition3`] for the current implementation.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [3, 1, 2, 3, 5, 2, 4];
+///
+/// // Partition in descending order.
+/// let (gt_len, eq_len) = ipnsort::partition3_by(&mut v, &3, |a, b| b.cmp(a));
+///
+/// assert_eq!((gt_len, eq_len), (2, 2));
+/// assert!(v[..2].iter().all(|&x| x > 3));
+/// ```
+#[inline]
+pub fn partition3_by<T, F>(arr: &mut [T], pivot: &T, mut compare: F) -> (usize, usize)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    quicksort::partition3(arr, pivot, &mut |a, b| compare(a, b) == Ordering::Less)
+}
+
 // --- IMPL ---
 
 /// Sorts `v` using pattern-defeating quicksort, which is *O*(*n* \* log(*n*)) worst-case.
 #[inline(always)]
-fn unstable_sort<T, F>(v: &mut [T], mut is_less: F)
+fn unstable_sort<T, F>(v: &mut [T], is_less: F)
 where
     F: FnMut(&T, &T) -> bool,
 {
     // Sorting has no meaningful behavior on zero-sized types.
-    if T::IS_ZST {
+    if mem::size_of::<T>() == 0 {
         return;
     }
 
     let len = v.len();
 
-    // This path is critical for very small inputs. Always pick insertion sort for these inputs,
-    // without any other analysis. This is perf critical for small inputs, in cold code.
-    const MAX_LEN_ALWAYS_INSERTION_SORT: usize = 20;
-
     // Instrumenting the standard library showed that 90+% of the calls to sort by rustc are either
     // of size 0 or 1. Make this path extra fast by assuming the branch is likely.
     if intrinsics::likely(len < 2) {
         return;
     }
 
+    #[cfg(not(feature = "check_ord"))]
+    insertion_sort_or_quicksort(v, is_less);
+
+    #[cfg(feature = "check_ord")]
+    check_ord::sort(v, is_less, |perm, is_less| {
+        insertion_sort_or_quicksort(perm, is_less)
+    });
+}
+
+#[inline(always)]
+fn insertion_sort_or_quicksort<T, F>(v: &mut [T], mut is_less: F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    // This path is critical for very small inputs. Always pick insertion sort for these inputs,
+    // without any other analysis. This is perf critical for small inputs, in cold code.
+    const MAX_LEN_ALWAYS_INSERTION_SORT: usize = 20;
+
     // It's important to differentiate between small-sort performance for small slices and
     // small-sort performance sorting small sub-slices as part of the main quicksort loop. For the
     // former, testing showed that the representative benchmarks for real-world performance are cold
@@ -143,7 +1081,7 @@ where
     // times, so hot benchmarks are fine and more realistic. And it's worth it to optimize sorting
     // small sub-slices with more sophisticated solutions than insertion sort.
 
-    if intrinsics::likely(len <= MAX_LEN_ALWAYS_INSERTION_SORT) {
+    if intrinsics::likely(v.len() <= MAX_LEN_ALWAYS_INSERTION_SORT) {
         // More specialized and faster options, extending the range of allocation free sorting
         // are possible but come at a great cost of additional code, which is problematic for
         // compile-times.
@@ -153,6 +1091,75 @@ where
     }
 }
 
+/// Sorts `v[..k]` such that it holds the `k` smallest elements of `v`.
+fn unstable_partial_sort<T, F>(v: &mut [T], k: usize, mut is_less: F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let len = v.len();
+
+    if k > len {
+        panic!("partial_sort k {} greater than length of slice {}", k, len);
+    }
+
+    if k == 0 {
+        return;
+    }
+
+    let sort_len = if k < len {
+        // After selecting the element at `k - 1`, all elements in `v[..k - 1]` are less than or
+        // equal to it, and only those still need to be sorted.
+        select::partition_at_index(v, k - 1, &mut is_less);
+        k - 1
+    } else {
+        len
+    };
+
+    unstable_sort(&mut v[..sort_len], &mut is_less);
+}
+
+/// Sorts `v[sorted_prefix_len..]` and merges it into the sorted `v[..sorted_prefix_len]`.
+fn unstable_sort_appended<T, F>(v: &mut [T], sorted_prefix_len: usize, mut is_less: F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    // The merge buffer is bounded to this size, beyond it the rotation-based fallback is used.
+    const MAX_MERGE_BUFFER_BYTES: usize = 4 * 1024 * 1024;
+
+    let len = v.len();
+
+    if sorted_prefix_len > len {
+        panic!(
+            "sort_appended sorted_prefix_len {} greater than length of slice {}",
+            sorted_prefix_len, len
+        );
+    }
+
+    unstable_sort(&mut v[sorted_prefix_len..], &mut is_less);
+
+    let mid = sorted_prefix_len;
+    if mid == 0 || mid == len || mem::size_of::<T>() == 0 {
+        return;
+    }
+
+    // Appending elements that all belong after the prefix is common, e.g. for timestamps.
+    if !is_less(&v[mid], &v[mid - 1]) {
+        return;
+    }
+
+    let buf_len = cmp::min(
+        cmp::min(mid, len - mid),
+        MAX_MERGE_BUFFER_BYTES / mem::size_of::<T>(),
+    );
+
+    // If the allocation fails `buf` stays empty, and the merge is done fully in-place, which needs
+    // no memory, only more time.
+    let mut buf = Vec::new();
+    let _ = buf.try_reserve_exact(buf_len);
+
+    stable::merge_with_scratch(v, mid, buf.spare_capacity_mut(), &mut is_less);
+}
+
 #[inline(never)]
 fn quicksort<T, F>(v: &mut [T], mut is_less: F)
 where
@@ -167,7 +1174,8 @@ where
         }
 
         // It would be possible to a do in-place merging here for a long existing streak. But that makes the
-        // implementation a lot bigger, users can use `slice::sort` for that use-case.
+        // implementation a lot bigger, users can use `slice::sort` for that use-case, or
+        // `sort_appended` if they know the length of the streak.
         return;
     }
 
@@ -194,7 +1202,7 @@ where
     let mut end = 2;
 
     // SAFETY: See below specific.
-    unsafe {
+    let (streak_end, was_reversed) = unsafe {
         // SAFETY: We checked that len >= 2, so 0 and 1 are valid indices.
         let assume_reverse = is_less(v.get_unchecked(1), v.get_unchecked(0));
 
@@ -212,25 +1220,31 @@ where
             }
             (end, false)
         }
-    }
-}
+    };
+
+    report!(streak(len, streak_end, was_reversed));
 
-// // #[rustc_unsafe_specialization_marker]
-// trait Freeze {}
+    (streak_end, was_reversed)
+}
 
-// Can the type have interior mutability, this is checked by testing if T is Freeze. If the type can
-// have interior mutability it may alter itself during comparison in a way that must be observed
-// after the sort operation concludes. Otherwise a type like Mutex<Option<Box<str>>> could lead to
-// double free.
-unsafe auto trait Freeze {}
+/// Calculates the distance between two pointers within the same allocation, where `ptr` must not
+/// be smaller than `origin`. See `<*const T>::sub_ptr`, which is nightly only.
+#[inline(always)]
+unsafe fn sub_ptr<T>(ptr: *const T, origin: *const T) -> usize {
+    // SAFETY: The caller must uphold the safety contract of `sub_ptr`.
+    #[cfg(feature = "nightly")]
+    unsafe {
+        ptr.sub_ptr(origin)
+    }
 
-impl<T: ?Sized> !Freeze for core::cell::UnsafeCell<T> {}
-unsafe impl<T: ?Sized> Freeze for core::marker::PhantomData<T> {}
-unsafe impl<T: ?Sized> Freeze for *const T {}
-unsafe impl<T: ?Sized> Freeze for *mut T {}
-unsafe impl<T: ?Sized> Freeze for &T {}
-unsafe impl<T: ?Sized> Freeze for &mut T {}
+    // SAFETY: The caller must uphold the safety contract of `sub_ptr`.
+    #[cfg(not(feature = "nightly"))]
+    unsafe {
+        ptr.offset_from(origin) as usize
+    }
+}
 
+#[cfg(not(feature = "opt_size"))]
 #[must_use]
 const fn has_efficient_in_place_swap<T>() -> bool {
     const MEM_SIZE_U64: usize = mem::size_of::<u64>();
@@ -238,15 +1252,18 @@ const fn has_efficient_in_place_swap<T>() -> bool {
     mem::size_of::<T>() <= MEM_SIZE_U64
 }
 
+#[cfg(not(feature = "opt_size"))]
 #[test]
 fn type_info() {
     assert!(has_efficient_in_place_swap::<i32>());
     assert!(has_efficient_in_place_swap::<u64>());
     assert!(!has_efficient_in_place_swap::<u128>());
-    assert!(!has_efficient_in_place_swap::<String>());
+    assert!(!has_efficient_in_place_swap::<alloc::string::String>());
 }
 
+#[cfg(all(feature = "nightly", not(feature = "opt_size")))]
 trait IsTrue<const B: bool> {}
+#[cfg(all(feature = "nightly", not(feature = "opt_size")))]
 impl IsTrue<true> for () {}
 
 struct GapGuard<T> {
@@ -0,0 +1,567 @@
+//! Sorting fixed-size arrays with sorting networks, see [`crate::sort_array`].
+
+use crate::smallsort::{insertion_sort_shift_left, swap_if_less};
+
+// Arrays up to this length are sorted with networks, longer ones with insertion sort.
+pub(crate) const MAX_NETWORK_LEN: usize = 32;
+
+/// Sorts `v` with a sorting network if `N` is at most [`MAX_NETWORK_LEN`], otherwise with
+/// insertion sort.
+///
+/// Each length uses the network with the fewest comparators known for it, except for 22 to 26 and
+/// 28 elements whose networks use 1 to 3 comparators more.
+#[inline(always)]
+pub(crate) fn sort_array<T, F, const N: usize>(v: &mut [T; N], is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    if N <= MAX_NETWORK_LEN {
+        apply_network(v, NETWORKS[N], is_less);
+    } else {
+        insertion_sort_shift_left(v, 1, is_less);
+    }
+}
+
+/// Applies the comparators of `network` to `v`, which must have the length the network was made
+/// for.
+#[inline(always)]
+fn apply_network<T, F>(v: &mut [T], network: &[(u8, u8)], is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let len = v.len();
+    let v_base = v.as_mut_ptr();
+
+    for &(a, b) in network {
+        let (a, b) = (a as usize, b as usize);
+        debug_assert!(a < b && b < len);
+
+        // SAFETY: The networks are only applied to arrays of the length they were made for, and
+        // the compile-time check below makes sure all their indices are in-bounds for that length.
+        unsafe {
+            swap_if_less(v_base, a, b, is_less);
+        }
+    }
+}
+
+// The networks don't change at runtime, so check them at compile-time.
+const _: () = {
+    const fn in_bounds(network: &[(u8, u8)], len: usize) -> bool {
+        let mut i = 0;
+        while i < network.len() {
+            let (a, b) = network[i];
+            if !(a < b && (b as usize) < len) {
+                return false;
+            }
+            i += 1;
+        }
+        true
+    }
+
+    let mut len = 0;
+    while len <= MAX_NETWORK_LEN {
+        assert!(in_bounds(NETWORKS[len], len));
+        len += 1;
+    }
+};
+
+// Sorting networks with the smallest known number of comparators for each length, with the
+// comparators of each layer on one line. For a few lengths above 21 no network of that size could
+// be reproduced here, those use up to 3 comparators more, noted next to the length. See
+// https://bertdobbelaere.github.io/sorting_networks.html.
+#[rustfmt::skip]
+const NETWORKS: [&[(u8, u8)]; MAX_NETWORK_LEN + 1] = [
+    &[],
+    &[],
+    // 2 elements, 1 comparator.
+    &[
+        (0, 1),
+    ],
+    // 3 elements, 3 comparators.
+    &[
+        (0, 2),
+        (0, 1),
+        (1, 2),
+    ],
+    // 4 elements, 5 comparators.
+    &[
+        (0, 2), (1, 3),
+        (0, 1), (2, 3),
+        (1, 2),
+    ],
+    // 5 elements, 9 comparators.
+    &[
+        (0, 4), (1, 2),
+        (0, 1), (3, 4),
+        (1, 3), (2, 4),
+        (0, 1), (2, 3),
+        (1, 2),
+    ],
+    // 6 elements, 12 comparators.
+    &[
+        (0, 2), (1, 3), (4, 5),
+        (0, 1), (2, 3),
+        (1, 4), (3, 5),
+        (0, 1), (2, 3),
+        (3, 4),
+        (2, 3),
+        (1, 2),
+    ],
+    // 7 elements, 16 comparators.
+    &[
+        (0, 2), (1, 3), (4, 6),
+        (0, 4), (1, 5), (2, 6),
+        (0, 1), (2, 3), (4, 5),
+        (2, 4), (3, 5),
+        (1, 4), (3, 6),
+        (1, 2), (3, 4), (5, 6),
+    ],
+    // 8 elements, 19 comparators.
+    &[
+        (0, 2), (1, 3), (4, 6), (5, 7),
+        (0, 4), (1, 5), (2, 6), (3, 7),
+        (0, 1), (2, 3), (4, 5), (6, 7),
+        (2, 4), (3, 5),
+        (1, 4), (3, 6),
+        (1, 2), (3, 4), (5, 6),
+    ],
+    // 9 elements, 25 comparators.
+    &[
+        (0, 3), (1, 7), (2, 5), (4, 8),
+        (0, 7), (2, 4), (3, 8), (5, 6),
+        (0, 2), (1, 3), (4, 5), (7, 8),
+        (1, 4), (3, 6), (5, 7),
+        (0, 1), (2, 4), (3, 5), (6, 8),
+        (2, 3), (4, 5), (6, 7),
+        (1, 2), (3, 4), (5, 6),
+    ],
+    // 10 elements, 29 comparators.
+    &[
+        (0, 8), (1, 9), (2, 7), (3, 5), (4, 6),
+        (0, 2), (1, 4), (5, 8), (7, 9),
+        (0, 3), (2, 4), (5, 7), (6, 9),
+        (0, 1), (3, 6), (8, 9),
+        (1, 5), (2, 3), (4, 8), (6, 7),
+        (1, 2), (3, 5), (4, 6), (7, 8),
+        (2, 3), (4, 5), (6, 7),
+        (3, 4), (5, 6),
+    ],
+    // 11 elements, 35 comparators.
+    &[
+        (0, 8), (1, 7), (2, 6), (4, 10), (5, 9),
+        (0, 1), (2, 5), (3, 4), (6, 9), (7, 8),
+        (0, 2), (1, 6), (5, 10),
+        (0, 3), (1, 2), (4, 6), (5, 7), (9, 10),
+        (1, 4), (3, 5), (6, 8), (7, 10),
+        (1, 3), (2, 5), (6, 9), (8, 10),
+        (2, 3), (4, 5), (6, 7), (8, 9),
+        (4, 6), (5, 7),
+        (3, 4), (5, 6), (7, 8),
+    ],
+    // 12 elements, 39 comparators.
+    &[
+        (0, 8), (1, 7), (2, 6), (3, 11), (4, 10), (5, 9),
+        (0, 1), (2, 5), (3, 4), (6, 9), (7, 8), (10, 11),
+        (0, 2), (1, 6), (5, 10), (9, 11),
+        (0, 3), (1, 2), (4, 6), (5, 7), (8, 11), (9, 10),
+        (1, 4), (3, 5), (6, 8), (7, 10),
+        (1, 3), (2, 5), (6, 9), (8, 10),
+        (2, 3), (4, 5), (6, 7), (8, 9),
+        (4, 6), (5, 7),
+        (3, 4), (5, 6), (7, 8),
+    ],
+    // 13 elements, 45 comparators.
+    &[
+        (0, 12), (1, 10), (2, 9), (3, 7), (5, 11), (6, 8),
+        (1, 6), (2, 3), (4, 11), (7, 9), (8, 10),
+        (0, 4), (1, 2), (3, 6), (7, 8), (9, 10), (11, 12),
+        (4, 6), (5, 9), (8, 11), (10, 12),
+        (0, 5), (3, 8), (4, 7), (6, 11), (9, 10),
+        (0, 1), (2, 5), (6, 9), (7, 8), (10, 11),
+        (1, 3), (2, 4), (5, 6), (9, 10),
+        (1, 2), (3, 4), (5, 7), (6, 8),
+        (2, 3), (4, 5), (6, 7), (8, 9),
+        (3, 4), (5, 6),
+    ],
+    // 14 elements, 51 comparators.
+    &[
+        (0, 13), (1, 12), (4, 8), (5, 6), (7, 11), (9, 10),
+        (0, 5), (1, 7), (2, 9), (3, 4), (6, 13), (11, 12),
+        (0, 1), (2, 3), (4, 5), (6, 8), (7, 9), (10, 11), (12, 13),
+        (0, 2), (1, 3), (4, 10), (5, 11), (6, 7), (8, 9),
+        (1, 2), (3, 12), (4, 6), (5, 7), (8, 10), (9, 11),
+        (1, 4), (2, 6), (5, 8), (7, 10), (9, 13),
+        (2, 4), (3, 6), (9, 12), (11, 13),
+        (3, 5), (6, 8), (7, 9), (10, 12),
+        (3, 4), (5, 6), (7, 8), (9, 10), (11, 12),
+        (6, 7), (8, 9),
+    ],
+    // 15 elements, 56 comparators.
+    &[
+        (0, 13), (1, 12), (3, 14), (4, 8), (5, 6), (7, 11), (9, 10),
+        (0, 5), (1, 7), (2, 9), (3, 4), (6, 13), (8, 14), (11, 12),
+        (0, 1), (2, 3), (4, 5), (6, 8), (7, 9), (10, 11), (12, 13),
+        (0, 2), (1, 3), (4, 10), (5, 11), (6, 7), (8, 9), (12, 14),
+        (1, 2), (3, 12), (4, 6), (5, 7), (8, 10), (9, 11), (13, 14),
+        (1, 4), (2, 6), (5, 8), (7, 10), (9, 13), (11, 14),
+        (2, 4), (3, 6), (9, 12), (11, 13),
+        (3, 5), (6, 8), (7, 9), (10, 12),
+        (3, 4), (5, 6), (7, 8), (9, 10), (11, 12),
+        (6, 7), (8, 9),
+    ],
+    // 16 elements, 60 comparators.
+    &[
+        (0, 13), (1, 12), (2, 15), (3, 14), (4, 8), (5, 6), (7, 11), (9, 10),
+        (0, 5), (1, 7), (2, 9), (3, 4), (6, 13), (8, 14), (10, 15), (11, 12),
+        (0, 1), (2, 3), (4, 5), (6, 8), (7, 9), (10, 11), (12, 13), (14, 15),
+        (0, 2), (1, 3), (4, 10), (5, 11), (6, 7), (8, 9), (12, 14), (13, 15),
+        (1, 2), (3, 12), (4, 6), (5, 7), (8, 10), (9, 11), (13, 14),
+        (1, 4), (2, 6), (5, 8), (7, 10), (9, 13), (11, 14),
+        (2, 4), (3, 6), (9, 12), (11, 13),
+        (3, 5), (6, 8), (7, 9), (10, 12),
+        (3, 4), (5, 6), (7, 8), (9, 10), (11, 12),
+        (6, 7), (8, 9),
+    ],
+    // 17 elements, 71 comparators.
+    &[
+        (0, 11), (1, 15), (2, 10), (3, 5), (4, 6), (8, 12), (9, 16), (13, 14),
+        (0, 6), (1, 13), (2, 8), (4, 14), (5, 15), (7, 11),
+        (0, 8), (3, 7), (4, 9), (6, 16), (10, 11), (12, 14),
+        (0, 2), (1, 4), (5, 6), (7, 13), (8, 9), (10, 12), (11, 14), (15, 16),
+        (0, 3), (2, 5), (6, 11), (7, 10), (9, 13), (12, 15), (14, 16),
+        (0, 1), (3, 4), (5, 10), (6, 9), (7, 8), (11, 15), (13, 14),
+        (1, 2), (3, 7), (4, 8), (6, 12), (11, 13), (14, 15),
+        (1, 3), (2, 7), (4, 5), (9, 11), (10, 12), (13, 14),
+        (2, 3), (4, 6), (5, 7), (8, 10),
+        (3, 4), (6, 8), (7, 9), (10, 12),
+        (5, 6), (7, 8), (9, 10), (11, 12),
+        (4, 5), (6, 7), (8, 9), (10, 11), (12, 13),
+    ],
+    // 18 elements, 77 comparators.
+    &[
+        (0, 1), (2, 3), (4, 5), (6, 7), (8, 9), (10, 11), (12, 13), (14, 15), (16, 17),
+        (0, 2), (1, 3), (4, 12), (5, 13), (6, 8), (9, 11), (14, 16), (15, 17),
+        (0, 14), (1, 16), (2, 15), (3, 17),
+        (0, 6), (1, 10), (2, 9), (7, 16), (8, 15), (11, 17),
+        (1, 4), (3, 9), (5, 7), (8, 14), (10, 12), (13, 16),
+        (0, 1), (2, 5), (3, 13), (4, 14), (7, 9), (8, 10), (12, 15), (16, 17),
+        (1, 2), (3, 5), (4, 6), (11, 13), (12, 14), (15, 16),
+        (4, 8), (5, 12), (6, 10), (7, 11), (9, 13),
+        (1, 4), (2, 8), (3, 6), (5, 7), (9, 15), (10, 12), (11, 14), (13, 16),
+        (2, 4), (5, 8), (6, 10), (7, 11), (9, 12), (13, 15),
+        (3, 5), (6, 8), (7, 10), (9, 11), (12, 14),
+        (3, 4), (5, 6), (7, 8), (9, 10), (11, 12), (13, 14),
+    ],
+    // 19 elements, 85 comparators.
+    &[
+        (0, 12), (1, 4), (2, 8), (3, 5), (6, 17), (7, 11), (9, 14), (10, 13), (15, 16),
+        (0, 2), (1, 7), (3, 6), (4, 11), (5, 17), (8, 12), (10, 15), (13, 16), (14, 18),
+        (3, 10), (4, 14), (5, 15), (6, 13), (7, 9), (11, 17), (16, 18),
+        (0, 7), (1, 10), (4, 6), (9, 15), (11, 16), (12, 17), (13, 14),
+        (0, 3), (2, 6), (5, 7), (8, 11), (12, 16),
+        (1, 8), (2, 9), (3, 4), (6, 15), (7, 13), (10, 11), (12, 18),
+        (1, 3), (2, 5), (6, 9), (7, 12), (8, 10), (11, 14), (17, 18),
+        (0, 1), (2, 3), (4, 8), (6, 10), (9, 12), (14, 15), (16, 17),
+        (1, 2), (5, 8), (6, 7), (9, 11), (10, 13), (14, 16), (15, 17),
+        (3, 6), (4, 5), (7, 9), (8, 10), (11, 12), (13, 14), (15, 16),
+        (3, 4), (5, 6), (7, 8), (9, 10), (11, 13), (12, 14),
+        (2, 3), (4, 5), (6, 7), (8, 9), (10, 11), (12, 13), (14, 15),
+    ],
+    // 20 elements, 91 comparators.
+    &[
+        (0, 3), (1, 7), (2, 5), (4, 8), (6, 9), (10, 13), (11, 15), (12, 18), (14, 17), (16, 19),
+        (0, 14), (1, 11), (2, 16), (3, 17), (4, 12), (5, 19), (6, 10), (7, 15), (8, 18), (9, 13),
+        (0, 4), (1, 2), (3, 8), (5, 7), (11, 16), (12, 14), (15, 19), (17, 18),
+        (1, 6), (2, 12), (3, 5), (4, 11), (7, 17), (8, 15), (13, 18), (14, 16),
+        (0, 1), (2, 6), (7, 10), (9, 12), (13, 17), (18, 19),
+        (1, 6), (5, 9), (7, 11), (8, 12), (10, 14), (13, 18),
+        (3, 5), (4, 7), (8, 10), (9, 11), (12, 15), (14, 16),
+        (1, 3), (2, 4), (5, 7), (6, 10), (9, 13), (12, 14), (15, 17), (16, 18),
+        (1, 2), (3, 4), (6, 7), (8, 9), (10, 11), (12, 13), (15, 16), (17, 18),
+        (2, 3), (4, 6), (5, 8), (7, 9), (10, 12), (11, 14), (13, 15), (16, 17),
+        (4, 5), (6, 8), (7, 10), (9, 12), (11, 13), (14, 15),
+        (3, 4), (5, 6), (7, 8), (9, 10), (11, 12), (13, 14), (15, 16),
+    ],
+    // 21 elements, 100 comparators.
+    &[
+        (0, 7), (1, 10), (3, 5), (4, 8), (6, 13), (9, 19), (11, 14), (12, 17), (15, 16), (18, 20),
+        (0, 11), (1, 15), (2, 12), (3, 4), (5, 8), (6, 9), (7, 14), (10, 16), (13, 19), (17, 20),
+        (0, 6), (1, 3), (2, 18), (4, 15), (5, 10), (8, 16), (11, 17), (12, 13), (14, 20),
+        (2, 6), (5, 12), (7, 18), (8, 14), (9, 11), (10, 17), (13, 19), (16, 20),
+        (1, 2), (4, 7), (5, 9), (6, 17), (10, 13), (11, 12), (14, 19), (15, 18),
+        (0, 2), (3, 6), (4, 5), (7, 10), (8, 11), (9, 15), (12, 16), (13, 18), (14, 17), (19, 20),
+        (0, 1), (2, 3), (5, 9), (6, 12), (7, 8), (11, 14), (13, 15), (16, 19), (17, 18),
+        (1, 2), (3, 9), (6, 13), (10, 11), (12, 15), (16, 17), (18, 19),
+        (1, 4), (2, 5), (3, 7), (6, 10), (8, 9), (11, 12), (13, 14), (17, 18),
+        (2, 4), (5, 6), (7, 8), (9, 11), (10, 13), (12, 15), (14, 16),
+        (3, 4), (5, 7), (6, 8), (9, 10), (11, 13), (12, 14), (15, 16),
+        (4, 5), (6, 7), (8, 9), (10, 11), (12, 13), (14, 15), (16, 17),
+    ],
+    // 22 elements, 108 comparators (smallest known 107).
+    &[
+        (0, 21), (1, 2), (3, 4), (5, 6), (7, 8), (9, 10), (11, 12), (13, 14), (15, 16), (17, 18),
+        (19, 20),
+        (0, 19), (2, 21), (3, 5), (4, 6), (7, 9), (8, 10), (11, 13), (12, 14), (15, 17), (16, 18),
+        (3, 7), (4, 8), (5, 9), (6, 10), (11, 15), (12, 16), (13, 17), (14, 18),
+        (3, 11), (4, 12), (5, 13), (6, 14), (7, 15), (8, 16), (9, 17), (10, 18),
+        (0, 6), (1, 4), (15, 21), (17, 20),
+        (0, 1), (2, 17), (4, 19), (6, 15), (20, 21),
+        (0, 3), (1, 7), (4, 13), (8, 17), (14, 20), (18, 21),
+        (2, 8), (4, 5), (13, 19), (16, 17),
+        (1, 4), (2, 11), (5, 7), (8, 12), (9, 13), (10, 19), (14, 16), (17, 20),
+        (2, 3), (6, 8), (7, 11), (10, 14), (13, 15), (18, 19),
+        (1, 2), (3, 4), (5, 6), (8, 9), (12, 13), (15, 16), (17, 18), (19, 20),
+        (2, 3), (7, 8), (9, 10), (11, 12), (13, 14), (18, 19),
+        (4, 7), (9, 11), (10, 12), (14, 17),
+        (4, 5), (6, 7), (10, 11), (14, 15), (16, 17),
+        (3, 4), (5, 6), (7, 9), (12, 14), (15, 16), (17, 18),
+        (8, 9), (12, 13),
+        (6, 8), (9, 10), (11, 12), (13, 15),
+        (7, 8), (10, 11), (13, 14),
+    ],
+    // 23 elements, 118 comparators (smallest known 115).
+    &[
+        (0, 8), (1, 7), (2, 6), (4, 10), (5, 9), (11, 19), (12, 18), (13, 17), (14, 22), (15, 21),
+        (16, 20),
+        (0, 1), (2, 5), (3, 4), (6, 9), (7, 8), (11, 12), (13, 16), (14, 15), (17, 20), (18, 19),
+        (21, 22),
+        (0, 2), (1, 6), (5, 10), (11, 13), (12, 17), (16, 21), (20, 22),
+        (0, 3), (1, 2), (4, 6), (5, 7), (9, 10), (11, 14), (12, 13), (15, 17), (16, 18), (19, 22),
+        (20, 21),
+        (1, 4), (3, 5), (6, 8), (7, 10), (12, 15), (14, 16), (17, 19), (18, 21),
+        (1, 3), (2, 5), (6, 9), (8, 10), (12, 14), (13, 16), (17, 20), (19, 21),
+        (0, 12), (2, 3), (4, 5), (6, 7), (8, 9), (10, 22), (13, 14), (15, 16), (17, 18), (19, 20),
+        (1, 13), (4, 6), (5, 7), (9, 21), (15, 17), (16, 18),
+        (3, 4), (5, 6), (7, 8), (9, 13), (14, 15), (16, 17), (18, 19),
+        (2, 14), (3, 15), (4, 16), (5, 17), (6, 18), (7, 19), (8, 20),
+        (5, 9), (7, 11), (8, 12), (10, 14), (13, 17),
+        (3, 7), (4, 8), (6, 10), (11, 15), (12, 16), (14, 18), (17, 19),
+        (1, 3), (2, 4), (5, 7), (6, 8), (9, 11), (10, 12), (13, 15), (14, 16), (18, 20),
+        (0, 1), (2, 3), (4, 5), (6, 7), (8, 9), (10, 11), (12, 13), (14, 15), (16, 17), (18, 19),
+        (20, 21),
+    ],
+    // 24 elements, 123 comparators (smallest known 120).
+    &[
+        (0, 8), (1, 7), (2, 6), (3, 11), (4, 10), (5, 9), (12, 20), (13, 19), (14, 18), (15, 23),
+        (16, 22), (17, 21),
+        (0, 1), (2, 5), (3, 4), (6, 9), (7, 8), (10, 11), (12, 13), (14, 17), (15, 16), (18, 21),
+        (19, 20), (22, 23),
+        (0, 2), (1, 6), (5, 10), (9, 11), (12, 14), (13, 18), (17, 22), (21, 23),
+        (0, 3), (1, 2), (4, 6), (5, 7), (8, 11), (9, 10), (12, 15), (13, 14), (16, 18), (17, 19),
+        (20, 23), (21, 22),
+        (0, 12), (1, 4), (3, 5), (6, 8), (7, 10), (11, 23), (13, 16), (15, 17), (18, 20), (19, 22),
+        (1, 3), (2, 5), (6, 9), (8, 10), (13, 15), (14, 17), (18, 21), (20, 22),
+        (1, 13), (2, 3), (4, 5), (6, 7), (8, 9), (10, 22), (14, 15), (16, 17), (18, 19), (20, 21),
+        (2, 14), (4, 6), (5, 7), (9, 21), (16, 18), (17, 19),
+        (3, 4), (5, 6), (7, 8), (9, 13), (10, 14), (15, 16), (17, 18), (19, 20),
+        (3, 15), (4, 16), (5, 17), (6, 18), (7, 19), (8, 20),
+        (5, 9), (6, 10), (8, 12), (11, 15), (13, 17), (14, 18),
+        (3, 5), (4, 8), (7, 11), (12, 16), (15, 19), (18, 20),
+        (2, 4), (6, 8), (7, 9), (10, 12), (11, 13), (14, 16), (15, 17), (19, 21),
+        (1, 2), (3, 4), (5, 6), (7, 8), (9, 10), (11, 12), (13, 14), (15, 16), (17, 18), (19, 20),
+        (21, 22),
+    ],
+    // 25 elements, 133 comparators (smallest known 132).
+    &[
+        (0, 8), (1, 7), (2, 6), (3, 11), (4, 10), (5, 9), (12, 24), (13, 22), (14, 21), (15, 19),
+        (17, 23), (18, 20),
+        (0, 1), (2, 5), (3, 4), (6, 9), (7, 8), (10, 11), (13, 18), (14, 15), (16, 23), (19, 21),
+        (20, 22),
+        (0, 2), (1, 6), (5, 10), (9, 11), (12, 16), (13, 14), (15, 18), (19, 20), (21, 22),
+        (23, 24),
+        (0, 3), (1, 2), (4, 6), (5, 7), (8, 11), (9, 10), (16, 18), (17, 21), (20, 23), (22, 24),
+        (1, 4), (3, 5), (6, 8), (7, 10), (11, 24), (12, 17), (15, 20), (16, 19), (18, 23), (21, 22),
+        (1, 3), (2, 5), (6, 9), (8, 10), (12, 13), (14, 17), (18, 21), (19, 20), (22, 23),
+        (2, 3), (4, 5), (6, 7), (8, 9), (10, 23), (13, 15), (14, 16), (17, 18), (21, 22),
+        (4, 6), (5, 7), (9, 22), (13, 14), (15, 16), (17, 19), (18, 20),
+        (0, 13), (3, 4), (5, 6), (7, 8), (14, 15), (16, 17), (18, 19), (20, 21),
+        (1, 14), (6, 19), (7, 20), (8, 21), (15, 16), (17, 18),
+        (2, 15), (3, 16), (4, 17), (5, 18), (7, 12), (8, 13), (9, 14),
+        (3, 7), (4, 8), (5, 9), (10, 15), (11, 16), (13, 17), (14, 18),
+        (1, 3), (2, 4), (5, 7), (6, 10), (11, 12), (15, 19), (16, 20),
+        (0, 1), (2, 3), (4, 5), (6, 8), (9, 11), (10, 13), (12, 14), (15, 17), (16, 18), (19, 21),
+        (20, 22),
+        (6, 7), (8, 9), (10, 11), (12, 13), (14, 15), (16, 17), (18, 19), (20, 21), (22, 23),
+    ],
+    // 26 elements, 140 comparators (smallest known 139).
+    &[
+        (0, 12), (1, 10), (2, 9), (3, 7), (5, 11), (6, 8), (13, 25), (14, 23), (15, 22), (16, 20),
+        (18, 24), (19, 21),
+        (1, 6), (2, 3), (4, 11), (7, 9), (8, 10), (14, 19), (15, 16), (17, 24), (20, 22), (21, 23),
+        (0, 4), (1, 2), (3, 6), (7, 8), (9, 10), (11, 12), (13, 17), (14, 15), (16, 19), (20, 21),
+        (22, 23), (24, 25),
+        (4, 6), (5, 9), (8, 11), (10, 12), (17, 19), (18, 22), (21, 24), (23, 25),
+        (0, 5), (3, 8), (4, 7), (6, 11), (9, 10), (12, 25), (13, 18), (16, 21), (17, 20), (19, 24),
+        (22, 23),
+        (0, 1), (2, 5), (6, 9), (7, 8), (10, 11), (13, 14), (15, 18), (19, 22), (20, 21), (23, 24),
+        (0, 13), (1, 3), (2, 4), (5, 6), (9, 10), (11, 24), (14, 16), (15, 17), (18, 19), (22, 23),
+        (1, 2), (3, 4), (5, 7), (6, 8), (10, 23), (14, 15), (16, 17), (18, 20), (19, 21),
+        (1, 14), (2, 3), (4, 5), (6, 7), (8, 9), (15, 16), (17, 18), (19, 20), (21, 22),
+        (2, 15), (3, 4), (5, 6), (7, 20), (8, 21), (9, 22), (16, 17), (18, 19),
+        (3, 16), (4, 17), (5, 18), (6, 19), (8, 13), (9, 14), (10, 15),
+        (4, 8), (5, 9), (6, 10), (11, 16), (12, 17), (14, 18), (15, 19),
+        (2, 4), (3, 5), (6, 8), (7, 11), (12, 13), (16, 20), (17, 21),
+        (1, 2), (3, 4), (5, 6), (7, 9), (10, 12), (11, 14), (13, 15), (16, 18), (17, 19), (20, 22),
+        (21, 23),
+        (7, 8), (9, 10), (11, 12), (13, 14), (15, 16), (17, 18), (19, 20), (21, 22), (23, 24),
+    ],
+    // 27 elements, 150 comparators.
+    &[
+        (0, 13), (1, 12), (2, 15), (3, 14), (4, 8), (5, 6), (7, 11), (9, 10), (16, 24), (17, 23),
+        (18, 22), (20, 26), (21, 25),
+        (0, 5), (1, 7), (2, 9), (3, 4), (6, 13), (8, 14), (10, 15), (11, 12), (16, 17), (18, 21),
+        (19, 20), (22, 25), (23, 24),
+        (0, 1), (2, 3), (4, 5), (6, 8), (7, 9), (10, 11), (12, 13), (14, 15), (16, 18), (17, 22),
+        (21, 26),
+        (0, 2), (1, 3), (4, 10), (5, 11), (6, 7), (8, 9), (12, 14), (13, 15), (16, 19), (17, 18),
+        (20, 22), (21, 23), (25, 26),
+        (1, 2), (3, 12), (4, 6), (5, 7), (8, 10), (9, 11), (13, 14), (17, 20), (19, 21), (22, 24),
+        (23, 26),
+        (1, 4), (2, 6), (5, 8), (7, 10), (9, 13), (11, 14), (17, 19), (18, 21), (22, 25), (24, 26),
+        (1, 16), (2, 4), (3, 6), (9, 12), (11, 13), (15, 26), (18, 19), (20, 21), (22, 23),
+        (24, 25),
+        (3, 5), (6, 8), (7, 9), (10, 12), (14, 25), (20, 22), (21, 23),
+        (3, 4), (5, 6), (7, 8), (9, 10), (11, 12), (19, 20), (21, 22), (23, 24),
+        (0, 19), (2, 21), (3, 22), (4, 23), (5, 20), (6, 7), (8, 9),
+        (0, 1), (2, 17), (3, 18), (4, 19), (9, 24), (10, 21), (11, 22), (12, 23),
+        (1, 2), (3, 16), (4, 17), (7, 18), (8, 19), (13, 24), (14, 23), (15, 22),
+        (2, 3), (5, 16), (6, 17), (12, 19), (13, 20), (14, 21), (15, 18), (22, 24),
+        (4, 5), (7, 16), (11, 15), (14, 19), (18, 20), (22, 23), (24, 25),
+        (6, 7), (9, 16), (14, 17), (18, 19), (20, 21),
+        (10, 14), (13, 16),
+        (8, 10), (11, 13), (12, 14), (15, 16),
+        (8, 9), (10, 11), (12, 13), (14, 15), (16, 17),
+    ],
+    // 28 elements, 156 comparators (smallest known 155).
+    &[
+        (0, 8), (1, 7), (2, 6), (3, 11), (4, 10), (5, 9), (12, 25), (13, 24), (14, 27), (15, 26),
+        (16, 20), (17, 18), (19, 23), (21, 22),
+        (0, 1), (2, 5), (3, 4), (6, 9), (7, 8), (10, 11), (12, 17), (13, 19), (14, 21), (15, 16),
+        (18, 25), (20, 26), (22, 27), (23, 24),
+        (0, 2), (1, 6), (5, 10), (9, 11), (12, 13), (14, 15), (16, 17), (18, 20), (19, 21),
+        (22, 23), (24, 25), (26, 27),
+        (0, 3), (1, 2), (4, 6), (5, 7), (8, 11), (9, 10), (12, 14), (13, 15), (16, 22), (17, 23),
+        (18, 19), (20, 21), (24, 26), (25, 27),
+        (1, 4), (3, 5), (6, 8), (7, 10), (11, 27), (13, 14), (15, 24), (16, 18), (17, 19), (20, 22),
+        (21, 23), (25, 26),
+        (1, 3), (2, 5), (6, 9), (8, 10), (13, 16), (14, 18), (17, 20), (19, 22), (21, 25), (23, 26),
+        (2, 3), (4, 5), (6, 7), (8, 9), (10, 26), (14, 16), (15, 18), (21, 24), (23, 25),
+        (4, 6), (5, 7), (9, 25), (15, 17), (18, 20), (19, 21), (22, 24),
+        (3, 4), (5, 6), (7, 8), (15, 16), (17, 18), (19, 20), (21, 22), (23, 24),
+        (0, 16), (1, 17), (6, 22), (7, 23), (8, 24), (18, 19), (20, 21),
+        (2, 18), (3, 19), (4, 20), (5, 21), (6, 14), (7, 15), (8, 16), (9, 17),
+        (2, 6), (3, 7), (4, 12), (5, 13), (10, 18), (11, 19), (16, 20), (17, 21),
+        (0, 4), (1, 5), (8, 12), (9, 13), (10, 14), (11, 15), (18, 22), (19, 23),
+        (2, 4), (3, 5), (6, 8), (7, 9), (10, 12), (11, 13), (14, 16), (15, 17), (18, 20), (19, 21),
+        (22, 24), (23, 25),
+        (1, 2), (3, 4), (5, 6), (7, 8), (9, 10), (11, 12), (13, 14), (15, 16), (17, 18), (19, 20),
+        (21, 22), (23, 24), (25, 26),
+    ],
+    // 29 elements, 165 comparators.
+    &[
+        (0, 12), (1, 10), (2, 9), (3, 7), (5, 11), (6, 8), (13, 26), (14, 25), (15, 28), (16, 27),
+        (17, 21), (18, 19), (20, 24), (22, 23),
+        (1, 6), (2, 3), (4, 11), (7, 9), (8, 10), (13, 18), (14, 20), (15, 22), (16, 17), (19, 26),
+        (21, 27), (23, 28), (24, 25),
+        (0, 4), (1, 2), (3, 6), (7, 8), (9, 10), (11, 12), (13, 14), (15, 16), (17, 18), (19, 21),
+        (20, 22), (23, 24), (25, 26), (27, 28),
+        (4, 6), (5, 9), (8, 11), (10, 12), (13, 15), (14, 16), (17, 23), (18, 24), (19, 20),
+        (21, 22), (25, 27), (26, 28),
+        (0, 5), (3, 8), (4, 7), (6, 11), (9, 10), (12, 28), (14, 15), (16, 25), (17, 19), (18, 20),
+        (21, 23), (22, 24), (26, 27),
+        (0, 1), (2, 5), (6, 9), (7, 8), (10, 11), (14, 17), (15, 19), (18, 21), (20, 23), (22, 26),
+        (24, 27),
+        (1, 3), (2, 4), (5, 6), (9, 10), (11, 27), (15, 17), (16, 19), (22, 25), (24, 26),
+        (1, 2), (3, 4), (5, 7), (6, 8), (10, 26), (16, 18), (19, 21), (20, 22), (23, 25),
+        (2, 3), (4, 5), (6, 7), (8, 9), (16, 17), (18, 19), (20, 21), (22, 23), (24, 25),
+        (0, 16), (1, 17), (2, 18), (3, 4), (5, 6), (7, 23), (8, 24), (9, 25), (19, 20), (21, 22),
+        (3, 19), (4, 20), (5, 21), (6, 22), (7, 15), (8, 16), (9, 17), (10, 18),
+        (3, 7), (4, 8), (5, 13), (6, 14), (11, 19), (12, 20), (17, 21), (18, 22),
+        (1, 5), (2, 6), (9, 13), (10, 14), (11, 15), (12, 16), (19, 23), (20, 24),
+        (0, 2), (3, 5), (4, 6), (7, 9), (8, 10), (11, 13), (12, 14), (15, 17), (16, 18), (19, 21),
+        (20, 22), (23, 25), (24, 26),
+        (0, 1), (2, 3), (4, 5), (6, 7), (8, 9), (10, 11), (12, 13), (14, 15), (16, 17), (18, 19),
+        (20, 21), (22, 23), (24, 25), (26, 27),
+    ],
+    // 30 elements, 172 comparators.
+    &[
+        (0, 11), (1, 14), (2, 13), (3, 7), (4, 5), (6, 10), (8, 9), (15, 26), (16, 29), (17, 28),
+        (18, 22), (19, 20), (21, 25), (23, 24),
+        (0, 6), (1, 8), (2, 3), (5, 12), (7, 13), (9, 14), (10, 11), (15, 21), (16, 23), (17, 18),
+        (20, 27), (22, 28), (24, 29), (25, 26),
+        (1, 2), (3, 4), (5, 7), (6, 8), (9, 10), (11, 12), (13, 14), (16, 17), (18, 19), (20, 22),
+        (21, 23), (24, 25), (26, 27), (28, 29),
+        (0, 2), (3, 9), (4, 10), (5, 6), (7, 8), (11, 13), (12, 14), (15, 17), (18, 24), (19, 25),
+        (20, 21), (22, 23), (26, 28), (27, 29),
+        (0, 1), (2, 11), (3, 5), (4, 6), (7, 9), (8, 10), (12, 13), (14, 29), (15, 16), (17, 26),
+        (18, 20), (19, 21), (22, 24), (23, 25), (27, 28),
+        (0, 3), (1, 5), (4, 7), (6, 9), (8, 12), (10, 13), (15, 18), (16, 20), (19, 22), (21, 24),
+        (23, 27), (25, 28),
+        (0, 15), (1, 3), (2, 5), (8, 11), (10, 12), (13, 28), (16, 18), (17, 20), (23, 26),
+        (25, 27),
+        (1, 16), (2, 4), (5, 7), (6, 8), (9, 11), (12, 27), (17, 19), (20, 22), (21, 23), (24, 26),
+        (2, 3), (4, 5), (6, 7), (8, 9), (10, 11), (17, 18), (19, 20), (21, 22), (23, 24), (25, 26),
+        (2, 17), (3, 18), (4, 19), (5, 6), (7, 8), (9, 24), (10, 25), (11, 26), (20, 21), (22, 23),
+        (5, 20), (6, 21), (7, 22), (8, 23), (9, 16), (10, 17), (11, 18), (12, 19),
+        (5, 9), (6, 10), (7, 11), (8, 15), (13, 20), (14, 21), (18, 22), (19, 23),
+        (3, 5), (4, 8), (7, 9), (12, 15), (13, 16), (14, 17), (20, 24), (21, 25),
+        (2, 4), (6, 8), (10, 12), (11, 13), (14, 15), (16, 18), (17, 19), (20, 22), (21, 23),
+        (24, 26), (25, 27),
+        (1, 2), (3, 4), (5, 6), (7, 8), (9, 10), (11, 12), (13, 14), (15, 16), (17, 18), (19, 20),
+        (21, 22), (23, 24), (25, 26), (27, 28),
+    ],
+    // 31 elements, 180 comparators.
+    &[
+        (0, 11), (1, 14), (2, 13), (3, 7), (4, 5), (6, 10), (8, 9), (15, 28), (16, 27), (17, 30),
+        (18, 29), (19, 23), (20, 21), (22, 26), (24, 25),
+        (0, 6), (1, 8), (2, 3), (5, 12), (7, 13), (9, 14), (10, 11), (15, 20), (16, 22), (17, 24),
+        (18, 19), (21, 28), (23, 29), (25, 30), (26, 27),
+        (1, 2), (3, 4), (5, 7), (6, 8), (9, 10), (11, 12), (13, 14), (15, 16), (17, 18), (19, 20),
+        (21, 23), (22, 24), (25, 26), (27, 28), (29, 30),
+        (0, 2), (3, 9), (4, 10), (5, 6), (7, 8), (11, 13), (12, 14), (15, 17), (16, 18), (19, 25),
+        (20, 26), (21, 22), (23, 24), (27, 29), (28, 30),
+        (0, 1), (2, 11), (3, 5), (4, 6), (7, 9), (8, 10), (12, 13), (14, 30), (16, 17), (18, 27),
+        (19, 21), (20, 22), (23, 25), (24, 26), (28, 29),
+        (0, 3), (1, 5), (4, 7), (6, 9), (8, 12), (10, 13), (16, 19), (17, 21), (20, 23), (22, 25),
+        (24, 28), (26, 29),
+        (0, 16), (1, 3), (2, 5), (8, 11), (10, 12), (13, 29), (17, 19), (18, 21), (24, 27),
+        (26, 28),
+        (1, 17), (2, 4), (5, 7), (6, 8), (9, 11), (12, 28), (18, 20), (21, 23), (22, 24), (25, 27),
+        (2, 3), (4, 5), (6, 7), (8, 9), (10, 11), (18, 19), (20, 21), (22, 23), (24, 25), (26, 27),
+        (2, 18), (3, 19), (4, 20), (5, 6), (7, 8), (9, 25), (10, 26), (11, 27), (21, 22), (23, 24),
+        (5, 21), (6, 22), (7, 23), (8, 24), (9, 17), (10, 18), (11, 19), (12, 20),
+        (5, 9), (6, 10), (7, 15), (8, 16), (13, 21), (14, 22), (19, 23), (20, 24),
+        (3, 7), (4, 8), (11, 15), (12, 16), (13, 17), (14, 18), (21, 25), (22, 26),
+        (1, 3), (2, 4), (5, 7), (6, 8), (9, 11), (10, 12), (13, 15), (14, 16), (17, 19), (18, 20),
+        (21, 23), (22, 24), (25, 27), (26, 28),
+        (0, 1), (2, 3), (4, 5), (6, 7), (8, 9), (10, 11), (12, 13), (14, 15), (16, 17), (18, 19),
+        (20, 21), (22, 23), (24, 25), (26, 27), (28, 29),
+    ],
+    // 32 elements, 185 comparators.
+    &[
+        (0, 13), (1, 12), (2, 15), (3, 14), (4, 8), (5, 6), (7, 11), (9, 10), (16, 29), (17, 28),
+        (18, 31), (19, 30), (20, 24), (21, 22), (23, 27), (25, 26),
+        (0, 5), (1, 7), (2, 9), (3, 4), (6, 13), (8, 14), (10, 15), (11, 12), (16, 21), (17, 23),
+        (18, 25), (19, 20), (22, 29), (24, 30), (26, 31), (27, 28),
+        (0, 1), (2, 3), (4, 5), (6, 8), (7, 9), (10, 11), (12, 13), (14, 15), (16, 17), (18, 19),
+        (20, 21), (22, 24), (23, 25), (26, 27), (28, 29), (30, 31),
+        (0, 2), (1, 3), (4, 10), (5, 11), (6, 7), (8, 9), (12, 14), (13, 15), (16, 18), (17, 19),
+        (20, 26), (21, 27), (22, 23), (24, 25), (28, 30), (29, 31),
+        (0, 16), (1, 2), (3, 12), (4, 6), (5, 7), (8, 10), (9, 11), (13, 14), (15, 31), (17, 18),
+        (19, 28), (20, 22), (21, 23), (24, 26), (25, 27), (29, 30),
+        (1, 4), (2, 6), (5, 8), (7, 10), (9, 13), (11, 14), (17, 20), (18, 22), (21, 24), (23, 26),
+        (25, 29), (27, 30),
+        (1, 17), (2, 4), (3, 6), (9, 12), (11, 13), (14, 30), (18, 20), (19, 22), (25, 28),
+        (27, 29),
+        (2, 18), (3, 5), (6, 8), (7, 9), (10, 12), (13, 29), (19, 21), (22, 24), (23, 25), (26, 28),
+        (3, 4), (5, 6), (7, 8), (9, 10), (11, 12), (19, 20), (21, 22), (23, 24), (25, 26), (27, 28),
+        (3, 19), (4, 20), (5, 21), (6, 7), (8, 9), (10, 26), (11, 27), (12, 28), (22, 23), (24, 25),
+        (6, 22), (7, 23), (8, 24), (9, 25), (10, 18), (11, 19), (12, 20), (13, 21),
+        (6, 10), (7, 11), (8, 16), (9, 17), (14, 22), (15, 23), (20, 24), (21, 25),
+        (4, 8), (5, 9), (12, 16), (13, 17), (14, 18), (15, 19), (22, 26), (23, 27),
+        (2, 4), (3, 5), (6, 8), (7, 9), (10, 12), (11, 13), (14, 16), (15, 17), (18, 20), (19, 21),
+        (22, 24), (23, 25), (26, 28), (27, 29),
+        (1, 2), (3, 4), (5, 6), (7, 8), (9, 10), (11, 12), (13, 14), (15, 16), (17, 18), (19, 20),
+        (21, 22), (23, 24), (25, 26), (27, 28), (29, 30),
+    ],
+];
@@ -0,0 +1,184 @@
+//! Parallel counterpart of the unstable sort. The partitions are done sequentially, and the
+//! recursion into the two sides of each partition is distributed across threads, until the
+//! sub-slices are small enough to be sorted sequentially.
+
+use core::mem;
+
+use crate::quicksort::partition;
+
+// Below this length, sub-slices are sorted with the sequential quicksort. Moving work to another
+// thread comes with a fixed overhead, which is only worth it for sufficiently large sub-slices.
+const MIN_PARALLEL_LEN: usize = 4096;
+
+/// Sorts `v` in parallel using pattern-defeating quicksort, which is *O*(*n* \* log(*n*))
+/// worst-case.
+pub(crate) fn par_unstable_sort<T, F>(v: &mut [T], is_less: &F)
+where
+    T: Send,
+    F: Fn(&T, &T) -> bool + Sync,
+{
+    // Sorting has no meaningful behavior on zero-sized types.
+    if mem::size_of::<T>() == 0 {
+        return;
+    }
+
+    let len = v.len();
+
+    if len <= MIN_PARALLEL_LEN {
+        crate::unstable_sort(v, is_less);
+        return;
+    }
+
+    let (streak_end, was_reversed) = crate::find_streak(v, &mut |a, b| is_less(a, b));
+    if streak_end == len {
+        if was_reversed {
+            v.reverse();
+        }
+
+        return;
+    }
+
+    // Limit the number of imbalanced partitions to `2 * floor(log2(len))`.
+    // The binary OR by one is used to eliminate the zero-check in the logarithm.
+    let limit = 2 * (len | 1).ilog2();
+
+    par_quicksort(v, is_less, None, limit, available_threads());
+}
+
+/// Sorts `v` recursively, using up to `threads` threads.
+///
+/// See [`crate::quicksort::quicksort`], the ancestor pivot is a mutable reference, because only
+/// those can be sent to another thread without requiring `T: Sync`.
+fn par_quicksort<'a, T, F>(
+    mut v: &'a mut [T],
+    is_less: &F,
+    mut ancestor_pivot: Option<&'a mut T>,
+    mut limit: u32,
+    threads: usize,
+) where
+    T: Send,
+    F: Fn(&T, &T) -> bool + Sync,
+{
+    let mut seq_is_less = |a: &T, b: &T| is_less(a, b);
+
+    loop {
+        if v.len() <= MIN_PARALLEL_LEN || threads <= 1 {
+            let ancestor_pivot = ancestor_pivot.map(|p| &*p);
+            crate::quicksort::quicksort(v, &mut seq_is_less, ancestor_pivot, limit);
+            return;
+        }
+
+        // If too many bad pivot choices were made, simply fall back to heapsort in order to
+        // guarantee `O(n * log(n))` worst-case.
+        if limit == 0 {
+            // SAFETY: We checked that the len is larger than `MIN_PARALLEL_LEN`.
+            unsafe {
+                crate::heapsort::heapsort(v, &mut seq_is_less);
+            }
+            return;
+        }
+
+        limit -= 1;
+
+        // Choose a pivot and try guessing whether the slice is already sorted.
+        let pivot_pos = crate::pivot::choose_pivot(v, &mut seq_is_less);
+
+        // If the chosen pivot is equal to the predecessor, then it's the smallest element in the
+        // slice. Partition the slice into elements equal to and elements greater than the pivot.
+        // This case is usually hit when the slice contains many duplicate elements.
+        if let Some(p) = ancestor_pivot.as_deref() {
+            // SAFETY: We assume choose_pivot yields an in-bounds position.
+            if !is_less(p, unsafe { v.get_unchecked(pivot_pos) }) {
+                let mid = partition(v, pivot_pos, &mut |a, b| !is_less(b, a));
+                report!(equal_partition(v.len(), mid + 1));
+
+                // Continue sorting elements greater than the pivot. We know that mid contains the
+                // pivot. So we can continue after mid.
+                v = &mut v[(mid + 1)..];
+                ancestor_pivot = None;
+                continue;
+            }
+        }
+
+        // Partition the slice.
+        let mid = partition(v, pivot_pos, &mut seq_is_less);
+
+        // Split the slice into `left`, `pivot`, and `right`.
+        let (left, right) = v.split_at_mut(mid);
+        let (pivot, right) = right.split_at_mut(1);
+        let pivot = &mut pivot[0];
+
+        if left.len() <= MIN_PARALLEL_LEN || right.len() <= MIN_PARALLEL_LEN {
+            // Handing off a side that will be sorted sequentially anyway isn't worth it, sort the
+            // left side here and continue with all threads on the right side.
+            par_quicksort(left, is_less, ancestor_pivot, limit, threads);
+        } else {
+            let (left_threads, right_threads) = split_threads(threads, left.len(), right.len());
+
+            join(
+                || par_quicksort(left, is_less, ancestor_pivot, limit, left_threads),
+                || par_quicksort(right, is_less, Some(pivot), limit, right_threads),
+            );
+            return;
+        }
+
+        v = right;
+        ancestor_pivot = Some(pivot);
+    }
+}
+
+// --- Thread pool ---
+
+#[cfg(feature = "rayon")]
+fn available_threads() -> usize {
+    rayon::current_num_threads()
+}
+
+/// rayon balances the work between its threads by itself, every task may use all threads.
+#[cfg(feature = "rayon")]
+fn split_threads(threads: usize, _left_len: usize, _right_len: usize) -> (usize, usize) {
+    (threads, threads)
+}
+
+#[cfg(feature = "rayon")]
+fn join<A, B>(a: A, b: B)
+where
+    A: FnOnce() + Send,
+    B: FnOnce() + Send,
+{
+    rayon::join(a, b);
+}
+
+#[cfg(not(feature = "rayon"))]
+fn available_threads() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Without work-stealing every spawned thread is a fixed commitment, so the threads are split
+/// proportionally to the amount of work on each side.
+#[cfg(not(feature = "rayon"))]
+fn split_threads(threads: usize, left_len: usize, right_len: usize) -> (usize, usize) {
+    let total_len = (left_len + right_len) as u128;
+    let left_threads = ((threads as u128 * left_len as u128) / total_len) as usize;
+    let left_threads = left_threads.clamp(1, threads - 1);
+
+    (left_threads, threads - left_threads)
+}
+
+/// Runs `a` on a newly spawned scoped thread and `b` on the current thread. If either panics, the
+/// panic is propagated after both have finished.
+#[cfg(not(feature = "rayon"))]
+fn join<A, B>(a: A, b: B)
+where
+    A: FnOnce() + Send,
+    B: FnOnce() + Send,
+{
+    std::thread::scope(|s| {
+        let handle = s.spawn(a);
+        b();
+
+        if let Err(payload) = handle.join() {
+            std::panic::resume_unwind(payload);
+        }
+    });
+}
@@ -1,4 +1,4 @@
-use core::intrinsics;
+use crate::intrinsics;
 
 const PSEUDO_MEDIAN_REC_THRESHOLD: usize = 64;
 
@@ -26,7 +26,7 @@ where
 
     // SAFETY: Assuming that `max_len_small_sort::<T>()` is larger than 0 all pointer calculations
     // below yield valid in-bounds pointers.
-    unsafe {
+    let pivot_pos = unsafe {
         let median_guess = if len < PSEUDO_MEDIAN_REC_THRESHOLD {
             median3(v_base, v_base.add(len_div_2), v_base.add(len - 1), is_less)
         } else {
@@ -38,8 +38,12 @@ where
             median3_rec(a, b, c, len_div_8, is_less)
         };
 
-        median_guess.sub_ptr(v_base)
-    }
+        crate::sub_ptr(median_guess, v_base)
+    };
+
+    report!(choose_pivot(len, pivot_pos));
+
+    pivot_pos
 }
 
 /// Calculates an approximate median of 3 elements from sections a, b, c, or recursively from an
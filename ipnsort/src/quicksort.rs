@@ -1,9 +1,11 @@
-use core::intrinsics;
 use core::mem::{self, ManuallyDrop};
 use core::ptr;
 
+use crate::intrinsics;
 use crate::smallsort::SmallSortImpl;
-use crate::{GapGuard, IsTrue};
+use crate::GapGuard;
+#[cfg(all(feature = "nightly", not(feature = "opt_size")))]
+use crate::IsTrue;
 
 /// Sorts `v` recursively.
 ///
@@ -19,10 +21,12 @@ pub(crate) fn quicksort<'a, T, F>(
 ) where
     F: FnMut(&T, &T) -> bool,
 {
-    loop {
-        // println!("len: {}", v.len());
+    #[cfg(feature = "instrument")]
+    let _depth_guard = crate::instrument::enter_quicksort(v.len());
 
+    loop {
         if v.len() <= T::SMALL_SORT_THRESHOLD {
+            report!(small_sort(v.len()));
             T::small_sort(v, is_less);
             return;
         }
@@ -49,6 +53,7 @@ pub(crate) fn quicksort<'a, T, F>(
             // SAFETY: We assume choose_pivot yields an in-bounds position.
             if !is_less(p, unsafe { v.get_unchecked(pivot_pos) }) {
                 let mid = partition(v, pivot_pos, &mut |a, b| !is_less(b, a));
+                report!(equal_partition(v.len(), mid + 1));
 
                 // Continue sorting elements greater than the pivot. We know that mid contains the
                 // pivot. So we can continue after mid.
@@ -113,7 +118,7 @@ pub(crate) fn quicksort<'a, T, F>(
 /// unspecified. All original elements will remain in `v` and any possible modifications via
 /// interior mutability will be observable. Same is true if `is_less` panics or `v.len()`
 /// exceeds `scratch.len()`.
-fn partition<T, F>(v: &mut [T], pivot: usize, is_less: &mut F) -> usize
+pub(crate) fn partition<T, F>(v: &mut [T], pivot: usize, is_less: &mut F) -> usize
 where
     F: FnMut(&T, &T) -> bool,
 {
@@ -145,9 +150,31 @@ where
     // Place the pivot between the two partitions.
     v.swap(0, lt_count);
 
+    report!(partition(len, lt_count));
+
     lt_count
 }
 
+/// See [`crate::partition3`].
+pub(crate) fn partition3<T, F>(v: &mut [T], pivot: &T, is_less: &mut F) -> (usize, usize)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    // The partitions don't support zero-sized types, which are all equal to each other.
+    if mem::size_of::<T>() == 0 {
+        return (0, v.len());
+    }
+
+    let lt_len = T::partition(v, pivot, is_less);
+
+    // Of the remaining elements, the ones not greater than `pivot` are equal to it.
+    let eq_len = T::partition(&mut v[lt_len..], pivot, &mut |elem, pivot| {
+        !is_less(pivot, elem)
+    });
+
+    (lt_len, eq_len)
+}
+
 trait PartitionImpl: Sized {
     /// See [`partition`].
     fn partition<F>(v: &mut [Self], pivot: &Self, is_less: &mut F) -> usize
@@ -155,6 +182,7 @@ trait PartitionImpl: Sized {
         F: FnMut(&Self, &Self) -> bool;
 }
 
+#[cfg(all(feature = "nightly", not(feature = "opt_size")))]
 impl<T> PartitionImpl for T {
     default fn partition<F>(v: &mut [T], pivot: &T, is_less: &mut F) -> usize
     where
@@ -164,15 +192,17 @@ impl<T> PartitionImpl for T {
     }
 }
 
+#[cfg(not(feature = "opt_size"))]
 const MAX_BRANCHLESS_PARTITION_SIZE: usize = 96;
 
 /// Specialize for types that are relatively cheap to copy, where branchless optimizations have
 /// large leverage e.g. `u64` and `String`.
+#[cfg(all(feature = "nightly", not(feature = "opt_size")))]
 impl<T> PartitionImpl for T
 where
     (): IsTrue<{ mem::size_of::<T>() <= MAX_BRANCHLESS_PARTITION_SIZE }>,
 {
-    fn partition<F>(v: &mut [T], pivot: &T, is_less: &mut F) -> usize
+    default fn partition<F>(v: &mut [T], pivot: &T, is_less: &mut F) -> usize
     where
         F: FnMut(&T, &T) -> bool,
     {
@@ -180,7 +210,67 @@ where
     }
 }
 
+/// Specialize for primitive types that can be compared with SIMD instructions, as long as `is_less`
+/// is known to be their natural order. The SIMD partition ignores `is_less`.
+#[cfg(all(feature = "simd", not(feature = "opt_size")))]
+macro_rules! simd_partition_impl {
+    ($t:ty) => {
+        impl PartitionImpl for $t {
+            fn partition<F>(v: &mut [$t], pivot: &$t, is_less: &mut F) -> usize
+            where
+                F: FnMut(&$t, &$t) -> bool,
+            {
+                if <F as crate::simd::IsOrdLess>::IS_ORD_LESS {
+                    crate::simd::partition(v, pivot)
+                } else {
+                    partition_lomuto_branchless_cyclic(v, pivot, is_less)
+                }
+            }
+        }
+    };
+}
+
+#[cfg(all(feature = "simd", not(feature = "opt_size")))]
+simd_partition_impl!(i32);
+#[cfg(all(feature = "simd", not(feature = "opt_size")))]
+simd_partition_impl!(u32);
+#[cfg(all(feature = "simd", not(feature = "opt_size")))]
+simd_partition_impl!(u64);
+
+/// Same selection as the specialized version, the size check is a constant so only the chosen
+/// implementation ends up in the generated code.
+#[cfg(all(not(feature = "nightly"), not(feature = "opt_size")))]
+impl<T> PartitionImpl for T {
+    #[inline(always)]
+    fn partition<F>(v: &mut [T], pivot: &T, is_less: &mut F) -> usize
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        if mem::size_of::<T>() <= MAX_BRANCHLESS_PARTITION_SIZE {
+            partition_lomuto_branchless_cyclic(v, pivot, is_less)
+        } else {
+            partition_hoare_branchy_cyclic(v, pivot, is_less)
+        }
+    }
+}
+
+/// With `opt_size` all types use the Hoare partition, which is optimized for small code-gen.
+#[cfg(feature = "opt_size")]
+impl<T> PartitionImpl for T {
+    #[inline(always)]
+    fn partition<F>(v: &mut [T], pivot: &T, is_less: &mut F) -> usize
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        partition_hoare_branchy_cyclic(v, pivot, is_less)
+    }
+}
+
 /// See [`partition`].
+///
+/// With `opt_size` it's kept out of line, which allows the compiler to merge the identical
+/// instantiations for different types.
+#[cfg_attr(feature = "opt_size", inline(never))]
 fn partition_hoare_branchy_cyclic<T, F>(v: &mut [T], pivot: &T, is_less: &mut F) -> usize
 where
     F: FnMut(&T, &T) -> bool,
@@ -245,7 +335,7 @@ where
             left = left.add(1);
         }
 
-        left.sub_ptr(v_base)
+        crate::sub_ptr(left, v_base)
 
         // `gap_guard_opt` goes out of scope and overwrites the last right wrong-side element with
         // the first left wrong-side element that was initially overwritten by the first right
@@ -253,6 +343,7 @@ where
     }
 }
 
+#[cfg(not(feature = "opt_size"))]
 fn partition_lomuto_branchless_cyclic<T, F>(v: &mut [T], pivot: &T, is_less: &mut F) -> usize
 where
     F: FnMut(&T, &T) -> bool,
@@ -0,0 +1,225 @@
+//! Radix sort fast path for primitive integers, used by [`crate::sort`] with the `radix` feature.
+//!
+//! `sort` uses the natural `Ord` of the type, which for primitive integers is the same as
+//! comparing their bits, after flipping the sign bit of signed integers. That allows sorting by
+//! distributing the elements into buckets by their bits, without comparing them with each other.
+
+use alloc::vec;
+use core::mem;
+
+// Below this length the comparison sort is faster than setting up the buckets.
+const COUNTING_SORT_MIN_LEN: usize = 128;
+
+// The 16-bit counting sort needs a count table with 2^16 entries, which is only worth allocating
+// and scanning for inputs at least as large. Shorter inputs use the in-place radix sort.
+const COUNTING_SORT_16_MIN_LEN: usize = 1 << 16;
+
+// Buckets of up to this length get sorted with the comparison sort.
+const RADIX_SORT_MIN_LEN: usize = 256;
+
+pub(crate) trait SortImpl: Sized {
+    fn sort(v: &mut [Self]);
+}
+
+impl<T: Ord> SortImpl for T {
+    default fn sort(v: &mut [T]) {
+        crate::unstable_sort(v, crate::ord_is_less());
+    }
+}
+
+/// An integer type that can be sorted by its bits.
+trait RadixKey: Copy + Ord {
+    /// Returns the bits of `self`, in an order preserving way.
+    fn to_bits(self) -> u64;
+
+    /// The inverse of `to_bits`.
+    fn from_bits(bits: u64) -> Self;
+}
+
+macro_rules! radix_key_impl {
+    ($t:ty, $unsigned:ty) => {
+        impl RadixKey for $t {
+            #[inline(always)]
+            fn to_bits(self) -> u64 {
+                // Flipping the sign bit maps the signed range onto the unsigned range in order.
+                // It's zero for unsigned types.
+                const SIGN_BIT: $unsigned = (<$t>::MIN as $unsigned);
+                ((self as $unsigned) ^ SIGN_BIT) as u64
+            }
+
+            #[inline(always)]
+            fn from_bits(bits: u64) -> Self {
+                const SIGN_BIT: $unsigned = (<$t>::MIN as $unsigned);
+                ((bits as $unsigned) ^ SIGN_BIT) as $t
+            }
+        }
+    };
+}
+
+radix_key_impl!(u8, u8);
+radix_key_impl!(i8, u8);
+radix_key_impl!(u16, u16);
+radix_key_impl!(i16, u16);
+radix_key_impl!(u32, u32);
+radix_key_impl!(i32, u32);
+radix_key_impl!(u64, u64);
+radix_key_impl!(i64, u64);
+radix_key_impl!(usize, usize);
+radix_key_impl!(isize, usize);
+
+macro_rules! counting_sort_8_impl {
+    ($t:ty) => {
+        impl SortImpl for $t {
+            fn sort(v: &mut [Self]) {
+                if v.len() < COUNTING_SORT_MIN_LEN || sort_presorted(v) {
+                    crate::unstable_sort(v, crate::ord_is_less());
+                    return;
+                }
+
+                counting_sort(v, &mut [0; 1 << 8]);
+            }
+        }
+    };
+}
+
+counting_sort_8_impl!(u8);
+counting_sort_8_impl!(i8);
+
+macro_rules! counting_sort_16_impl {
+    ($t:ty) => {
+        impl SortImpl for $t {
+            fn sort(v: &mut [Self]) {
+                if v.len() < COUNTING_SORT_MIN_LEN || sort_presorted(v) {
+                    crate::unstable_sort(v, crate::ord_is_less());
+                    return;
+                }
+
+                if v.len() >= COUNTING_SORT_16_MIN_LEN {
+                    counting_sort(v, &mut vec![0; 1 << 16]);
+                } else {
+                    radix_sort(v, 8);
+                }
+            }
+        }
+    };
+}
+
+counting_sort_16_impl!(u16);
+counting_sort_16_impl!(i16);
+
+macro_rules! radix_sort_impl {
+    ($t:ty) => {
+        impl SortImpl for $t {
+            fn sort(v: &mut [Self]) {
+                if v.len() < COUNTING_SORT_MIN_LEN || sort_presorted(v) {
+                    crate::unstable_sort(v, crate::ord_is_less());
+                    return;
+                }
+
+                radix_sort(v, (mem::size_of::<$t>() as u32 - 1) * 8);
+            }
+        }
+    };
+}
+
+radix_sort_impl!(u32);
+radix_sort_impl!(i32);
+radix_sort_impl!(u64);
+radix_sort_impl!(i64);
+radix_sort_impl!(usize);
+radix_sort_impl!(isize);
+
+/// Returns `true` if `v` is fully ascending or descending, which the comparison sort handles in
+/// linear time.
+fn sort_presorted<K: RadixKey>(v: &[K]) -> bool {
+    crate::find_streak(v, &mut |a: &K, b: &K| a.lt(b)).0 == v.len()
+}
+
+/// Sorts `v` by counting how often each value occurs. `counts` must have one entry, initialized to
+/// zero, for every possible value of `K`.
+fn counting_sort<K: RadixKey>(v: &mut [K], counts: &mut [usize]) {
+    for elem in v.iter() {
+        counts[elem.to_bits() as usize] += 1;
+    }
+
+    let mut start = 0;
+    for (bits, &count) in counts.iter().enumerate() {
+        if count != 0 {
+            v[start..(start + count)].fill(K::from_bits(bits as u64));
+            start += count;
+        }
+    }
+}
+
+/// Sorts `v` in-place with a most significant digit first radix sort, also known as American flag
+/// sort, starting with the 8-bit digit at `shift`.
+///
+/// The elements are distributed into 256 buckets by the current digit, by swapping each element
+/// into the next free slot of its bucket. Then each bucket is sorted recursively by the next
+/// digit, so the recursion depth is limited by the number of bytes in `K`.
+fn radix_sort<K: RadixKey>(v: &mut [K], mut shift: u32) {
+    loop {
+        let len = v.len();
+
+        if len <= RADIX_SORT_MIN_LEN {
+            crate::unstable_sort(v, crate::ord_is_less());
+            return;
+        }
+
+        let digit = |elem: &K| (elem.to_bits() >> shift) as u8 as usize;
+
+        let mut counts = [0usize; 256];
+        for elem in v.iter() {
+            counts[digit(elem)] += 1;
+        }
+
+        // If all elements share the same digit, there is nothing to distribute. This is common
+        // for the upper digits of small values.
+        if counts.contains(&len) {
+            if shift == 0 {
+                return;
+            }
+
+            shift -= 8;
+            continue;
+        }
+
+        // `heads[d]` is the next free slot of bucket `d`, and `ends[d]` is its end.
+        let mut heads = [0usize; 256];
+        let mut ends = [0usize; 256];
+        let mut sum = 0;
+        for d in 0..256 {
+            heads[d] = sum;
+            sum += counts[d];
+            ends[d] = sum;
+        }
+
+        for d in 0..256 {
+            while heads[d] < ends[d] {
+                let elem_digit = digit(&v[heads[d]]);
+                if elem_digit == d {
+                    heads[d] += 1;
+                } else {
+                    // Swap the element into its bucket, and continue with the element that took
+                    // its place.
+                    v.swap(heads[d], heads[elem_digit]);
+                    heads[elem_digit] += 1;
+                }
+            }
+        }
+
+        if shift == 0 {
+            return;
+        }
+
+        let mut start = 0;
+        for &end in ends.iter() {
+            if end - start > 1 {
+                radix_sort(&mut v[start..end], shift - 8);
+            }
+            start = end;
+        }
+
+        return;
+    }
+}
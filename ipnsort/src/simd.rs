@@ -0,0 +1,257 @@
+//! Partitioning primitive types with portable SIMD, used by [`crate::sort`] with the `simd` feature.
+//!
+//! Each vector of elements is compared with the pivot in one go, and the resulting mask selects a
+//! byte shuffle from a lookup table, that moves the elements less than the pivot to the front of
+//! the vector and the others to the back. The shuffled vector is then stored in full at both ends
+//! of the partitioned area, and only the write positions decide which part of each store is kept.
+//! The shuffle is done with `core::simd`, which falls back to a portable implementation if the
+//! target has no suitable instruction, so this works on any x86-64 target.
+//!
+//! A comparator can't be inspected, so SIMD comparisons are only used if `is_less` is [`OrdLess`],
+//! which is what [`crate::sort`] passes in.
+
+use core::mem;
+use core::ptr;
+use core::simd::cmp::SimdPartialOrd;
+use core::simd::Simd;
+
+const VECTOR_BYTES: usize = 32;
+
+/// `is_less` for the natural order of `T`.
+///
+/// Unlike a closure it's a nameable type, which allows the partition specialization to recognize
+/// it, see [`IsOrdLess`].
+pub(crate) struct OrdLess;
+
+impl<'a, 'b, T: Ord> FnOnce<(&'a T, &'b T)> for OrdLess {
+    type Output = bool;
+
+    #[inline(always)]
+    extern "rust-call" fn call_once(mut self, args: (&'a T, &'b T)) -> bool {
+        self.call_mut(args)
+    }
+}
+
+impl<'a, 'b, T: Ord> FnMut<(&'a T, &'b T)> for OrdLess {
+    #[inline(always)]
+    extern "rust-call" fn call_mut(&mut self, (a, b): (&'a T, &'b T)) -> bool {
+        a.lt(b)
+    }
+}
+
+/// `IS_ORD_LESS` is `true` if the comparator type is [`OrdLess`]. Unused with `opt_size`, which
+/// doesn't specialize the partition.
+#[cfg(not(feature = "opt_size"))]
+pub(crate) trait IsOrdLess {
+    const IS_ORD_LESS: bool;
+}
+
+#[cfg(not(feature = "opt_size"))]
+impl<F> IsOrdLess for F {
+    default const IS_ORD_LESS: bool = false;
+}
+
+#[cfg(not(feature = "opt_size"))]
+impl IsOrdLess for OrdLess {
+    const IS_ORD_LESS: bool = true;
+}
+
+/// A primitive type that can be partitioned with [`partition`]. Implemented for [`i32`], [`u32`],
+/// [`u64`] and [`f32`].
+pub trait SimdPartition: Copy + private::Sealed {}
+
+impl SimdPartition for i32 {}
+impl SimdPartition for u32 {}
+impl SimdPartition for u64 {}
+impl SimdPartition for f32 {}
+
+/// Re-arranges `v` such that all elements `elem` for which `elem < *pivot` holds come first,
+/// followed by the other elements, and returns the number of the former.
+///
+/// For `f32` the comparison is done with `<`, which places NaNs on the right side.
+pub fn partition<T: SimdPartition>(v: &mut [T], pivot: &T) -> usize {
+    partition_compress(v, pivot)
+}
+
+mod private {
+    pub trait Sealed: Copy + PartialOrd + 'static {
+        /// Returns the byte shuffles for vectors of `Self`, see [`super::compress_table`].
+        fn compress_table() -> &'static [[u8; super::VECTOR_BYTES]];
+
+        /// Returns the comparison mask of `elem < pivot` for the vector of elements at `src`.
+        ///
+        /// # Safety
+        ///
+        /// `src` has to be valid for reading a full vector of elements.
+        unsafe fn lt_mask(src: *const Self, pivot: Self) -> usize;
+    }
+}
+
+macro_rules! simd_partition_impl {
+    ($t:ty, $compress_table:ident) => {
+        impl private::Sealed for $t {
+            #[inline(always)]
+            fn compress_table() -> &'static [[u8; VECTOR_BYTES]] {
+                &$compress_table
+            }
+
+            #[inline(always)]
+            unsafe fn lt_mask(src: *const Self, pivot: Self) -> usize {
+                const LANES: usize = VECTOR_BYTES / mem::size_of::<$t>();
+                let vec = ptr::read_unaligned(src as *const Simd<$t, LANES>);
+                vec.simd_lt(Simd::splat(pivot)).to_bitmask() as usize
+            }
+        }
+    };
+}
+
+static COMPRESS_TABLE_8: [[u8; VECTOR_BYTES]; 1 << 8] = compress_table::<8, { 1 << 8 }>();
+static COMPRESS_TABLE_4: [[u8; VECTOR_BYTES]; 1 << 4] = compress_table::<4, { 1 << 4 }>();
+
+simd_partition_impl!(i32, COMPRESS_TABLE_8);
+simd_partition_impl!(u32, COMPRESS_TABLE_8);
+simd_partition_impl!(u64, COMPRESS_TABLE_4);
+simd_partition_impl!(f32, COMPRESS_TABLE_8);
+
+/// Returns the byte shuffles for vectors of `LANES` lanes, indexed by the comparison mask. Each
+/// shuffle moves the lanes with their mask bit set to the front, and the others to the back, both
+/// in their original order.
+const fn compress_table<const LANES: usize, const MASKS: usize>() -> [[u8; VECTOR_BYTES]; MASKS] {
+    let lane_bytes = VECTOR_BYTES / LANES;
+    let mut table = [[0; VECTOR_BYTES]; MASKS];
+
+    let mut mask = 0;
+    while mask < MASKS {
+        let mut out_lane = 0;
+        let mut want_set = true;
+        loop {
+            let mut lane = 0;
+            while lane < LANES {
+                if ((mask >> lane) & 1 == 1) == want_set {
+                    let mut byte = 0;
+                    while byte < lane_bytes {
+                        table[mask][out_lane * lane_bytes + byte] =
+                            (lane * lane_bytes + byte) as u8;
+                        byte += 1;
+                    }
+                    out_lane += 1;
+                }
+                lane += 1;
+            }
+
+            if !want_set {
+                break;
+            }
+            want_set = false;
+        }
+
+        mask += 1;
+    }
+
+    table
+}
+
+/// See [`partition`].
+///
+/// The first and last vector are loaded up front, which leaves a vector of free space at both
+/// ends. Each further vector is loaded from the side with less free space, which gains it a vector
+/// of free space. Storing the compressed vector at both ends uses up one vector of free space in
+/// total, so both sides always have room for a full vector store, without overwriting elements not
+/// yet loaded.
+fn partition_compress<T: private::Sealed>(v: &mut [T], pivot: &T) -> usize {
+    type Bytes = Simd<u8, VECTOR_BYTES>;
+
+    let lanes = VECTOR_BYTES / mem::size_of::<T>();
+
+    let len = v.len();
+    if len < 2 * lanes {
+        return partition_scalar(v, pivot);
+    }
+
+    let pivot = *pivot;
+
+    // SAFETY: All loads happen from `[l_read, r_read)` before the elements there are overwritten,
+    // and all stores are within `[l_write, l_read)` and `[r_read, r_write)`, which are inside `v`
+    // and at least a vector long each, see the function comment. `T` is `Copy`, so there is
+    // nothing to take care of if elements get duplicated or overwritten in the process.
+    unsafe {
+        let v_base = v.as_mut_ptr();
+
+        let load = |pos: usize| ptr::read_unaligned(v_base.add(pos) as *const Bytes);
+
+        let left = load(0);
+        let right = load(len - lanes);
+
+        let mut l_read = lanes;
+        let mut r_read = len - lanes;
+        let mut l_write = 0;
+        let mut r_write = len;
+
+        while r_read - l_read >= lanes {
+            let pos = if l_read - l_write <= r_write - r_read {
+                l_read += lanes;
+                l_read - lanes
+            } else {
+                r_read -= lanes;
+                r_read
+            };
+
+            let mask = T::lt_mask(v_base.add(pos), pivot);
+            let lt_count = mask.count_ones() as usize;
+
+            let compress_table = T::compress_table();
+            debug_assert!(mask < compress_table.len());
+            let shuffle = Bytes::from_array(*compress_table.get_unchecked(mask));
+            let compressed = load(pos).swizzle_dyn(shuffle);
+
+            ptr::write_unaligned(v_base.add(l_write) as *mut Bytes, compressed);
+            ptr::write_unaligned(v_base.add(r_write - lanes) as *mut Bytes, compressed);
+
+            l_write += lt_count;
+            r_write -= lanes - lt_count;
+        }
+
+        // The remaining gap `[l_write, r_write)` fits exactly the elements that are not placed
+        // yet, the two vectors loaded up front and the fewer than `lanes` never loaded ones.
+        let rest_len = r_read - l_read;
+        let mut rest = [0u8; VECTOR_BYTES];
+        // `rest` is only byte aligned, so copy bytes and read the elements back unaligned.
+        ptr::copy_nonoverlapping(
+            v_base.add(l_read) as *const u8,
+            rest.as_mut_ptr(),
+            rest_len * mem::size_of::<T>(),
+        );
+
+        for (bytes, count) in [
+            (left.to_array(), lanes),
+            (right.to_array(), lanes),
+            (rest, rest_len),
+        ] {
+            for i in 0..count {
+                let elem = ptr::read_unaligned((bytes.as_ptr() as *const T).add(i));
+                if elem < pivot {
+                    ptr::write(v_base.add(l_write), elem);
+                    l_write += 1;
+                } else {
+                    r_write -= 1;
+                    ptr::write(v_base.add(r_write), elem);
+                }
+            }
+        }
+
+        debug_assert_eq!(l_write, r_write);
+        l_write
+    }
+}
+
+/// Lomuto partition for inputs too short for two vectors.
+fn partition_scalar<T: Copy + PartialOrd>(v: &mut [T], pivot: &T) -> usize {
+    let mut lt_count = 0;
+    for i in 0..v.len() {
+        let is_lt = v[i] < *pivot;
+        v.swap(lt_count, i);
+        lt_count += is_lt as usize;
+    }
+
+    lt_count
+}
@@ -1,8 +1,17 @@
-use core::intrinsics;
-use core::mem::{self, ManuallyDrop, MaybeUninit};
+#[cfg(any(feature = "nightly", not(feature = "opt_size")))]
+use core::mem;
+use core::mem::ManuallyDrop;
+#[cfg(feature = "nightly")]
+use core::mem::MaybeUninit;
 use core::ptr;
 
-use crate::{has_efficient_in_place_swap, Freeze, GapGuard, IsTrue};
+#[cfg(not(feature = "opt_size"))]
+use crate::has_efficient_in_place_swap;
+#[cfg(feature = "nightly")]
+use crate::Freeze;
+#[cfg(all(feature = "nightly", not(feature = "opt_size")))]
+use crate::IsTrue;
+use crate::{intrinsics, GapGuard};
 
 // Use a trait to focus code-gen on only the parts actually relevant for the type. Avoid generating
 // LLVM-IR for the sorting-network and median-networks for types that don't qualify.
@@ -15,6 +24,7 @@ pub(crate) trait SmallSortImpl: Sized {
         F: FnMut(&Self, &Self) -> bool;
 }
 
+#[cfg(all(feature = "nightly", not(feature = "opt_size")))]
 impl<T> SmallSortImpl for T {
     default const SMALL_SORT_THRESHOLD: usize = 20;
 
@@ -28,6 +38,7 @@ impl<T> SmallSortImpl for T {
     }
 }
 
+#[cfg(all(feature = "nightly", not(feature = "opt_size")))]
 impl<T: Freeze> SmallSortImpl for T {
     default const SMALL_SORT_THRESHOLD: usize = 20;
 
@@ -39,6 +50,7 @@ impl<T: Freeze> SmallSortImpl for T {
     }
 }
 
+#[cfg(all(feature = "nightly", not(feature = "opt_size")))]
 impl<T> SmallSortImpl for T
 where
     T: Freeze + Copy,
@@ -58,6 +70,45 @@ where
     }
 }
 
+/// Stable Rust can't query `Freeze` or `Copy` for a generic `T`. Types that don't need to be
+/// dropped and are cheap to swap are the closest approximation of `Freeze + Copy` types, and for
+/// them the in-place sorting-networks are worth it. Which types take which path is decided by
+/// constants, so the other paths are removed from the generated code.
+#[cfg(all(not(feature = "nightly"), not(feature = "opt_size")))]
+impl<T> SmallSortImpl for T {
+    const SMALL_SORT_THRESHOLD: usize = 20;
+
+    #[inline(always)]
+    fn small_sort<F>(v: &mut [T], is_less: &mut F)
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        if has_efficient_in_place_swap::<T>() && !mem::needs_drop::<T>() {
+            small_sort_network_in_place(v, is_less);
+        } else if v.len() >= 2 {
+            insertion_sort_shift_left(v, 1, is_less);
+        }
+    }
+}
+
+/// With `opt_size` insertion sort is the only small-sort, which generates by far the least code. The
+/// threshold is low enough for its quadratic worst-case to not matter.
+#[cfg(feature = "opt_size")]
+impl<T> SmallSortImpl for T {
+    const SMALL_SORT_THRESHOLD: usize = 20;
+
+    #[inline(always)]
+    fn small_sort<F>(v: &mut [T], is_less: &mut F)
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        if v.len() >= 2 {
+            insertion_sort_shift_left(v, 1, is_less);
+        }
+    }
+}
+
+#[cfg(feature = "nightly")]
 #[inline(always)]
 unsafe fn merge_up<T, F>(
     mut left_src: *const T,
@@ -94,6 +145,7 @@ where
     (left_src, right_src, dst)
 }
 
+#[cfg(feature = "nightly")]
 #[inline(always)]
 unsafe fn merge_down<T, F>(
     mut left_src: *const T,
@@ -130,6 +182,7 @@ where
     (left_src, right_src, dst)
 }
 
+#[cfg(feature = "nightly")]
 /// Merge v assuming the len is even and v[..len / 2] and v[len / 2..] are sorted.
 ///
 /// Original idea for bi-directional merging by Igor van den Hoven (quadsort), adapted to only use
@@ -254,6 +307,7 @@ where
 
 // Never inline this function to avoid code bloat. It still optimizes nicely and has practically no
 // performance impact.
+#[cfg(not(feature = "opt_size"))]
 #[inline(never)]
 fn sort9_optimal<T, F>(v: &mut [T], is_less: &mut F)
 where
@@ -301,6 +355,7 @@ where
 
 // Never inline this function to avoid code bloat. It still optimizes nicely and has practically no
 // performance impact.
+#[cfg(not(feature = "opt_size"))]
 #[inline(never)]
 fn sort13_optimal<T, F>(v: &mut [T], is_less: &mut F)
 where
@@ -366,6 +421,7 @@ where
     }
 }
 
+#[cfg(all(feature = "nightly", not(feature = "opt_size")))]
 fn sort18_plus<T, F>(v: &mut [T], is_less: &mut F) -> usize
 where
     T: Freeze,
@@ -411,6 +467,7 @@ where
     even_len
 }
 
+#[cfg(all(feature = "nightly", not(feature = "opt_size")))]
 fn small_sort_network<T, F>(v: &mut [T], is_less: &mut F)
 where
     T: Freeze,
@@ -440,6 +497,30 @@ where
     }
 }
 
+/// Only uses the sorting-networks that compare elements in their place in `v`, which unlike
+/// [`sort18_plus`] is observation safe for types with interior mutability.
+#[cfg(all(not(feature = "nightly"), not(feature = "opt_size")))]
+fn small_sort_network_in_place<T, F>(v: &mut [T], is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let len = v.len();
+
+    if len >= 2 {
+        let mut end = 1;
+        if len >= 13 {
+            sort13_optimal(&mut v[0..13], is_less);
+            end = 13;
+        } else if len >= 9 {
+            sort9_optimal(&mut v[0..9], is_less);
+            end = 9;
+        }
+
+        insertion_sort_shift_left(v, end, is_less);
+    }
+}
+
+#[cfg(all(feature = "nightly", not(feature = "opt_size")))]
 fn small_sort_general<T, F>(v: &mut [T], is_less: &mut F)
 where
     T: Freeze,
@@ -447,13 +528,36 @@ where
 {
     // This implementation is tuned to be efficient for various types that are larger than u64.
 
+    if v.len() >= 2 {
+        const SCRATCH_LEN: usize = alloc::string::String::SMALL_SORT_THRESHOLD + 16;
+        let mut scratch = MaybeUninit::<[MaybeUninit<T>; SCRATCH_LEN]>::uninit();
+
+        if SCRATCH_LEN < (T::SMALL_SORT_THRESHOLD + 16) {
+            intrinsics::abort();
+        }
+
+        // SAFETY: An uninitialized `[MaybeUninit<T>; N]` is valid.
+        let scratch = unsafe { scratch.assume_init_mut() };
+
+        small_sort_general_with_scratch(v, scratch, is_less);
+    }
+}
+
+#[cfg(feature = "nightly")]
+/// Stably sorts `v` using `scratch` as temporary storage, which must hold at least `v.len() + 16`
+/// elements. Used as small-sort by [`small_sort_general`] and to create runs in the stable sort.
+pub(crate) fn small_sort_general_with_scratch<T, F>(
+    v: &mut [T],
+    scratch: &mut [MaybeUninit<T>],
+    is_less: &mut F,
+) where
+    T: Freeze,
+    F: FnMut(&T, &T) -> bool,
+{
     let len = v.len();
 
     if len >= 2 {
-        const SCRATCH_LEN: usize = String::SMALL_SORT_THRESHOLD + 16;
-        let mut scratch = MaybeUninit::<[T; SCRATCH_LEN]>::uninit();
-
-        if SCRATCH_LEN < (T::SMALL_SORT_THRESHOLD + 16) {
+        if scratch.len() < (len + 16) {
             intrinsics::abort();
         }
 
@@ -468,16 +572,11 @@ where
 
                 let presorted_len = if len >= 16 {
                     // SAFETY: scratch_base is valid and has enough space.
-                    sort8_stable(
-                        v_base,
-                        scratch_base.add(T::SMALL_SORT_THRESHOLD),
-                        scratch_base,
-                        is_less,
-                    );
+                    sort8_stable(v_base, scratch_base.add(len), scratch_base, is_less);
 
                     sort8_stable(
                         v_base.add(len_div_2),
-                        scratch_base.add(T::SMALL_SORT_THRESHOLD + 8),
+                        scratch_base.add(len + 8),
                         scratch_base.add(len_div_2),
                         is_less,
                     );
@@ -547,6 +646,7 @@ where
     }
 }
 
+#[cfg(feature = "nightly")]
 /// SAFETY: The caller MUST guarantee that `v_base` is valid for 4 reads and `dest_ptr` is valid
 /// for 4 writes. The result will be stored in `dst[0..4]`.
 pub unsafe fn sort4_stable<T, F>(v_base: *const T, dst: *mut T, is_less: &mut F)
@@ -605,6 +705,7 @@ where
     }
 }
 
+#[cfg(feature = "nightly")]
 /// SAFETY: The caller MUST guarantee that `v_base` is valid for 8 reads and writes, `scratch_base`
 /// and `dst` MUST be valid for 8 writes. The result will be stored in `dst[0..8]`.
 #[inline(never)]
@@ -700,6 +801,7 @@ where
     }
 }
 
+#[cfg(feature = "nightly")]
 #[inline(never)]
 fn panic_on_ord_violation() -> ! {
     panic!("Ord violation");
@@ -0,0 +1,747 @@
+//! Stable sort counterpart of ipnsort.
+
+use alloc::vec::Vec;
+use core::cmp::{self, Ordering};
+use core::mem::{self, MaybeUninit};
+use core::ptr;
+
+use crate::smallsort::insertion_sort_shift_left;
+#[cfg(feature = "nightly")]
+use crate::{smallsort::small_sort_general_with_scratch, Freeze};
+
+/// Sorts the slice, preserving the order of equal elements.
+///
+/// This sort is stable (i.e., does not reorder equal elements) and *O*(*n* \* log(*n*))
+/// worst-case.
+///
+/// When applicable, unstable sorting is preferred because it is generally faster than stable
+/// sorting and it doesn't allocate auxiliary memory. See [`crate::sort`].
+///
+/// # Current implementation
+///
+/// The current algorithm is an adaptive, iterative merge sort inspired by
+/// [timsort](https://en.wikipedia.org/wiki/Timsort). Natural runs are detected and short runs are
+/// extended with the same stable sorting-networks and bi-directional merge used by the unstable
+/// small-sort.
+///
+/// It allocates temporary storage half the size of `self`, but for short slices a
+/// non-allocating insertion sort is used instead.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [-5, 4, 1, -3, 2];
+///
+/// ipnsort::stable::sort(&mut v);
+/// assert!(v == [-5, -3, 1, 2, 4]);
+/// ```
+#[inline(always)]
+pub fn sort<T>(arr: &mut [T])
+where
+    T: Ord,
+{
+    stable_sort(arr, |a, b| a.lt(b));
+}
+
+/// Sorts the slice with a comparator function, preserving the order of equal elements.
+///
+/// This sort is stable (i.e., does not reorder equal elements) and *O*(*n* \* log(*n*))
+/// worst-case.
+///
+/// The comparator function must define a total ordering for the elements in the slice. If
+/// the ordering is not total, the order of the elements is unspecified. An order is a
+/// total order if it is (for all `a`, `b` and `c`):
+///
+/// * total and antisymmetric: exactly one of `a < b`, `a == b` or `a > b` is true, and
+/// * transitive, `a < b` and `b < c` implies `a < c`. The same must hold for both `==` and `>`.
+///
+/// # Current implementation
+///
+/// See [`sort`].
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [5, 4, 1, 3, 2];
+/// ipnsort::stable::sort_by(&mut v, |a, b| a.cmp(b));
+/// assert!(v == [1, 2, 3, 4, 5]);
+///
+/// // reverse sorting
+/// ipnsort::stable::sort_by(&mut v, |a, b| b.cmp(a));
+/// assert!(v == [5, 4, 3, 2, 1]);
+/// ```
+#[inline(always)]
+pub fn sort_by<T, F>(arr: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    stable_sort(arr, |a, b| compare(a, b) == Ordering::Less);
+}
+
+/// Sorts the slice with a key extraction function, preserving the order of equal elements.
+///
+/// This sort is stable (i.e., does not reorder equal elements) and *O*(*m* \* *n* \* log(*n*))
+/// worst-case, where the key function is *O*(*m*).
+///
+/// For expensive key functions (e.g. functions that are not simple property accesses or
+/// basic operations), [`crate::sort_by_cached_key`] is likely to be significantly faster, as it
+/// does not recompute element keys and is stable as well.
+///
+/// # Current implementation
+///
+/// See [`sort`].
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [-5i32, 4, 1, -3, 2];
+///
+/// ipnsort::stable::sort_by_key(&mut v, |k| k.abs());
+/// assert!(v == [1, 2, -3, 4, -5]);
+/// ```
+#[inline(always)]
+pub fn sort_by_key<T, K, F>(arr: &mut [T], mut f: F)
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    stable_sort(arr, |a, b| f(a).lt(&f(b)));
+}
+
+/// Sorts the slice with a fallible comparator function, preserving the order of equal elements.
+///
+/// Sorting stops at the first error returned by `compare`, `compare` is not called again and the
+/// error is returned. In that case the slice is left in its original order. Otherwise this sorts
+/// like [`sort_by`], see there for the requirements on the comparator function.
+///
+/// This sort is stable (i.e., does not reorder equal elements) and *O*(*n* \* log(*n*))
+/// worst-case. It allocates `arr.len()` indices, but no scratch memory.
+///
+/// # Current implementation
+///
+/// See [`crate::try_sort_by`]. Equal elements are ordered by their index, which makes the index
+/// sort stable even though it is unstable. That also keeps the merges of this module out of it,
+/// which can't handle the inconsistent comparison results after an error.
+///
+/// # Examples
+///
+/// ```
+/// use core::num::ParseIntError;
+///
+/// let by_abs = |a: &&str, b: &&str| -> Result<_, ParseIntError> {
+///     Ok(a.parse::<i32>()?.abs().cmp(&b.parse::<i32>()?.abs()))
+/// };
+///
+/// let mut v = ["2", "-1", "-2", "1"];
+/// assert!(ipnsort::stable::try_sort_by(&mut v, by_abs).is_ok());
+/// assert!(v == ["-1", "1", "2", "-2"]);
+///
+/// let mut v = ["2", "-1", "two", "1"];
+/// assert!(ipnsort::stable::try_sort_by(&mut v, by_abs).is_err());
+/// assert!(v == ["2", "-1", "two", "1"]);
+/// ```
+#[inline]
+pub fn try_sort_by<T, E, F>(arr: &mut [T], compare: F) -> Result<(), E>
+where
+    F: FnMut(&T, &T) -> Result<Ordering, E>,
+{
+    crate::argsort::try_sort_by::<T, E, F, true>(arr, compare)
+}
+
+/// Returns the permutation that sorts the slice, preserving the order of equal elements.
+///
+/// Equal elements are listed in the order of their indices. See [`crate::argsort`] for the
+/// result.
+///
+/// This sort is stable (i.e., does not reorder equal elements) and *O*(*n* \* log(*n*))
+/// worst-case. Besides the returned `Vec` it allocates the scratch memory of [`sort`].
+///
+/// # Examples
+///
+/// ```
+/// let v = [2, 1, 2, 1];
+///
+/// let perm = ipnsort::stable::argsort(&v);
+/// assert!(perm == [1, 3, 0, 2]);
+/// ```
+#[inline]
+pub fn argsort<T>(arr: &[T]) -> Vec<usize>
+where
+    T: Ord,
+{
+    let mut perm = crate::argsort::identity(arr.len());
+    stable_sort(
+        &mut perm,
+        crate::argsort::index_is_less(arr, |a: &T, b: &T| a.lt(b)),
+    );
+    perm
+}
+
+/// Returns the permutation that sorts the slice with a comparator function, preserving the
+/// order of equal elements.
+///
+/// See [`sort_by`] for the requirements on the comparator function and [`argsort`] for the
+/// result.
+///
+/// # Examples
+///
+/// ```
+/// let v = [-2i32, 1, 2, -1];
+///
+/// let perm = ipnsort::stable::argsort_by(&v, |a, b| a.abs().cmp(&b.abs()));
+/// assert!(perm == [1, 3, 0, 2]);
+/// ```
+#[inline]
+pub fn argsort_by<T, F>(arr: &[T], mut compare: F) -> Vec<usize>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut perm = crate::argsort::identity(arr.len());
+    stable_sort(
+        &mut perm,
+        crate::argsort::index_is_less(arr, |a: &T, b: &T| compare(a, b) == Ordering::Less),
+    );
+    perm
+}
+
+/// Sorts `keys` and applies the same reordering to `payloads`, preserving the order of equal
+/// keys.
+///
+/// This sort is stable (i.e., does not reorder equal keys), in-place (i.e., does not allocate),
+/// and *O*(*n* \* log<sup>2</sup>(*n*)) worst-case. See [`crate::sort_zip`] for the payloads.
+///
+/// # Current implementation
+///
+/// A bottom-up merge sort of insertion sorted runs. Moving a key into a scratch buffer would need
+/// a buffer for each payload too, so the runs are merged in-place like the fallback of
+/// [`sort_with_buffer`] without any buffer, by rotating the keys and payloads together.
+///
+/// # Examples
+///
+/// ```
+/// let mut keys = [2, 1, 2, 1];
+/// let mut values = ['a', 'b', 'c', 'd'];
+///
+/// ipnsort::stable::sort_zip(&mut keys, &mut values[..]);
+/// assert!(keys == [1, 1, 2, 2]);
+/// assert!(values == ['b', 'd', 'a', 'c']);
+/// ```
+#[inline]
+pub fn sort_zip<K, P>(keys: &mut [K], payloads: P)
+where
+    K: Ord,
+    P: crate::Payloads,
+{
+    crate::zip::stable_sort_zip(keys, payloads, |a: &K, b: &K| a.lt(b));
+}
+
+/// Sorts `keys` with a comparator function and applies the same reordering to `payloads`,
+/// preserving the order of equal keys.
+///
+/// See [`sort_by`] for the requirements on the comparator function and [`crate::sort_zip`] for
+/// the payloads.
+///
+/// # Examples
+///
+/// ```
+/// let mut keys = [-2i32, 1, 2, -1];
+/// let mut values = ['a', 'b', 'c', 'd'];
+///
+/// ipnsort::stable::sort_zip_by(&mut keys, &mut values[..], |a, b| a.abs().cmp(&b.abs()));
+/// assert!(keys == [1, -1, -2, 2]);
+/// assert!(values == ['b', 'd', 'a', 'c']);
+/// ```
+#[inline]
+pub fn sort_zip_by<K, P, F>(keys: &mut [K], payloads: P, mut compare: F)
+where
+    P: crate::Payloads,
+    F: FnMut(&K, &K) -> Ordering,
+{
+    crate::zip::stable_sort_zip(keys, payloads, |a, b| compare(a, b) == Ordering::Less);
+}
+
+/// Sorts the slice using `buf` as scratch memory, preserving the order of equal elements.
+///
+/// This sort is stable (i.e., does not reorder equal elements) and does not allocate. It is
+/// *O*(*n* \* log(*n*)) worst-case if `buf` can hold at least [`buffer_len`] elements. With a
+/// shorter, or even empty, `buf` it falls back to merging in-place, which is
+/// *O*(*n* \* log<sup>2</sup>(*n*)) worst-case.
+///
+/// `buf` can be re-used across calls, its contents are never read and left uninitialized.
+///
+/// # Current implementation
+///
+/// See [`sort`].
+///
+/// # Examples
+///
+/// ```
+/// use core::mem::MaybeUninit;
+///
+/// let mut v = [-5, 4, 1, -3, 2];
+/// let mut buf = [MaybeUninit::uninit(); 64];
+///
+/// ipnsort::stable::sort_with_buffer(&mut v, &mut buf);
+/// assert!(v == [-5, -3, 1, 2, 4]);
+/// ```
+#[inline(always)]
+pub fn sort_with_buffer<T>(arr: &mut [T], buf: &mut [MaybeUninit<T>])
+where
+    T: Ord,
+{
+    stable_sort_with_buffer(arr, buf, |a, b| a.lt(b));
+}
+
+/// Sorts the slice with a comparator function using `buf` as scratch memory, preserving the order
+/// of equal elements.
+///
+/// See [`sort_by`] for the requirements on the comparator function and [`sort_with_buffer`] for
+/// the requirements on `buf`.
+///
+/// # Examples
+///
+/// ```
+/// use core::mem::MaybeUninit;
+///
+/// let mut v = [5, 4, 1, 3, 2];
+/// let mut buf = Vec::with_capacity(ipnsort::stable::buffer_len::<i32>(v.len()));
+///
+/// ipnsort::stable::sort_by_with_buffer(&mut v, buf.spare_capacity_mut(), |a, b| b.cmp(a));
+/// assert!(v == [5, 4, 3, 2, 1]);
+/// ```
+#[inline(always)]
+pub fn sort_by_with_buffer<T, F>(arr: &mut [T], buf: &mut [MaybeUninit<T>], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    stable_sort_with_buffer(arr, buf, |a, b| compare(a, b) == Ordering::Less);
+}
+
+/// Returns the number of elements a scratch buffer must be able to hold, for
+/// [`sort_with_buffer`] to sort a slice of `len` elements at full speed.
+pub const fn buffer_len<T>(len: usize) -> usize {
+    if mem::size_of::<T>() == 0 || len <= MAX_LEN_ALWAYS_INSERTION_SORT {
+        0
+    } else if len / 2 < MIN_SCRATCH_LEN {
+        MIN_SCRATCH_LEN
+    } else {
+        len / 2
+    }
+}
+
+// --- IMPL ---
+
+// Slices of up to this length get sorted using insertion sort.
+const MAX_LEN_ALWAYS_INSERTION_SORT: usize = 20;
+
+// Runs shorter than this get extended to this length with the stable small-sort.
+const MIN_RUN_LEN: usize = 20;
+
+// The small-sort needs additional scratch space on top of the slice it sorts.
+const MIN_SCRATCH_LEN: usize = MIN_RUN_LEN + 16;
+
+// Upper bound for the number of pending runs. The invariants enforced by `merge_sort` make the run
+// lengths grow at least as fast as the Fibonacci sequence, from the top to the bottom of the
+// stack, and the 94th Fibonacci number already exceeds `u64::MAX`.
+const MAX_PENDING_RUNS: usize = 96;
+
+#[inline(always)]
+fn stable_sort<T, F>(v: &mut [T], is_less: F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    // Allocate a buffer to use as scratch memory. We keep the length 0 so we can keep in it
+    // shallow copies of the contents of `v` without risking the dtors running on copies if
+    // `is_less` panics. When merging two sorted runs, this buffer holds a copy of the shorter run,
+    // which will always have length at most `len / 2`. Short slices don't need a buffer, and
+    // `Vec::with_capacity(0)` doesn't allocate.
+    let mut buf = Vec::<T>::with_capacity(buffer_len::<T>(v.len()));
+
+    stable_sort_with_buffer(v, buf.spare_capacity_mut(), is_less);
+}
+
+#[inline(always)]
+fn stable_sort_with_buffer<T, F>(v: &mut [T], buf: &mut [MaybeUninit<T>], is_less: F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    // Sorting has no meaningful behavior on zero-sized types.
+    if mem::size_of::<T>() == 0 {
+        return;
+    }
+
+    let len = v.len();
+
+    if len < 2 {
+        return;
+    }
+
+    #[cfg(not(feature = "check_ord"))]
+    insertion_sort_or_merge_sort(v, buf, is_less);
+
+    #[cfg(feature = "check_ord")]
+    {
+        // The buffer is for `T`, the indices that get sorted instead need their own.
+        let _ = buf;
+        crate::check_ord::sort(v, is_less, |perm, is_less| {
+            let mut buf = Vec::<usize>::with_capacity(buffer_len::<usize>(perm.len()));
+            insertion_sort_or_merge_sort(perm, buf.spare_capacity_mut(), is_less);
+        });
+    }
+}
+
+#[inline(always)]
+fn insertion_sort_or_merge_sort<T, F>(v: &mut [T], buf: &mut [MaybeUninit<T>], mut is_less: F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    if v.len() <= MAX_LEN_ALWAYS_INSERTION_SORT {
+        insertion_sort_shift_left(v, 1, &mut is_less);
+        return;
+    }
+
+    merge_sort(v, buf, &mut is_less);
+}
+
+/// This merge sort borrows some (but not all) ideas from TimSort, which is described in detail
+/// [here](https://github.com/python/cpython/blob/main/Objects/listsort.txt).
+///
+/// The algorithm identifies strictly descending and non-descending subsequences, which are called
+/// natural runs. There is a stack of pending runs yet to be merged. Each newly found run is pushed
+/// onto the stack, and then some pairs of adjacent runs are merged until these two invariants are
+/// satisfied:
+///
+/// 1. for every `i` in `1..runs.len()`: `runs[i - 1].len > runs[i].len`
+/// 2. for every `i` in `2..runs.len()`: `runs[i - 2].len > runs[i - 1].len + runs[i].len`
+///
+/// The invariants ensure that the total running time is *O*(*n* \* log(*n*)) worst-case.
+///
+/// `scratch` may have any length, but if it can't hold `max(v.len() / 2, MIN_SCRATCH_LEN)`
+/// elements some of the merges have to be done in-place, which is slower. The pending runs are
+/// kept on the stack, so this function never allocates.
+fn merge_sort<T, F>(v: &mut [T], scratch: &mut [MaybeUninit<T>], is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let len = v.len();
+
+    let mut runs = [Run { start: 0, len: 0 }; MAX_PENDING_RUNS];
+    let mut runs_len = 0;
+    let mut start = 0;
+    while start < len {
+        // Find the next natural run, and reverse it if it's strictly descending.
+        let (streak_len, was_reversed) = crate::find_streak(&v[start..], is_less);
+        let mut end = start + streak_len;
+
+        if was_reversed {
+            v[start..end].reverse();
+        }
+
+        // Extend the run if it's too short. The small-sort is faster than merge sort on short
+        // sequences, so this significantly improves performance.
+        if streak_len < MIN_RUN_LEN {
+            end = cmp::min(start + MIN_RUN_LEN, len);
+            if end - start > streak_len {
+                if scratch.len() >= MIN_SCRATCH_LEN {
+                    T::stable_small_sort(&mut v[start..end], scratch, is_less);
+                } else {
+                    insertion_sort_shift_left(&mut v[start..end], streak_len, is_less);
+                }
+            }
+        }
+
+        // Push this run onto the stack.
+        runs[runs_len] = Run {
+            start,
+            len: end - start,
+        };
+        runs_len += 1;
+        start = end;
+
+        // Merge some pairs of adjacent runs to satisfy the invariants.
+        while let Some(r) = collapse(&runs[..runs_len], len) {
+            let left = runs[r];
+            let right = runs[r + 1];
+            merge_with_scratch(
+                &mut v[left.start..right.start + right.len],
+                left.len,
+                scratch,
+                is_less,
+            );
+            runs[r] = Run {
+                start: left.start,
+                len: left.len + right.len,
+            };
+            runs.copy_within((r + 2)..runs_len, r + 1);
+            runs_len -= 1;
+        }
+    }
+
+    // Finally, exactly one run must remain in the stack.
+    debug_assert!(runs_len == 1 && runs[0].start == 0 && runs[0].len == len);
+
+    // Examines the stack of runs and identifies the next pair of runs to merge. More specifically,
+    // if `Some(r)` is returned, that means `runs[r]` and `runs[r + 1]` must be merged next. If the
+    // algorithm should continue building a new run instead, `None` is returned.
+    //
+    // TimSort is infamous for its buggy implementations, as described here:
+    // http://envisage-project.eu/timsort-specification-and-verification/
+    //
+    // The gist of the story is: we must enforce the invariants on the top four runs on the stack.
+    // Enforcing them on just top three is not sufficient to ensure that the invariants will still
+    // hold for *all* runs in the stack.
+    //
+    // This function correctly checks invariants for the top four runs. Additionally, if the top
+    // run ends at `len`, it will always demand a merge operation until the stack is fully
+    // collapsed, in order to complete the sort.
+    #[inline]
+    fn collapse(runs: &[Run], len: usize) -> Option<usize> {
+        let n = runs.len();
+        if n >= 2
+            && (runs[n - 1].start + runs[n - 1].len == len
+                || runs[n - 2].len <= runs[n - 1].len
+                || (n >= 3 && runs[n - 3].len <= runs[n - 2].len + runs[n - 1].len)
+                || (n >= 4 && runs[n - 4].len <= runs[n - 3].len + runs[n - 2].len))
+        {
+            if n >= 3 && runs[n - 3].len < runs[n - 1].len {
+                Some(n - 3)
+            } else {
+                Some(n - 2)
+            }
+        } else {
+            None
+        }
+    }
+
+    #[derive(Clone, Copy)]
+    struct Run {
+        start: usize,
+        len: usize,
+    }
+}
+
+trait StableSmallSortImpl: Sized {
+    /// Stably sorts `v` using `scratch`, which must hold at least `MIN_SCRATCH_LEN` elements.
+    fn stable_small_sort<F>(v: &mut [Self], scratch: &mut [MaybeUninit<Self>], is_less: &mut F)
+    where
+        F: FnMut(&Self, &Self) -> bool;
+}
+
+#[cfg(feature = "nightly")]
+impl<T> StableSmallSortImpl for T {
+    default fn stable_small_sort<F>(v: &mut [T], _scratch: &mut [MaybeUninit<T>], is_less: &mut F)
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        if v.len() >= 2 {
+            insertion_sort_shift_left(v, 1, is_less);
+        }
+    }
+}
+
+/// The sorting-networks and bi-directional merge compare copies of the elements, which is only
+/// observation safe for types without interior mutability.
+#[cfg(feature = "nightly")]
+impl<T: Freeze> StableSmallSortImpl for T {
+    fn stable_small_sort<F>(v: &mut [T], scratch: &mut [MaybeUninit<T>], is_less: &mut F)
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        small_sort_general_with_scratch(v, scratch, is_less);
+    }
+}
+
+/// Without `Freeze` there is no way to tell if a type has interior mutability, so only insertion
+/// sort, which compares the elements in place, is observation safe.
+#[cfg(not(feature = "nightly"))]
+impl<T> StableSmallSortImpl for T {
+    fn stable_small_sort<F>(v: &mut [T], _scratch: &mut [MaybeUninit<T>], is_less: &mut F)
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        if v.len() >= 2 {
+            insertion_sort_shift_left(v, 1, is_less);
+        }
+    }
+}
+
+/// Merges non-decreasing runs `v[..mid]` and `v[mid..]`, using `scratch` as temporary storage.
+///
+/// If `scratch` can't hold the shorter run, the longer run is split in half and the matching split
+/// point of the shorter run is found with a binary search. Rotating the two inner pieces leaves two
+/// independent smaller merges, which are repeated until the pieces fit into `scratch`.
+pub(crate) fn merge_with_scratch<T, F>(
+    v: &mut [T],
+    mid: usize,
+    scratch: &mut [MaybeUninit<T>],
+    is_less: &mut F,
+) where
+    F: FnMut(&T, &T) -> bool,
+{
+    let len = v.len();
+
+    if mid == 0 || mid == len {
+        return;
+    }
+
+    if cmp::min(mid, len - mid) <= scratch.len() {
+        // SAFETY: Both runs are non-empty, and `scratch` can hold the shorter of the two runs.
+        unsafe {
+            merge(v, mid, scratch.as_mut_ptr() as *mut T, is_less);
+        }
+        return;
+    }
+
+    if len == 2 {
+        // Only reachable with an empty `scratch`, splitting a run of length 1 makes no progress.
+        if is_less(&v[1], &v[0]) {
+            v.swap(0, 1);
+        }
+        return;
+    }
+
+    let (left_cut, right_cut) = if mid >= len - mid {
+        // All elements of `v[mid..right_cut]` are less than `v[left_cut..mid]`, moving them in
+        // front of it preserves stability.
+        let left_cut = mid / 2;
+        let (left, right) = v.split_at(mid);
+        let right_cut = mid + right.partition_point(|x| is_less(x, &left[left_cut]));
+        (left_cut, right_cut)
+    } else {
+        // All elements of `v[left_cut..mid]` are greater than `v[mid..right_cut]`, moving them
+        // behind it preserves stability.
+        let right_cut = mid + (len - mid) / 2;
+        let (left, right) = v.split_at(mid);
+        let left_cut = left.partition_point(|x| !is_less(&right[right_cut - mid], x));
+        (left_cut, right_cut)
+    };
+
+    v[left_cut..right_cut].rotate_left(mid - left_cut);
+
+    let new_mid = left_cut + (right_cut - mid);
+    let (left, right) = v.split_at_mut(new_mid);
+    merge_with_scratch(left, left_cut, scratch, is_less);
+    merge_with_scratch(right, mid - left_cut, scratch, is_less);
+}
+
+/// Merges non-decreasing runs `v[..mid]` and `v[mid..]` using `buf` as temporary storage, and
+/// stores the result into `v[..]`.
+///
+/// # Safety
+///
+/// The two slices must be non-empty and `mid` must be in bounds. Buffer `buf` must be long enough
+/// to hold a copy of the shorter slice. Also, `T` must not be a zero-sized type.
+unsafe fn merge<T, F>(v: &mut [T], mid: usize, buf: *mut T, is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let len = v.len();
+    let v = v.as_mut_ptr();
+
+    // SAFETY: mid and len must be in-bounds of v.
+    let (v_mid, v_end) = unsafe { (v.add(mid), v.add(len)) };
+
+    // The merge process first copies the shorter run into `buf`. Then it traces the newly copied
+    // run and the longer run forwards (or backwards), comparing their next unconsumed elements and
+    // copying the lesser (or greater) one into `v`.
+    //
+    // As soon as the shorter run is fully consumed, the process is done. If the longer run gets
+    // consumed first, then we must copy whatever is left of the shorter run into the remaining
+    // hole in `v`.
+    //
+    // Intermediate state of the process is always tracked by `hole`, which serves two purposes:
+    // 1. Protects integrity of `v` from panics in `is_less`.
+    // 2. Fills the remaining hole in `v` if the longer run gets consumed first.
+    //
+    // Panic safety:
+    //
+    // If `is_less` panics at any point during the process, `hole` will get dropped and fill the
+    // hole in `v` with the unconsumed range in `buf`, thus ensuring that `v` still holds every
+    // object it initially held exactly once.
+    let mut hole;
+
+    if mid <= len - mid {
+        // The left run is shorter.
+
+        // SAFETY: buf must have enough capacity for `v[..mid]`.
+        unsafe {
+            ptr::copy_nonoverlapping(v, buf, mid);
+            hole = MergeHole {
+                start: buf,
+                end: buf.add(mid),
+                dest: v,
+            };
+        }
+
+        // Initially, these pointers point to the beginnings of their arrays.
+        let left = &mut hole.start;
+        let mut right = v_mid;
+        let out = &mut hole.dest;
+
+        while *left < hole.end && right < v_end {
+            // Consume the lesser side.
+            // If equal, prefer the left run to maintain stability.
+
+            // SAFETY: left and right must be valid and part of v same for out.
+            unsafe {
+                let is_l = is_less(&*right, &**left);
+                let to_copy = if is_l { right } else { *left };
+                ptr::copy_nonoverlapping(to_copy, *out, 1);
+                *out = out.add(1);
+                right = right.add(is_l as usize);
+                *left = left.add(!is_l as usize);
+            }
+        }
+    } else {
+        // The right run is shorter.
+
+        // SAFETY: buf must have enough capacity for `v[mid..]`.
+        unsafe {
+            ptr::copy_nonoverlapping(v_mid, buf, len - mid);
+            hole = MergeHole {
+                start: buf,
+                end: buf.add(len - mid),
+                dest: v_mid,
+            };
+        }
+
+        // Initially, these pointers point past the ends of their arrays.
+        let left = &mut hole.dest;
+        let right = &mut hole.end;
+        let mut out = v_end;
+
+        while v < *left && buf < *right {
+            // Consume the greater side.
+            // If equal, prefer the right run to maintain stability.
+
+            // SAFETY: left and right must be valid and part of v same for out.
+            unsafe {
+                let is_l = is_less(&*right.sub(1), &*left.sub(1));
+                *left = left.sub(is_l as usize);
+                *right = right.sub(!is_l as usize);
+                let to_copy = if is_l { *left } else { *right };
+                out = out.sub(1);
+                ptr::copy_nonoverlapping(to_copy, out, 1);
+            }
+        }
+    }
+    // Finally, `hole` gets dropped. If the shorter run was not fully consumed, whatever remains of
+    // it will now be copied into the hole in `v`.
+
+    // When dropped, copies the range `start..end` into `dest..`.
+    struct MergeHole<T> {
+        start: *mut T,
+        end: *mut T,
+        dest: *mut T,
+    }
+
+    impl<T> Drop for MergeHole<T> {
+        fn drop(&mut self) {
+            // SAFETY: `T` is not a zero-sized type, and these are pointers into a slice's elements.
+            unsafe {
+                let len = crate::sub_ptr(self.end, self.start);
+                ptr::copy_nonoverlapping(self.start, self.dest, len);
+            }
+        }
+    }
+}
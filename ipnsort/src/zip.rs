@@ -0,0 +1,385 @@
+//! Sorting a key slice together with payload slices, see [`crate::sort_zip`].
+
+use core::{cmp, mem};
+
+use crate::smallsort::SmallSortImpl;
+
+/// One or more payload slices, that are reordered together with the keys by [`crate::sort_zip`].
+///
+/// Implemented for `&mut [V]` and tuples of up to four payloads, e.g.
+/// `(&mut [u32], &mut [String])`.
+pub trait Payloads {
+    /// Returns `true` if all payload slices have length `len`.
+    fn has_len(&self, len: usize) -> bool;
+
+    /// Swaps the elements at `a` and `b` in all payload slices.
+    fn swap(&mut self, a: usize, b: usize);
+}
+
+impl<V> Payloads for &mut [V] {
+    #[inline]
+    fn has_len(&self, len: usize) -> bool {
+        self.len() == len
+    }
+
+    #[inline]
+    fn swap(&mut self, a: usize, b: usize) {
+        <[V]>::swap(self, a, b);
+    }
+}
+
+macro_rules! payloads_tuple_impl {
+    ($($p:ident $i:tt),+) => {
+        impl<$($p: Payloads),+> Payloads for ($($p,)+) {
+            #[inline]
+            fn has_len(&self, len: usize) -> bool {
+                $(self.$i.has_len(len))&&+
+            }
+
+            #[inline]
+            fn swap(&mut self, a: usize, b: usize) {
+                $(self.$i.swap(a, b);)+
+            }
+        }
+    };
+}
+
+payloads_tuple_impl!(A 0);
+payloads_tuple_impl!(A 0, B 1);
+payloads_tuple_impl!(A 0, B 1, C 2);
+payloads_tuple_impl!(A 0, B 1, C 2, D 3);
+
+/// Panics if the payloads don't have the same length as the keys.
+fn check_payload_len<P: Payloads>(payloads: &P, len: usize) {
+    assert!(
+        payloads.has_len(len),
+        "all payloads have to be as long as the keys"
+    );
+}
+
+/// The keys and payloads sorted together. All positions are indices into the full slices, so that
+/// the payloads never have to be sub-sliced.
+///
+/// Every reordering is done by swapping, which moves the keys and payloads together. If `is_less`
+/// panics, each key still has its own payloads.
+struct Zip<'a, K, P> {
+    keys: &'a mut [K],
+    payloads: P,
+}
+
+impl<K, P: Payloads> Zip<'_, K, P> {
+    #[inline(always)]
+    fn swap(&mut self, a: usize, b: usize) {
+        self.keys.swap(a, b);
+        self.payloads.swap(a, b);
+    }
+
+    fn reverse(&mut self, mut start: usize, mut end: usize) {
+        while start + 1 < end {
+            end -= 1;
+            self.swap(start, end);
+            start += 1;
+        }
+    }
+
+    /// Rotates `start..end` to the left by `mid - start`, with three reversals.
+    fn rotate(&mut self, start: usize, mid: usize, end: usize) {
+        self.reverse(start, mid);
+        self.reverse(mid, end);
+        self.reverse(start, end);
+    }
+}
+
+/// Sorts `keys` and reorders `payloads` with it, see [`crate::sort_zip`].
+pub(crate) fn unstable_sort_zip<K, P, F>(keys: &mut [K], payloads: P, mut is_less: F)
+where
+    P: Payloads,
+    F: FnMut(&K, &K) -> bool,
+{
+    check_payload_len(&payloads, keys.len());
+
+    let len = keys.len();
+
+    // Sorting has no meaningful behavior on zero-sized types.
+    if mem::size_of::<K>() == 0 || len < 2 {
+        return;
+    }
+
+    let (streak_end, was_reversed) = crate::find_streak(keys, &mut is_less);
+
+    let mut zip = Zip { keys, payloads };
+
+    if streak_end == len {
+        if was_reversed {
+            zip.reverse(0, len);
+        }
+        return;
+    }
+
+    // Limit the number of imbalanced partitions to `2 * floor(log2(len))`.
+    // The binary OR by one is used to eliminate the zero-check in the logarithm.
+    let limit = 2 * (len | 1).ilog2();
+
+    quicksort(&mut zip, 0, len, &mut is_less, None, limit);
+}
+
+/// Sorts `zip.keys[start..end]` recursively, mirroring [`crate::quicksort::quicksort`].
+///
+/// If the range had a predecessor in the original array, its position is specified as
+/// `ancestor_pivot`.
+fn quicksort<K, P, F>(
+    zip: &mut Zip<'_, K, P>,
+    mut start: usize,
+    end: usize,
+    is_less: &mut F,
+    mut ancestor_pivot: Option<usize>,
+    mut limit: u32,
+) where
+    P: Payloads,
+    F: FnMut(&K, &K) -> bool,
+{
+    loop {
+        // `choose_pivot` expects more than `SMALL_SORT_THRESHOLD` elements.
+        if end - start <= K::SMALL_SORT_THRESHOLD {
+            insertion_sort(zip, start, end, is_less);
+            return;
+        }
+
+        // If too many bad pivot choices were made, simply fall back to heapsort in order to
+        // guarantee `O(n * log(n))` worst-case.
+        if limit == 0 {
+            heapsort(zip, start, end, is_less);
+            return;
+        }
+
+        limit -= 1;
+
+        let pivot_pos = start + crate::pivot::choose_pivot(&zip.keys[start..end], is_less);
+
+        // If the chosen pivot is equal to the predecessor, then it's the smallest element in the
+        // range. Partition the range into elements equal to and elements greater than the pivot.
+        if let Some(p) = ancestor_pivot {
+            if !is_less(&zip.keys[p], &zip.keys[pivot_pos]) {
+                let mid = partition(zip, start, end, pivot_pos, &mut |a, b| !is_less(b, a));
+                start = mid + 1;
+                ancestor_pivot = None;
+                continue;
+            }
+        }
+
+        let mid = partition(zip, start, end, pivot_pos, is_less);
+
+        quicksort(zip, start, mid, is_less, ancestor_pivot, limit);
+
+        start = mid + 1;
+        ancestor_pivot = Some(mid);
+    }
+}
+
+/// Moves the elements that compare true for `is_less(elem, pivot)` in front of the pivot, and the
+/// other elements behind it. Returns the new position of the pivot.
+///
+/// This is a swapping Hoare partition, the cyclic permutation of [`crate::quicksort::partition`]
+/// would need a temporary for each payload.
+fn partition<K, P, F>(
+    zip: &mut Zip<'_, K, P>,
+    start: usize,
+    end: usize,
+    pivot_pos: usize,
+    is_less: &mut F,
+) -> usize
+where
+    P: Payloads,
+    F: FnMut(&K, &K) -> bool,
+{
+    // Place the pivot at the beginning of the range.
+    zip.swap(start, pivot_pos);
+
+    let mut left = start + 1;
+    let mut right = end;
+
+    loop {
+        // Find the first element greater than or equal to the pivot.
+        while left < right && is_less(&zip.keys[left], &zip.keys[start]) {
+            left += 1;
+        }
+
+        // Find the last element less than the pivot.
+        while left < right && !is_less(&zip.keys[right - 1], &zip.keys[start]) {
+            right -= 1;
+        }
+
+        // Are we done?
+        if left >= right {
+            break;
+        }
+
+        right -= 1;
+        zip.swap(left, right);
+        left += 1;
+    }
+
+    // Place the pivot between the two partitions.
+    let mid = left - 1;
+    zip.swap(start, mid);
+
+    mid
+}
+
+/// Sorts `zip.keys[start..end]` with insertion sort, which preserves the order of equal keys.
+fn insertion_sort<K, P, F>(zip: &mut Zip<'_, K, P>, start: usize, end: usize, is_less: &mut F)
+where
+    P: Payloads,
+    F: FnMut(&K, &K) -> bool,
+{
+    for i in (start + 1)..end {
+        let mut j = i;
+        while j > start && is_less(&zip.keys[j], &zip.keys[j - 1]) {
+            zip.swap(j - 1, j);
+            j -= 1;
+        }
+    }
+}
+
+/// Sorts `zip.keys[start..end]` with heapsort, see [`crate::heapsort::heapsort`].
+#[inline(never)]
+fn heapsort<K, P, F>(zip: &mut Zip<'_, K, P>, start: usize, end: usize, is_less: &mut F)
+where
+    P: Payloads,
+    F: FnMut(&K, &K) -> bool,
+{
+    let len = end - start;
+
+    // Build the heap in linear time.
+    for i in (0..len / 2).rev() {
+        sift_down(zip, start, len, i, is_less);
+    }
+
+    // Pop maximal elements from the heap.
+    for i in (1..len).rev() {
+        zip.swap(start, start + i);
+        sift_down(zip, start, i, 0, is_less);
+    }
+}
+
+// This binary heap over `start..start + len` respects the invariant `parent >= child`.
+fn sift_down<K, P, F>(
+    zip: &mut Zip<'_, K, P>,
+    start: usize,
+    len: usize,
+    mut node: usize,
+    is_less: &mut F,
+) where
+    P: Payloads,
+    F: FnMut(&K, &K) -> bool,
+{
+    loop {
+        // Children of `node`.
+        let mut child = 2 * node + 1;
+        if child >= len {
+            break;
+        }
+
+        // Choose the greater child.
+        if child + 1 < len {
+            child += is_less(&zip.keys[start + child], &zip.keys[start + child + 1]) as usize;
+        }
+
+        // Stop if the invariant holds at `node`.
+        if !is_less(&zip.keys[start + node], &zip.keys[start + child]) {
+            break;
+        }
+
+        zip.swap(start + node, start + child);
+        node = child;
+    }
+}
+
+/// Sorts `keys` and reorders `payloads` with it, preserving the order of equal keys, see
+/// [`crate::stable::sort_zip`].
+pub(crate) fn stable_sort_zip<K, P, F>(keys: &mut [K], payloads: P, mut is_less: F)
+where
+    P: Payloads,
+    F: FnMut(&K, &K) -> bool,
+{
+    // Runs of this length are sorted with insertion sort, before they get merged.
+    const RUN_LEN: usize = 16;
+
+    check_payload_len(&payloads, keys.len());
+
+    let len = keys.len();
+
+    // Zero-sized keys are all equal, so they are already sorted.
+    if mem::size_of::<K>() == 0 || len < 2 {
+        return;
+    }
+
+    let mut zip = Zip { keys, payloads };
+
+    for start in (0..len).step_by(RUN_LEN) {
+        insertion_sort(
+            &mut zip,
+            start,
+            cmp::min(start + RUN_LEN, len),
+            &mut is_less,
+        );
+    }
+
+    let mut width = RUN_LEN;
+    while width < len {
+        for start in (0..len - width).step_by(2 * width) {
+            let mid = start + width;
+            let end = cmp::min(mid + width, len);
+            merge(&mut zip, start, mid, end, &mut is_less);
+        }
+        width *= 2;
+    }
+}
+
+/// Merges the non-decreasing runs `zip.keys[start..mid]` and `zip.keys[mid..end]` in-place.
+///
+/// Same as the fallback of [`crate::stable::merge_with_scratch`] without any scratch: the longer
+/// run is split in half, the matching split point of the shorter run is found with a binary
+/// search, and rotating the two inner pieces leaves two independent smaller merges.
+fn merge<K, P, F>(zip: &mut Zip<'_, K, P>, start: usize, mid: usize, end: usize, is_less: &mut F)
+where
+    P: Payloads,
+    F: FnMut(&K, &K) -> bool,
+{
+    if start == mid || mid == end {
+        return;
+    }
+
+    // The runs are already in order, which makes presorted inputs linear.
+    if !is_less(&zip.keys[mid], &zip.keys[mid - 1]) {
+        return;
+    }
+
+    if end - start == 2 {
+        zip.swap(start, mid);
+        return;
+    }
+
+    let (left_cut, right_cut) = if mid - start >= end - mid {
+        // All elements of `mid..right_cut` are less than `left_cut..mid`, moving them in front of
+        // it preserves stability.
+        let left_cut = start + (mid - start) / 2;
+        let (left, right) = zip.keys[..end].split_at(mid);
+        let right_cut = mid + right.partition_point(|x| is_less(x, &left[left_cut]));
+        (left_cut, right_cut)
+    } else {
+        // All elements of `left_cut..mid` are greater than `mid..right_cut`, moving them behind it
+        // preserves stability.
+        let right_cut = mid + (end - mid) / 2;
+        let (left, right) = zip.keys.split_at(mid);
+        let left_cut =
+            start + left[start..].partition_point(|x| !is_less(&right[right_cut - mid], x));
+        (left_cut, right_cut)
+    };
+
+    zip.rotate(left_cut, mid, right_cut);
+
+    let new_mid = left_cut + (right_cut - mid);
+    merge(zip, start, left_cut, new_mid, is_less);
+    merge(zip, new_mid, right_cut, end, is_less);
+}
@@ -1,3 +1,8 @@
+use std::cmp::Ordering;
+use std::panic::{self, AssertUnwindSafe};
+
+use sort_test_tools::ffi_types::{FFIOneKiloByte, FFIString};
+use sort_test_tools::patterns;
 use sort_test_tools::{instantiate_sort_tests, Sort};
 
 struct SortImpl {}
@@ -16,10 +21,1994 @@ impl Sort for SortImpl {
 
     fn sort_by<T, F>(arr: &mut [T], compare: F)
     where
-        F: FnMut(&T, &T) -> std::cmp::Ordering,
+        F: FnMut(&T, &T) -> Ordering,
     {
         ipnsort::sort_by(arr, compare);
     }
+
+    fn sort_by_key<T, K, F>(arr: &mut [T], f: F)
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        ipnsort::sort_by_key(arr, f);
+    }
+
+    fn sort_by_cached_key<T, K, F>(arr: &mut [T], f: F)
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        ipnsort::sort_by_cached_key(arr, f);
+    }
+
+    fn try_sort_by<T, E, F>(arr: &mut [T], compare: F) -> Result<(), E>
+    where
+        F: FnMut(&T, &T) -> Result<Ordering, E>,
+    {
+        ipnsort::try_sort_by(arr, compare)
+    }
 }
 
 instantiate_sort_tests!(SortImpl);
+
+mod stable {
+    use super::*;
+
+    struct SortImpl {}
+
+    impl Sort for SortImpl {
+        fn name() -> String {
+            "rust_ipnsort_stable".into()
+        }
+
+        fn sort<T>(arr: &mut [T])
+        where
+            T: Ord,
+        {
+            ipnsort::stable::sort(arr);
+        }
+
+        fn sort_by<T, F>(arr: &mut [T], compare: F)
+        where
+            F: FnMut(&T, &T) -> Ordering,
+        {
+            ipnsort::stable::sort_by(arr, compare);
+        }
+
+        fn sort_by_key<T, K, F>(arr: &mut [T], f: F)
+        where
+            F: FnMut(&T) -> K,
+            K: Ord,
+        {
+            ipnsort::stable::sort_by_key(arr, f);
+        }
+
+        fn try_sort_by<T, E, F>(arr: &mut [T], compare: F) -> Result<(), E>
+        where
+            F: FnMut(&T, &T) -> Result<Ordering, E>,
+        {
+            ipnsort::stable::try_sort_by(arr, compare)
+        }
+    }
+
+    instantiate_sort_tests!(SortImpl);
+}
+
+// Buffers that are too small for the regular merges exercise the in-place merge fallback.
+macro_rules! instantiate_sort_with_buffer_tests {
+    ($mod_name:ident, $name:expr, $buf_len_fn:expr) => {
+        mod $mod_name {
+            use super::*;
+
+            struct SortImpl {}
+
+            impl Sort for SortImpl {
+                fn name() -> String {
+                    $name.into()
+                }
+
+                fn sort<T>(arr: &mut [T])
+                where
+                    T: Ord,
+                {
+                    let buf_len = $buf_len_fn(arr.len());
+                    let mut buf = Vec::<T>::with_capacity(buf_len);
+                    let buf = &mut buf.spare_capacity_mut()[..buf_len];
+                    ipnsort::stable::sort_with_buffer(arr, buf);
+                }
+
+                fn sort_by<T, F>(arr: &mut [T], compare: F)
+                where
+                    F: FnMut(&T, &T) -> Ordering,
+                {
+                    let buf_len = $buf_len_fn(arr.len());
+                    let mut buf = Vec::<T>::with_capacity(buf_len);
+                    let buf = &mut buf.spare_capacity_mut()[..buf_len];
+                    ipnsort::stable::sort_by_with_buffer(arr, buf, compare);
+                }
+            }
+
+            instantiate_sort_tests!(SortImpl);
+        }
+    };
+}
+
+instantiate_sort_with_buffer_tests!(stable_no_buffer, "rust_ipnsort_stable_no_buffer", |_| 0);
+
+instantiate_sort_with_buffer_tests!(
+    stable_small_buffer,
+    "rust_ipnsort_stable_small_buffer",
+    |len: usize| len / 8
+);
+
+// --- select_nth_unstable ---
+
+const SELECT_TEST_SIZES: [usize; 16] = [
+    1, 2, 3, 4, 7, 16, 17, 20, 24, 33, 50, 100, 280, 1_000, 10_000, 100_000,
+];
+
+fn select_pattern_fns() -> Vec<fn(usize) -> Vec<i32>> {
+    vec![
+        patterns::random,
+        |size| patterns::random_uniform(size, 0..4),
+        |size| patterns::random_uniform(size, 0..256),
+        |size| patterns::random_zipf(size, 1.0),
+        |size| patterns::random_sorted(size, 95.0),
+        patterns::all_equal,
+        patterns::ascending,
+        patterns::descending,
+        |size| patterns::saw_mixed(size, ((size as f64).log2().round()) as usize),
+        patterns::pipe_organ,
+    ]
+}
+
+fn select_indices(len: usize) -> Vec<usize> {
+    if len <= 50 {
+        return (0..len).collect();
+    }
+
+    let mut indices = vec![0, 1, len / 2, len - 2, len - 1];
+    indices.extend(patterns::random_uniform::<usize>(8, 0..(len as i32)));
+
+    indices
+}
+
+fn check_select<T: Ord + Clone + std::fmt::Debug>(original: &[T], selected: &[T], index: usize) {
+    let mut expected = original.to_vec();
+    expected.sort();
+
+    assert_eq!(selected[index], expected[index]);
+    assert!(selected[..index].iter().all(|x| *x <= selected[index]));
+    assert!(selected[(index + 1)..]
+        .iter()
+        .all(|x| *x >= selected[index]));
+
+    let mut selected_sorted = selected.to_vec();
+    selected_sorted.sort();
+    assert_eq!(selected_sorted, expected);
+}
+
+#[test]
+fn select_nth_unstable_patterns() {
+    for pattern_fn in select_pattern_fns() {
+        for len in SELECT_TEST_SIZES {
+            let original = pattern_fn(len);
+
+            for index in select_indices(len) {
+                let mut v = original.clone();
+                let (left, nth, right) = ipnsort::select_nth_unstable(&mut v, index);
+                assert_eq!(left.len(), index);
+                assert_eq!(right.len(), len - index - 1);
+                let nth = *nth;
+
+                check_select(&original, &v, index);
+                assert_eq!(v[index], nth);
+            }
+        }
+    }
+}
+
+#[test]
+fn select_nth_unstable_by_reversed() {
+    for len in SELECT_TEST_SIZES {
+        let original = patterns::random::<i32>(len);
+
+        for index in select_indices(len) {
+            let mut v = original.clone();
+            ipnsort::select_nth_unstable_by(&mut v, index, |a, b| b.cmp(a));
+
+            let reversed = |v: &[i32]| -> Vec<std::cmp::Reverse<i32>> {
+                v.iter().copied().map(std::cmp::Reverse).collect()
+            };
+            check_select(&reversed(&original), &reversed(&v), index);
+        }
+    }
+}
+
+#[test]
+fn select_nth_unstable_by_key_abs() {
+    for len in SELECT_TEST_SIZES {
+        let original = patterns::random_uniform(len, -1000..1000);
+
+        for index in select_indices(len) {
+            let mut v = original.clone();
+            ipnsort::select_nth_unstable_by_key(&mut v, index, |x: &i32| x.abs());
+
+            let abs = |v: &[i32]| -> Vec<i32> { v.iter().map(|x| x.abs()).collect() };
+            check_select(&abs(&original), &abs(&v), index);
+        }
+    }
+}
+
+#[test]
+fn select_nth_unstable_ffi_string() {
+    for len in SELECT_TEST_SIZES {
+        let original = patterns::random::<FFIString>(len);
+
+        for index in select_indices(len) {
+            let mut v = original.clone();
+            ipnsort::select_nth_unstable(&mut v, index);
+            check_select(&original, &v, index);
+        }
+    }
+}
+
+#[test]
+fn select_nth_unstable_zst() {
+    let mut v = [(), (), ()];
+    let (left, _, right) = ipnsort::select_nth_unstable(&mut v, 1);
+    assert_eq!(left.len(), 1);
+    assert_eq!(right.len(), 1);
+}
+
+#[test]
+#[should_panic]
+fn select_nth_unstable_empty() {
+    let mut v: [i32; 0] = [];
+    ipnsort::select_nth_unstable(&mut v, 0);
+}
+
+#[test]
+#[should_panic]
+fn select_nth_unstable_out_of_bounds() {
+    let mut v = [3, 1, 2];
+    ipnsort::select_nth_unstable(&mut v, 3);
+}
+
+#[test]
+fn select_nth_unstable_violate_ord_retain_original_set() {
+    // Every comparison result is driven by a random sequence, which violates Ord.
+    let random_orderings = patterns::random_uniform::<i32>(5_000, 0..3);
+
+    for len in SELECT_TEST_SIZES {
+        let original = patterns::random::<i32>(len);
+        let sum_before: i64 = original.iter().map(|x| *x as i64).sum();
+
+        for index in select_indices(len) {
+            let mut v = original.clone();
+            let mut random_idx = 0;
+
+            // It's ok to panic on Ord violation or to complete.
+            // In both cases the original elements must still be present.
+            let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+                ipnsort::select_nth_unstable_by(&mut v, index, |_, _| {
+                    random_idx = (random_idx + 1) % random_orderings.len();
+                    [Ordering::Less, Ordering::Equal, Ordering::Greater]
+                        [random_orderings[random_idx] as usize]
+                });
+            }));
+
+            let sum_after: i64 = v.iter().map(|x| *x as i64).sum();
+            assert_eq!(sum_before, sum_after);
+        }
+    }
+}
+
+// --- partition3 ---
+
+fn check_partition3<T: Ord + Clone + std::fmt::Debug>(
+    original: &[T],
+    partitioned: &[T],
+    pivot: &T,
+    (lt_len, eq_len): (usize, usize),
+) {
+    let (lt, rest) = partitioned.split_at(lt_len);
+    let (eq, gt) = rest.split_at(eq_len);
+
+    assert!(lt.iter().all(|x| x < pivot));
+    assert!(eq.iter().all(|x| x == pivot));
+    assert!(gt.iter().all(|x| x > pivot));
+
+    let mut partitioned_sorted = partitioned.to_vec();
+    partitioned_sorted.sort();
+    let mut expected = original.to_vec();
+    expected.sort();
+    assert_eq!(partitioned_sorted, expected);
+}
+
+/// The pivots that get tested for `v`, elements of `v` and values outside its range.
+fn partition3_pivots(v: &[i32]) -> Vec<i32> {
+    let mut pivots = v
+        .iter()
+        .step_by(v.len() / 8 + 1)
+        .copied()
+        .collect::<Vec<_>>();
+    pivots.extend([i32::MIN, i32::MAX]);
+    pivots
+}
+
+#[test]
+fn partition3_patterns() {
+    for pattern_fn in select_pattern_fns() {
+        for len in SELECT_TEST_SIZES {
+            let original = pattern_fn(len);
+
+            for pivot in partition3_pivots(&original) {
+                let mut v = original.clone();
+                let lens = ipnsort::partition3(&mut v, &pivot);
+                check_partition3(&original, &v, &pivot, lens);
+
+                let mut v = original.iter().map(|&x| x as u64).collect::<Vec<_>>();
+                let lens = ipnsort::partition3(&mut v, &(pivot as u64));
+                let original = original.iter().map(|&x| x as u64).collect::<Vec<_>>();
+                check_partition3(&original, &v, &(pivot as u64), lens);
+            }
+        }
+    }
+}
+
+#[test]
+fn partition3_by_reversed() {
+    for len in SELECT_TEST_SIZES {
+        let original = patterns::random_uniform(len, 0..10);
+
+        for pivot in partition3_pivots(&original) {
+            let mut v = original.clone();
+            let lens = ipnsort::partition3_by(&mut v, &pivot, |a, b| b.cmp(a));
+
+            let reversed = |v: &[i32]| -> Vec<std::cmp::Reverse<i32>> {
+                v.iter().copied().map(std::cmp::Reverse).collect()
+            };
+            check_partition3(
+                &reversed(&original),
+                &reversed(&v),
+                &std::cmp::Reverse(pivot),
+                lens,
+            );
+        }
+    }
+}
+
+#[test]
+fn partition3_ffi_types() {
+    for len in SELECT_TEST_SIZES {
+        let original = patterns::random_uniform(len, 0..10);
+
+        for pivot in partition3_pivots(&original) {
+            let strings = original
+                .iter()
+                .map(|val| FFIString::new(format!("{val:010}")))
+                .collect::<Vec<_>>();
+            let pivot_string = FFIString::new(format!("{pivot:010}"));
+            let mut v = strings.clone();
+            let lens = ipnsort::partition3(&mut v, &pivot_string);
+            check_partition3(&strings, &v, &pivot_string, lens);
+
+            let kilo_bytes = original
+                .iter()
+                .map(|&val| FFIOneKiloByte::new(val))
+                .collect::<Vec<_>>();
+            let pivot_kilo_byte = FFIOneKiloByte::new(pivot);
+            let mut v = kilo_bytes.clone();
+            let lens = ipnsort::partition3(&mut v, &pivot_kilo_byte);
+            check_partition3(&kilo_bytes, &v, &pivot_kilo_byte, lens);
+        }
+    }
+}
+
+#[test]
+fn partition3_empty_and_zst() {
+    let mut v: [i32; 0] = [];
+    assert_eq!(ipnsort::partition3(&mut v, &0), (0, 0));
+
+    let mut v = [(), (), ()];
+    assert_eq!(ipnsort::partition3(&mut v, &()), (0, 3));
+}
+
+#[test]
+fn partition3_violate_ord_retain_original_set() {
+    // Every comparison result is driven by a random sequence, which violates Ord.
+    let random_orderings = patterns::random_uniform::<i32>(5_000, 0..3);
+
+    for len in SELECT_TEST_SIZES {
+        let original = patterns::random(len);
+        let sum_before: i64 = original.iter().map(|x| *x as i64).sum();
+
+        let mut v = original.clone();
+        let mut random_idx = 0;
+
+        // It's ok to panic on Ord violation or to complete.
+        // In both cases the original elements must still be present.
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+            let (lt_len, eq_len) = ipnsort::partition3_by(&mut v, &0, |_, _| {
+                random_idx = (random_idx + 1) % random_orderings.len();
+                [Ordering::Less, Ordering::Equal, Ordering::Greater]
+                    [random_orderings[random_idx] as usize]
+            });
+            assert!(lt_len + eq_len <= len);
+        }));
+
+        let sum_after: i64 = v.iter().map(|x| *x as i64).sum();
+        assert_eq!(sum_before, sum_after);
+    }
+}
+
+// --- partial_sort ---
+
+fn check_partial_sort<T: Ord + Clone + std::fmt::Debug>(original: &[T], sorted: &[T], k: usize) {
+    let mut expected = original.to_vec();
+    expected.sort();
+
+    assert_eq!(sorted[..k], expected[..k]);
+
+    let mut sorted_all = sorted.to_vec();
+    sorted_all.sort();
+    assert_eq!(sorted_all, expected);
+}
+
+fn partial_sort_ks(len: usize) -> Vec<usize> {
+    let mut ks = select_indices(len);
+    ks.push(len);
+
+    ks
+}
+
+#[test]
+fn partial_sort_patterns() {
+    for pattern_fn in select_pattern_fns() {
+        for len in SELECT_TEST_SIZES {
+            let original = pattern_fn(len);
+
+            for k in partial_sort_ks(len) {
+                let mut v = original.clone();
+                ipnsort::partial_sort(&mut v, k);
+                check_partial_sort(&original, &v, k);
+            }
+        }
+    }
+}
+
+#[test]
+fn partial_sort_by_reversed() {
+    for len in SELECT_TEST_SIZES {
+        let original = patterns::random::<i32>(len);
+
+        for k in partial_sort_ks(len) {
+            let mut v = original.clone();
+            ipnsort::partial_sort_by(&mut v, k, |a, b| b.cmp(a));
+
+            let reversed = |v: &[i32]| -> Vec<std::cmp::Reverse<i32>> {
+                v.iter().copied().map(std::cmp::Reverse).collect()
+            };
+            check_partial_sort(&reversed(&original), &reversed(&v), k);
+        }
+    }
+}
+
+#[test]
+fn partial_sort_ffi_string() {
+    for len in SELECT_TEST_SIZES {
+        let original = patterns::random::<FFIString>(len);
+
+        for k in partial_sort_ks(len) {
+            let mut v = original.clone();
+            ipnsort::partial_sort(&mut v, k);
+            check_partial_sort(&original, &v, k);
+        }
+    }
+}
+
+#[test]
+fn partial_sort_empty_and_zst() {
+    let mut v: [i32; 0] = [];
+    ipnsort::partial_sort(&mut v, 0);
+
+    let mut v = [(), (), ()];
+    ipnsort::partial_sort(&mut v, 2);
+}
+
+#[test]
+#[should_panic]
+fn partial_sort_out_of_bounds() {
+    let mut v = [3, 1, 2];
+    ipnsort::partial_sort(&mut v, 4);
+}
+
+#[test]
+fn partial_sort_violate_ord_retain_original_set() {
+    // Every comparison result is driven by a random sequence, which violates Ord.
+    let random_orderings = patterns::random_uniform::<i32>(5_000, 0..3);
+
+    for len in SELECT_TEST_SIZES {
+        let original = patterns::random::<i32>(len);
+        let sum_before: i64 = original.iter().map(|x| *x as i64).sum();
+
+        for k in partial_sort_ks(len) {
+            let mut v = original.clone();
+            let mut random_idx = 0;
+
+            // It's ok to panic on Ord violation or to complete.
+            // In both cases the original elements must still be present.
+            let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+                ipnsort::partial_sort_by(&mut v, k, |_, _| {
+                    random_idx = (random_idx + 1) % random_orderings.len();
+                    [Ordering::Less, Ordering::Equal, Ordering::Greater]
+                        [random_orderings[random_idx] as usize]
+                });
+            }));
+
+            let sum_after: i64 = v.iter().map(|x| *x as i64).sum();
+            assert_eq!(sum_before, sum_after);
+        }
+    }
+}
+
+// --- sort_dedup ---
+
+fn dedup_pattern_fns() -> Vec<fn(usize) -> Vec<i32>> {
+    vec![
+        |size| patterns::random_uniform(size, 0..2),
+        |size| patterns::random_uniform(size, 0..4),
+        |size| patterns::random_uniform(size, 0..20),
+        |size| patterns::random_uniform(size, 0..256),
+        |size| patterns::random_uniform(size, 0..1024),
+        |size| patterns::random_zipf(size, 1.0),
+        |size| patterns::random_zipf(size, 1.1),
+        |size| patterns::random_zipf(size, 1.2),
+        patterns::random,
+        patterns::all_equal,
+        patterns::ascending,
+        patterns::descending,
+        |size| patterns::saw_mixed(size, ((size as f64).log2().round()) as usize),
+        patterns::pipe_organ,
+    ]
+}
+
+fn check_sort_dedup<T: Ord + Clone + std::fmt::Debug>(original: &[T], result: &[T], n: usize) {
+    let mut expected = original.to_vec();
+    expected.sort();
+    expected.dedup();
+
+    assert_eq!(&result[..n], &expected[..]);
+
+    let mut result_sorted = result.to_vec();
+    result_sorted.sort();
+    let mut original_sorted = original.to_vec();
+    original_sorted.sort();
+    assert_eq!(result_sorted, original_sorted);
+}
+
+#[test]
+fn sort_dedup_patterns() {
+    for pattern_fn in dedup_pattern_fns() {
+        for len in SELECT_TEST_SIZES {
+            let original = pattern_fn(len);
+
+            let mut v = original.clone();
+            let n = ipnsort::sort_dedup(&mut v);
+            check_sort_dedup(&original, &v, n);
+        }
+    }
+}
+
+#[test]
+fn sort_dedup_by_abs() {
+    for len in SELECT_TEST_SIZES {
+        let original = patterns::random_uniform::<i32>(len, -100..100);
+
+        let mut v = original.clone();
+        let n = ipnsort::sort_dedup_by(&mut v, |a, b| a.abs().cmp(&b.abs()));
+
+        let abs = |v: &[i32]| -> Vec<i32> { v.iter().map(|x| x.abs()).collect() };
+        check_sort_dedup(&abs(&original), &abs(&v), n);
+    }
+}
+
+#[test]
+fn sort_dedup_ffi_string() {
+    for len in SELECT_TEST_SIZES {
+        let original = patterns::random_zipf::<FFIString>(len, 1.0);
+
+        let mut v = original.clone();
+        let n = ipnsort::sort_dedup(&mut v);
+        check_sort_dedup(&original, &v, n);
+    }
+}
+
+#[test]
+fn sort_dedup_empty_and_zst() {
+    let mut v: [i32; 0] = [];
+    assert_eq!(ipnsort::sort_dedup(&mut v), 0);
+
+    let mut v = [(), (), ()];
+    assert_eq!(ipnsort::sort_dedup(&mut v), 1);
+}
+
+#[test]
+fn sort_dedup_violate_ord_retain_original_set() {
+    // Every comparison result is driven by a random sequence, which violates Ord.
+    let random_orderings = patterns::random_uniform::<i32>(5_000, 0..3);
+
+    for len in SELECT_TEST_SIZES {
+        let original = patterns::random_uniform::<i32>(len, 0..20);
+        let sum_before: i64 = original.iter().map(|x| *x as i64).sum();
+
+        let mut v = original.clone();
+        let mut random_idx = 0;
+
+        // It's ok to panic on Ord violation or to complete.
+        // In both cases the original elements must still be present.
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+            let n = ipnsort::sort_dedup_by(&mut v, |_, _| {
+                random_idx = (random_idx + 1) % random_orderings.len();
+                [Ordering::Less, Ordering::Equal, Ordering::Greater]
+                    [random_orderings[random_idx] as usize]
+            });
+            assert!(n <= len);
+        }));
+
+        let sum_after: i64 = v.iter().map(|x| *x as i64).sum();
+        assert_eq!(sum_before, sum_after);
+    }
+}
+
+// --- argsort ---
+
+fn check_argsort<T: Ord + Clone + std::fmt::Debug>(original: &[T], perm: &[usize]) {
+    let mut is_index_used = vec![false; original.len()];
+    for &i in perm {
+        assert!(!is_index_used[i]);
+        is_index_used[i] = true;
+    }
+    assert_eq!(perm.len(), original.len());
+
+    let mut expected = original.to_vec();
+    expected.sort();
+
+    let permuted = perm
+        .iter()
+        .map(|&i| original[i].clone())
+        .collect::<Vec<_>>();
+    assert_eq!(permuted, expected);
+}
+
+/// The stable argsort is uniquely defined, which allows comparing it to the std library.
+fn check_stable_argsort<T: Ord>(original: &[T], perm: &[usize]) {
+    let mut expected = (0..original.len()).collect::<Vec<_>>();
+    expected.sort_by_key(|&i| &original[i]);
+
+    assert_eq!(perm, expected);
+}
+
+#[test]
+fn argsort_patterns() {
+    for pattern_fn in dedup_pattern_fns() {
+        for len in SELECT_TEST_SIZES {
+            let original = pattern_fn(len);
+
+            check_argsort(&original, &ipnsort::argsort(&original));
+            check_stable_argsort(&original, &ipnsort::stable::argsort(&original));
+
+            let original_u32 = original.iter().map(|&x| x as u32).collect::<Vec<_>>();
+            check_argsort(&original_u32, &ipnsort::argsort(&original_u32));
+        }
+    }
+}
+
+#[test]
+fn argsort_ffi_string() {
+    for len in SELECT_TEST_SIZES {
+        let original = patterns::random_uniform::<FFIString>(len, 0..100);
+
+        check_argsort(&original, &ipnsort::argsort(&original));
+        check_stable_argsort(&original, &ipnsort::stable::argsort(&original));
+    }
+}
+
+#[test]
+fn argsort_by_reversed() {
+    for len in SELECT_TEST_SIZES {
+        let original = patterns::random_uniform::<i32>(len, -50..50);
+
+        let perm = ipnsort::argsort_by(&original, |a, b| b.cmp(a));
+        let reversed = original
+            .iter()
+            .map(|x| std::cmp::Reverse(*x))
+            .collect::<Vec<_>>();
+        check_argsort(&reversed, &perm);
+
+        let perm = ipnsort::stable::argsort_by(&original, |a, b| b.cmp(a));
+        check_stable_argsort(&reversed, &perm);
+    }
+}
+
+#[test]
+fn argsort_empty() {
+    let v: [i32; 0] = [];
+    assert!(ipnsort::argsort(&v).is_empty());
+    assert!(ipnsort::stable::argsort(&v).is_empty());
+
+    let v = [(); 100];
+    check_stable_argsort(&v, &ipnsort::stable::argsort(&v));
+    check_argsort(&v, &ipnsort::argsort(&v));
+}
+
+#[test]
+fn argsort_violate_ord_returns_permutation() {
+    // Every comparison result is driven by a random sequence, which violates Ord.
+    let random_orderings = patterns::random_uniform::<i32>(5_000, 0..3);
+
+    for len in SELECT_TEST_SIZES {
+        let original = patterns::random(len);
+        let mut random_idx = 0;
+
+        let mut random_cmp = |_: &i32, _: &i32| {
+            random_idx = (random_idx + 1) % random_orderings.len();
+            [Ordering::Less, Ordering::Equal, Ordering::Greater]
+                [random_orderings[random_idx] as usize]
+        };
+
+        // It's ok to panic on Ord violation or to complete, but the indices must stay valid.
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut perm = ipnsort::argsort_by(&original, &mut random_cmp);
+            perm.sort();
+            assert!(perm.into_iter().eq(0..len));
+
+            let mut perm = ipnsort::stable::argsort_by(&original, &mut random_cmp);
+            perm.sort();
+            assert!(perm.into_iter().eq(0..len));
+        }));
+    }
+}
+
+#[test]
+fn apply_permutation_in_place_sorts() {
+    for pattern_fn in dedup_pattern_fns() {
+        for len in SELECT_TEST_SIZES {
+            let original = pattern_fn(len);
+
+            let mut perm = ipnsort::argsort(&original);
+            let perm_before = perm.clone();
+
+            let mut v = original.clone();
+            ipnsort::apply_permutation_in_place(&mut v, &mut perm);
+            assert_eq!(perm, perm_before);
+
+            let mut expected = original.clone();
+            expected.sort();
+            assert_eq!(v, expected);
+        }
+    }
+}
+
+#[test]
+fn apply_permutation_in_place_random() {
+    for len in SELECT_TEST_SIZES {
+        // A random permutation, with cycles of all lengths.
+        let mut perm = ipnsort::argsort(&patterns::random::<i32>(len));
+        let original = (0..len)
+            .map(|i| FFIString::new(format!("{:010}", i)))
+            .collect::<Vec<_>>();
+
+        let mut v = original.clone();
+        ipnsort::apply_permutation_in_place(&mut v, &mut perm);
+
+        for (i, &p) in perm.iter().enumerate() {
+            assert_eq!(v[i], original[p]);
+        }
+    }
+}
+
+#[test]
+fn apply_permutation_in_place_invalid() {
+    let invalid_perms: [&[usize]; 5] = [&[0, 1], &[0, 1, 2, 3], &[0, 0, 1], &[1, 2, 1], &[0, 3, 1]];
+
+    for invalid_perm in invalid_perms {
+        let mut perm = invalid_perm.to_vec();
+        let mut v = [10, 20, 30];
+
+        let res = panic::catch_unwind(AssertUnwindSafe(|| {
+            ipnsort::apply_permutation_in_place(&mut v, &mut perm);
+        }));
+        assert!(res.is_err(), "perm: {invalid_perm:?}");
+
+        // The permutation is restored, and the elements are still present.
+        assert_eq!(perm, invalid_perm);
+        v.sort();
+        assert_eq!(v, [10, 20, 30]);
+    }
+}
+
+#[test]
+fn apply_permutation_in_place_invalid_high_bit() {
+    // Entries with the highest bit set must not be mistaken for visited ones.
+    let high = isize::MAX as usize + 1;
+    let invalid_perms: [&[usize]; 6] = [
+        &[usize::MAX],
+        &[high],
+        &[isize::MAX as usize],
+        &[0, usize::MAX, 1],
+        &[2, 1, high],
+        &[!1, 0, 1],
+    ];
+
+    for invalid_perm in invalid_perms {
+        let mut perm = invalid_perm.to_vec();
+        let original = (0..invalid_perm.len() as i32).collect::<Vec<_>>();
+        let mut v = original.clone();
+
+        let res = panic::catch_unwind(AssertUnwindSafe(|| {
+            ipnsort::apply_permutation_in_place(&mut v, &mut perm);
+        }));
+        assert!(res.is_err(), "perm: {invalid_perm:?}");
+
+        assert_eq!(perm, invalid_perm);
+        v.sort();
+        assert_eq!(v, original);
+    }
+}
+
+// --- try_sort_by ---
+
+#[test]
+fn try_sort_by_stable() {
+    for pattern_fn in dedup_pattern_fns() {
+        for len in SELECT_TEST_SIZES {
+            // Few distinct keys, tagged with their original position.
+            let original = pattern_fn(len)
+                .into_iter()
+                .enumerate()
+                .map(|(i, val)| (val.rem_euclid(8), i))
+                .collect::<Vec<_>>();
+
+            let mut expected = original.clone();
+            expected.sort_by_key(|&(key, _)| key);
+
+            let mut v = original.clone();
+            let res: Result<(), ()> =
+                ipnsort::stable::try_sort_by(&mut v, |a, b| Ok(a.0.cmp(&b.0)));
+            assert_eq!(res, Ok(()));
+            assert_eq!(v, expected);
+        }
+    }
+}
+
+#[test]
+fn try_sort_by_error_large() {
+    let original = patterns::random(100_000);
+
+    for error_at in [0, 1_000, 1_000_000] {
+        for stable in [false, true] {
+            let mut v = original.clone();
+            let mut comp_counter = 0;
+            let compare = |a: &i32, b: &i32| {
+                comp_counter += 1;
+                if comp_counter > error_at {
+                    return Err(comp_counter);
+                }
+                Ok(a.cmp(b))
+            };
+
+            let res = if stable {
+                ipnsort::stable::try_sort_by(&mut v, compare)
+            } else {
+                ipnsort::try_sort_by(&mut v, compare)
+            };
+
+            assert_eq!(res, Err(error_at + 1));
+            assert_eq!(comp_counter, error_at + 1);
+            assert_eq!(v, original);
+        }
+    }
+}
+
+#[test]
+fn try_sort_by_zst() {
+    let mut v = [(); 100];
+    let res = ipnsort::try_sort_by(&mut v, |_, _| Err(()));
+    assert_eq!(res, Ok(()));
+
+    let res = ipnsort::stable::try_sort_by(&mut v, |_, _| Err(()));
+    assert_eq!(res, Ok(()));
+}
+
+// --- sort_zip ---
+
+#[test]
+fn sort_zip_patterns() {
+    for pattern_fn in dedup_pattern_fns() {
+        for len in SELECT_TEST_SIZES {
+            let original = pattern_fn(len);
+
+            // The payload is the original index, which identifies where each key came from.
+            let mut keys = original.clone();
+            let mut indices = (0..len).collect::<Vec<_>>();
+            ipnsort::sort_zip(&mut keys, &mut indices[..]);
+
+            check_argsort(&original, &indices);
+            assert!(indices.iter().zip(&keys).all(|(&i, k)| original[i] == *k));
+
+            let mut keys = original.clone();
+            let mut indices = (0..len).collect::<Vec<_>>();
+            ipnsort::stable::sort_zip(&mut keys, &mut indices[..]);
+
+            check_stable_argsort(&original, &indices);
+            assert!(indices.iter().zip(&keys).all(|(&i, k)| original[i] == *k));
+        }
+    }
+}
+
+#[test]
+fn sort_zip_multiple_payloads() {
+    for len in SELECT_TEST_SIZES {
+        let original = patterns::random_uniform::<i32>(len, 0..100);
+
+        let mut keys = original.clone();
+        let mut indices = (0..len as u32).collect::<Vec<_>>();
+        let mut strings = (0..len)
+            .map(|i| FFIString::new(format!("{:010}", i)))
+            .collect::<Vec<_>>();
+        let mut neg = original.iter().map(|x| -x).collect::<Vec<_>>();
+        let mut tags = vec![(); len];
+
+        ipnsort::sort_zip(
+            &mut keys,
+            (
+                &mut indices[..],
+                &mut strings[..],
+                &mut neg[..],
+                &mut tags[..],
+            ),
+        );
+
+        let mut expected = original.clone();
+        expected.sort();
+        assert_eq!(keys, expected);
+
+        for i in 0..len {
+            assert_eq!(original[indices[i] as usize], keys[i]);
+            assert_eq!(strings[i], FFIString::new(format!("{:010}", indices[i])));
+            assert_eq!(neg[i], -keys[i]);
+        }
+    }
+}
+
+#[test]
+fn sort_zip_by_reversed() {
+    for len in SELECT_TEST_SIZES {
+        let original = patterns::random_uniform::<i32>(len, -50..50);
+        let reversed = original
+            .iter()
+            .map(|x| std::cmp::Reverse(*x))
+            .collect::<Vec<_>>();
+
+        let mut keys = original.clone();
+        let mut indices = (0..len).collect::<Vec<_>>();
+        ipnsort::sort_zip_by(&mut keys, &mut indices[..], |a, b| b.cmp(a));
+        check_argsort(&reversed, &indices);
+
+        let mut keys = original.clone();
+        let mut indices = (0..len).collect::<Vec<_>>();
+        ipnsort::stable::sort_zip_by(&mut keys, (&mut indices[..],), |a, b| b.cmp(a));
+        check_stable_argsort(&reversed, &indices);
+    }
+}
+
+#[test]
+fn sort_zip_panic_keeps_payloads() {
+    for len in SELECT_TEST_SIZES {
+        let original = patterns::random::<i32>(len);
+
+        for stable in [false, true] {
+            for panic_after in [0, len / 2, len * 4] {
+                let mut keys = original.clone();
+                let mut indices = (0..len).collect::<Vec<_>>();
+                let mut comp_count = 0;
+
+                let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+                    let compare = |a: &i32, b: &i32| {
+                        comp_count += 1;
+                        if comp_count > panic_after {
+                            panic!("compare");
+                        }
+                        a.cmp(b)
+                    };
+
+                    if stable {
+                        ipnsort::stable::sort_zip_by(&mut keys, &mut indices[..], compare);
+                    } else {
+                        ipnsort::sort_zip_by(&mut keys, &mut indices[..], compare);
+                    }
+                }));
+
+                // Whatever order the keys are left in, each is still next to its own payload.
+                assert!(indices.iter().zip(&keys).all(|(&i, k)| original[i] == *k));
+            }
+        }
+    }
+}
+
+#[test]
+fn sort_zip_len_mismatch() {
+    let mut keys = [3, 1, 2];
+    let mut short = [30, 10];
+    let mut exact = [30, 10, 20];
+
+    let res = panic::catch_unwind(AssertUnwindSafe(|| {
+        ipnsort::sort_zip(&mut keys, (&mut exact[..], &mut short[..]));
+    }));
+    assert!(res.is_err());
+
+    // Nothing is reordered if the lengths don't match.
+    assert_eq!(keys, [3, 1, 2]);
+    assert_eq!(exact, [30, 10, 20]);
+
+    let res = panic::catch_unwind(AssertUnwindSafe(|| {
+        ipnsort::stable::sort_zip(&mut keys, &mut short[..]);
+    }));
+    assert!(res.is_err());
+    assert_eq!(keys, [3, 1, 2]);
+
+    let mut keys: [i32; 0] = [];
+    let mut payload: [u8; 0] = [];
+    ipnsort::sort_zip(&mut keys, &mut payload[..]);
+}
+
+// --- sort_array ---
+
+/// Calls `$check::<N>()` for every `N` in the list.
+macro_rules! for_each_array_len {
+    ($check:ident, [$($n:literal),+ $(,)?]) => {
+        $($check::<$n>();)+
+    };
+}
+
+fn check_sort_array_zero_one_exhaustive<const N: usize>() {
+    // By the 0-1 principle a sorting network sorts all inputs if it sorts all inputs of zeros and
+    // ones.
+    for bits in 0u32..(1 << N) {
+        let mut v = [0u8; N];
+        for (i, val) in v.iter_mut().enumerate() {
+            *val = ((bits >> i) & 1) as u8;
+        }
+
+        ipnsort::sort_array(&mut v);
+
+        let num_zeros = N - bits.count_ones() as usize;
+        assert!(
+            v[..num_zeros].iter().all(|&val| val == 0),
+            "N: {N} bits: {bits:b}"
+        );
+        assert!(
+            v[num_zeros..].iter().all(|&val| val == 1),
+            "N: {N} bits: {bits:b}"
+        );
+    }
+}
+
+fn check_sort_array_zero_one_sampled<const N: usize>() {
+    // Too many inputs to check them all, so check random zero-one inputs instead. Each sample picks
+    // its own share of ones, which covers sparse and dense inputs alike.
+    const SAMPLES: usize = 100_000;
+
+    let values = patterns::random_uniform::<i32>(SAMPLES * (N + 1), 0..=(N as i32));
+    for sample in values.chunks_exact(N + 1) {
+        let (threshold, vals) = (sample[0], &sample[1..]);
+        let original: [u8; N] = std::array::from_fn(|i| (vals[i] < threshold) as u8);
+
+        let mut v = original;
+        ipnsort::sort_array(&mut v);
+
+        let num_zeros = N - original.iter().filter(|&&val| val == 1).count();
+        assert!(
+            v[..num_zeros].iter().all(|&val| val == 0),
+            "N: {N} original: {original:?}"
+        );
+        assert!(
+            v[num_zeros..].iter().all(|&val| val == 1),
+            "N: {N} original: {original:?}"
+        );
+    }
+}
+
+fn check_sort_array_patterns<const N: usize>() {
+    for pattern_fn in dedup_pattern_fns() {
+        let original: [i32; N] = pattern_fn(N).try_into().unwrap();
+
+        let mut expected = original;
+        expected.sort();
+
+        let mut v = original;
+        ipnsort::sort_array(&mut v);
+        assert_eq!(v, expected);
+
+        let mut v = original.map(|val| FFIString::new(format!("{:010}", val.saturating_abs())));
+        ipnsort::sort_array_by(&mut v, |a, b| b.cmp(a));
+        let mut expected =
+            original.map(|val| FFIString::new(format!("{:010}", val.saturating_abs())));
+        expected.sort_by(|a, b| b.cmp(a));
+        assert_eq!(v, expected);
+    }
+}
+
+#[test]
+fn sort_array_zero_one_exhaustive() {
+    for_each_array_len!(
+        check_sort_array_zero_one_exhaustive,
+        [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20]
+    );
+}
+
+#[test]
+fn sort_array_zero_one_sampled() {
+    for_each_array_len!(
+        check_sort_array_zero_one_sampled,
+        [21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32]
+    );
+}
+
+#[test]
+fn sort_array_patterns() {
+    for_each_array_len!(
+        check_sort_array_patterns,
+        [
+            1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24,
+            25, 26, 27, 28, 29, 30, 31, 32, 33, 40, 100
+        ]
+    );
+}
+
+#[test]
+fn sort_array_violate_ord_retain_original_set() {
+    // Every comparison result is driven by a random sequence, which violates Ord.
+    let random_orderings = patterns::random_uniform::<i32>(5_000, 0..3);
+    let mut random_idx = 0;
+
+    let original: [i32; 32] = patterns::random(32).try_into().unwrap();
+    let mut v = original;
+
+    ipnsort::sort_array_by(&mut v, |_, _| {
+        random_idx = (random_idx + 1) % random_orderings.len();
+        [Ordering::Less, Ordering::Equal, Ordering::Greater][random_orderings[random_idx] as usize]
+    });
+
+    let mut expected = original;
+    expected.sort();
+    v.sort();
+    assert_eq!(v, expected);
+}
+
+// --- sort_appended ---
+
+fn check_sort_appended<T: Ord + Clone + std::fmt::Debug>(original: &[T], sorted_prefix_len: usize) {
+    let mut expected = original.to_vec();
+    expected.sort();
+
+    let mut v = original.to_vec();
+    ipnsort::sort_appended(&mut v, sorted_prefix_len);
+    assert_eq!(v, expected, "sorted_prefix_len: {sorted_prefix_len}");
+}
+
+#[test]
+fn sort_appended_random_sorted() {
+    for sorted_percent in [0.0, 50.0, 95.0, 99.0, 100.0] {
+        for len in SELECT_TEST_SIZES {
+            // Same rounding as `patterns::random_sorted`.
+            let original = patterns::random_sorted::<i32>(len, sorted_percent);
+            let sorted_prefix_len = ((len as f64) * (sorted_percent / 100.0)).round() as usize;
+
+            check_sort_appended(&original, sorted_prefix_len);
+
+            let original_string = patterns::random_sorted::<FFIString>(len, sorted_percent);
+            check_sort_appended(&original_string, sorted_prefix_len);
+        }
+    }
+}
+
+#[test]
+fn sort_appended_shorter_prefix() {
+    // Any sorted prefix can be specified, not just the longest one.
+    for len in SELECT_TEST_SIZES {
+        let original = patterns::ascending::<i32>(len);
+
+        for sorted_prefix_len in [0, 1, len / 2, len.saturating_sub(1), len] {
+            check_sort_appended(&original, sorted_prefix_len);
+        }
+    }
+}
+
+#[test]
+fn sort_appended_tail_before_and_after_prefix() {
+    for len in SELECT_TEST_SIZES {
+        let sorted_prefix_len = len / 2;
+
+        let mut original = patterns::random_uniform::<i32>(len, 0..1_000);
+        original[..sorted_prefix_len].sort();
+        for val in &mut original[sorted_prefix_len..] {
+            *val += 1_000;
+        }
+        check_sort_appended(&original, sorted_prefix_len);
+
+        for val in &mut original[sorted_prefix_len..] {
+            *val -= 2_000;
+        }
+        check_sort_appended(&original, sorted_prefix_len);
+    }
+}
+
+#[test]
+fn sort_appended_rotation_merge() {
+    // The merge buffer can't hold half of a slice of large elements, which requires rotations.
+    let len = 20_000;
+    let original = patterns::random_sorted(len, 50.0)
+        .into_iter()
+        .map(FFIOneKiloByte::new)
+        .collect::<Vec<_>>();
+
+    check_sort_appended(&original, len / 2);
+}
+
+#[test]
+fn sort_appended_by_reversed() {
+    for len in SELECT_TEST_SIZES {
+        let sorted_prefix_len = len - len / 10;
+
+        let mut original = patterns::random::<i32>(len);
+        original[..sorted_prefix_len].sort_by(|a, b| b.cmp(a));
+
+        let mut expected = original.clone();
+        expected.sort_by(|a, b| b.cmp(a));
+
+        let mut v = original.clone();
+        ipnsort::sort_appended_by(&mut v, sorted_prefix_len, |a, b| b.cmp(a));
+        assert_eq!(v, expected);
+    }
+}
+
+#[test]
+#[should_panic]
+fn sort_appended_prefix_too_long() {
+    let mut v = [1, 2, 3];
+    ipnsort::sort_appended(&mut v, 4);
+}
+
+// --- sort_floats ---
+
+fn check_sort_floats_f64(original: &[f64]) {
+    let mut expected = original.to_vec();
+    expected.sort_by(|a, b| a.total_cmp(b));
+
+    let mut v = original.to_vec();
+    ipnsort::sort_floats(&mut v);
+
+    // Compare the bits, to distinguish signed zeros and NaN payloads.
+    let to_bits = |v: &[f64]| v.iter().map(|x| x.to_bits()).collect::<Vec<_>>();
+    assert_eq!(to_bits(&v), to_bits(&expected), "len: {}", original.len());
+}
+
+fn check_sort_floats_f32(original: &[f32]) {
+    let mut expected = original.to_vec();
+    expected.sort_by(|a, b| a.total_cmp(b));
+
+    let mut v = original.to_vec();
+    ipnsort::sort_floats(&mut v);
+
+    let to_bits = |v: &[f32]| v.iter().map(|x| x.to_bits()).collect::<Vec<_>>();
+    assert_eq!(to_bits(&v), to_bits(&expected), "len: {}", original.len());
+}
+
+fn special_floats_f64() -> Vec<f64> {
+    vec![
+        0.0,
+        -0.0,
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+        f64::MAX,
+        f64::MIN,
+        f64::MIN_POSITIVE,
+        -f64::MIN_POSITIVE,
+        f64::EPSILON,
+        // Subnormals.
+        f64::from_bits(1),
+        -f64::from_bits(1),
+        f64::from_bits(0x000f_ffff_ffff_ffff),
+        // Quiet NaNs with different payloads.
+        f64::NAN,
+        -f64::NAN,
+        f64::from_bits(0x7ff8_0000_0000_0001),
+        f64::from_bits(0xfff8_0000_0000_0001),
+        f64::from_bits(0x7fff_ffff_ffff_ffff),
+        f64::from_bits(0xffff_ffff_ffff_ffff),
+        // Signaling NaNs with different payloads.
+        f64::from_bits(0x7ff0_0000_0000_0001),
+        f64::from_bits(0xfff0_0000_0000_0001),
+        f64::from_bits(0x7ff4_0000_0000_0000),
+        f64::from_bits(0xfff4_0000_0000_0000),
+    ]
+}
+
+fn special_floats_f32() -> Vec<f32> {
+    vec![
+        0.0,
+        -0.0,
+        f32::INFINITY,
+        f32::NEG_INFINITY,
+        f32::MAX,
+        f32::MIN,
+        f32::MIN_POSITIVE,
+        -f32::MIN_POSITIVE,
+        f32::from_bits(1),
+        -f32::from_bits(1),
+        f32::NAN,
+        -f32::NAN,
+        f32::from_bits(0x7fc0_0001),
+        f32::from_bits(0xffc0_0001),
+        f32::from_bits(0x7f80_0001),
+        f32::from_bits(0xff80_0001),
+    ]
+}
+
+#[test]
+fn sort_floats_patterns() {
+    for pattern_fn in dedup_pattern_fns() {
+        for len in SELECT_TEST_SIZES {
+            let original = pattern_fn(len);
+
+            check_sort_floats_f64(&original.iter().map(|&x| x as f64 / 7.0).collect::<Vec<_>>());
+            check_sort_floats_f32(&original.iter().map(|&x| x as f32 / 7.0).collect::<Vec<_>>());
+        }
+    }
+}
+
+#[test]
+fn sort_floats_special_values() {
+    let special_f64 = special_floats_f64();
+    let special_f32 = special_floats_f32();
+
+    for len in SELECT_TEST_SIZES {
+        // Mix the special values into random values, with many duplicates.
+        let original = patterns::random::<i32>(len);
+
+        let v = original
+            .iter()
+            .map(|&x| match special_f64.get(x.unsigned_abs() as usize % 64) {
+                Some(&special) => special,
+                None => x as f64,
+            })
+            .collect::<Vec<_>>();
+        check_sort_floats_f64(&v);
+
+        let v = original
+            .iter()
+            .map(|&x| match special_f32.get(x.unsigned_abs() as usize % 64) {
+                Some(&special) => special,
+                None => x as f32,
+            })
+            .collect::<Vec<_>>();
+        check_sort_floats_f32(&v);
+    }
+}
+
+#[test]
+fn sort_floats_order() {
+    let mut v = [
+        f64::NAN,
+        1.0,
+        -0.0,
+        -f64::NAN,
+        f64::NEG_INFINITY,
+        0.0,
+        -1.0,
+        f64::INFINITY,
+    ];
+    ipnsort::sort_floats(&mut v);
+
+    assert!(v[0].is_nan() && v[0].is_sign_negative());
+    assert_eq!(
+        v[1..7],
+        [f64::NEG_INFINITY, -1.0, -0.0, 0.0, 1.0, f64::INFINITY]
+    );
+    assert!(v[3].is_sign_negative() && v[4].is_sign_positive());
+    assert!(v[7].is_nan() && v[7].is_sign_positive());
+
+    let mut v: [f32; 0] = [];
+    ipnsort::sort_floats(&mut v);
+}
+
+// --- radix ---
+
+#[cfg(feature = "radix")]
+mod radix {
+    use super::*;
+
+    // Covers the comparison sort cutoff, radix buckets that are sorted with the comparison sort
+    // and the 16-bit counting sort.
+    const RADIX_TEST_SIZES: [usize; 9] = [0, 1, 2, 127, 128, 257, 1_000, 10_000, 70_000];
+
+    fn radix_pattern_fns() -> Vec<fn(usize) -> Vec<i32>> {
+        vec![
+            patterns::random,
+            |size| patterns::random_uniform(size, 0..4),
+            |size| patterns::random_uniform(size, -300..300),
+            |size| patterns::random_uniform(size, 0..=u16::MAX as i32),
+            |size| patterns::random_uniform(size, i32::MIN..=i32::MAX),
+            |size| patterns::random_sorted(size, 95.0),
+            patterns::all_equal,
+            patterns::ascending,
+            patterns::descending,
+            |size| patterns::saw_mixed(size, ((size as f64).log2().round()) as usize),
+            patterns::pipe_organ,
+        ]
+    }
+
+    fn check_radix_sort<T: Ord + Copy + std::fmt::Debug>(original: &[i32], map: fn(i32) -> T) {
+        let original = original.iter().map(|&val| map(val)).collect::<Vec<_>>();
+
+        let mut expected = original.clone();
+        expected.sort();
+
+        let mut v = original;
+        ipnsort::sort(&mut v);
+        assert_eq!(v, expected);
+    }
+
+    #[test]
+    fn radix_sort_patterns() {
+        for pattern_fn in radix_pattern_fns() {
+            for len in RADIX_TEST_SIZES {
+                let original = pattern_fn(len);
+
+                check_radix_sort(&original, |val| val as u8);
+                check_radix_sort(&original, |val| val as i8);
+                check_radix_sort(&original, |val| val as u16);
+                check_radix_sort(&original, |val| val as i16);
+                check_radix_sort(&original, |val| val as u32);
+                check_radix_sort(&original, |val| val);
+                check_radix_sort(&original, |val| val as i64 as u64);
+                check_radix_sort(&original, |val| (val as i64) << 32 | val as u32 as i64);
+                check_radix_sort(&original, |val| val as usize);
+                check_radix_sort(&original, |val| val as isize);
+            }
+        }
+    }
+
+    #[test]
+    fn radix_sort_extremes() {
+        // Only the minimum and maximum values, which differ in every digit.
+        for len in RADIX_TEST_SIZES {
+            let original = patterns::random_uniform(len, 0..2);
+
+            check_radix_sort(&original, |val| if val == 0 { u8::MIN } else { u8::MAX });
+            check_radix_sort(&original, |val| if val == 0 { i16::MIN } else { i16::MAX });
+            check_radix_sort(&original, |val| if val == 0 { i32::MIN } else { i32::MAX });
+            check_radix_sort(&original, |val| if val == 0 { u64::MIN } else { u64::MAX });
+            check_radix_sort(&original, |val| if val == 0 { i64::MIN } else { i64::MAX });
+        }
+    }
+
+    #[test]
+    fn radix_sort_non_integer() {
+        // Types without a radix sort use the comparison sort.
+        for len in RADIX_TEST_SIZES {
+            let original = patterns::random(len);
+            check_radix_sort(&original, |val| (val as u8, val));
+            check_radix_sort(&original, |val| val as u128);
+        }
+    }
+}
+
+// --- simd ---
+
+#[cfg(feature = "simd")]
+mod simd {
+    use super::*;
+
+    // Covers the scalar fallback below two vectors and all remainder lengths after the vector loop,
+    // for both 4 and 8 lanes.
+    fn simd_test_sizes() -> impl Iterator<Item = usize> {
+        (0..=40).chain([63, 64, 65, 100, 1_000, 10_000])
+    }
+
+    fn simd_pattern_fns() -> Vec<fn(usize) -> Vec<i32>> {
+        vec![
+            patterns::random,
+            |size| patterns::random_uniform(size, 0..4),
+            |size| patterns::random_sorted(size, 95.0),
+            patterns::all_equal,
+            patterns::ascending,
+            patterns::descending,
+            patterns::pipe_organ,
+        ]
+    }
+
+    fn check_simd_partition<T: ipnsort::simd::SimdPartition + PartialOrd + std::fmt::Debug>(
+        original: &[i32],
+        map: fn(i32) -> T,
+    ) {
+        let original = original.iter().map(|&val| map(val)).collect::<Vec<_>>();
+
+        // Pivots from the input, as quicksort picks them, and one below and above all elements.
+        let mut pivots = original
+            .iter()
+            .step_by(original.len() / 4 + 1)
+            .copied()
+            .collect::<Vec<_>>();
+        pivots.extend([map(i32::MIN), map(i32::MAX)]);
+
+        for pivot in pivots {
+            let mut v = original.clone();
+            let lt_count = ipnsort::simd::partition(&mut v, &pivot);
+
+            assert!(v[..lt_count].iter().all(|elem| *elem < pivot));
+            assert!(v[lt_count..]
+                .iter()
+                .all(|elem| elem.partial_cmp(&pivot) != Some(Ordering::Less)));
+
+            let mut sorted = v.clone();
+            sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            let mut expected = original.clone();
+            expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            assert_eq!(sorted, expected);
+        }
+    }
+
+    #[test]
+    fn simd_partition_patterns() {
+        for pattern_fn in simd_pattern_fns() {
+            for len in simd_test_sizes() {
+                let original = pattern_fn(len);
+
+                check_simd_partition(&original, |val| val);
+                check_simd_partition(&original, |val| val as u32);
+                check_simd_partition(&original, |val| val as i64 as u64);
+                check_simd_partition(&original, |val| val as f32);
+            }
+        }
+    }
+
+    #[test]
+    fn simd_partition_nan() {
+        for len in simd_test_sizes() {
+            let original = patterns::random_uniform::<i32>(len, 0..10)
+                .into_iter()
+                .map(|val| if val == 0 { f32::NAN } else { val as f32 })
+                .collect::<Vec<_>>();
+
+            let mut v = original.clone();
+            let lt_count = ipnsort::simd::partition(&mut v, &5.0);
+
+            let expected_lt_count = original.iter().filter(|&&val| val < 5.0).count();
+            assert_eq!(lt_count, expected_lt_count);
+            assert!(v[..lt_count].iter().all(|&val| val < 5.0));
+            assert!(v[lt_count..].iter().all(|&val| val.is_nan() || val >= 5.0));
+        }
+    }
+
+    #[test]
+    fn simd_sort() {
+        for pattern_fn in simd_pattern_fns() {
+            for len in simd_test_sizes() {
+                let original = pattern_fn(len);
+
+                let mut expected = original.clone();
+                expected.sort();
+                let mut v = original.clone();
+                ipnsort::sort(&mut v);
+                assert_eq!(v, expected);
+
+                let mut v = original.iter().map(|&val| val as u64).collect::<Vec<_>>();
+                ipnsort::sort(&mut v);
+                assert!(v.windows(2).all(|w| w[0] <= w[1]));
+
+                // Comparators other than the natural order must not use the SIMD partition.
+                let mut expected = original.clone();
+                expected.sort_by(|a, b| b.cmp(a));
+                let mut v = original;
+                ipnsort::sort_by(&mut v, |a, b| b.cmp(a));
+                assert_eq!(v, expected);
+            }
+        }
+    }
+}
+
+// --- check_ord ---
+
+#[cfg(feature = "check_ord")]
+mod check_ord {
+    use super::*;
+
+    /// Returns the panic message of `f`, which has to panic.
+    fn panic_message(f: impl FnOnce()) -> String {
+        let err = panic::catch_unwind(AssertUnwindSafe(f)).unwrap_err();
+        err.downcast_ref::<String>()
+            .cloned()
+            .or_else(|| err.downcast_ref::<&str>().map(|msg| msg.to_string()))
+            .unwrap()
+    }
+
+    #[test]
+    fn check_ord_valid_order() {
+        for pattern_fn in select_pattern_fns() {
+            for len in [1, 2, 20, 21, 100, 1_000, 10_000] {
+                let original = pattern_fn(len);
+
+                let mut expected = original.clone();
+                expected.sort();
+
+                let mut v = original.clone();
+                ipnsort::sort(&mut v);
+                assert_eq!(v, expected);
+
+                let mut v = original.clone();
+                ipnsort::stable::sort(&mut v);
+                assert_eq!(v, expected);
+
+                // Stability is kept by sorting the indices stably.
+                let mut v = original.iter().map(|&x| (x % 16, x)).collect::<Vec<_>>();
+                let mut expected = v.clone();
+                expected.sort_by_key(|&(key, _)| key);
+                ipnsort::stable::sort_by_key(&mut v, |&(key, _)| key);
+                assert_eq!(v, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn check_ord_antisymmetry() {
+        for len in [20, 1_000] {
+            let original = patterns::random::<i32>(len);
+            let mut v = original.clone();
+
+            let msg = panic_message(|| ipnsort::sort_by(&mut v, |_, _| Ordering::Less));
+            assert!(
+                msg.starts_with("Ord violation: ") && msg.contains("are both true"),
+                "{msg}"
+            );
+
+            // Nothing gets moved before the whole order has been checked.
+            assert_eq!(v, original);
+        }
+    }
+
+    #[test]
+    fn check_ord_transitivity() {
+        // Rock, paper, scissors, every element is less than the next one, modulo 3.
+        let rps_cmp = |a: &i32, b: &i32| {
+            if a == b {
+                Ordering::Equal
+            } else if (a + 1) % 3 == *b {
+                Ordering::Less
+            } else {
+                Ordering::Greater
+            }
+        };
+
+        let original = patterns::random_uniform(1_000, 0..3);
+
+        let mut v = original.clone();
+        let msg = panic_message(|| ipnsort::sort_by(&mut v, rps_cmp));
+        assert!(msg.contains("but is_less"), "{msg}");
+
+        // The reported indices point at elements that really are intransitive.
+        let indices = msg
+            .split(|c: char| !c.is_ascii_digit())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse::<usize>().unwrap())
+            .collect::<Vec<_>>();
+        let (a, b, c) = (indices[0], indices[1], indices[3]);
+        assert_eq!(rps_cmp(&original[a], &original[b]), Ordering::Less);
+        assert_eq!(rps_cmp(&original[b], &original[c]), Ordering::Less);
+        assert_ne!(rps_cmp(&original[a], &original[c]), Ordering::Less);
+
+        let mut v = original;
+        let msg = panic_message(|| ipnsort::stable::sort_by(&mut v, rps_cmp));
+        // The small-sort can detect the violation too, before it's sampled.
+        assert!(msg.starts_with("Ord violation"), "{msg}");
+    }
+}
+
+// --- par_sort ---
+
+#[cfg(feature = "parallel")]
+mod parallel {
+    use super::*;
+
+    use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+    // Covers the sequential path, the parallel path and the boundary between them.
+    const PAR_TEST_SIZES: [usize; 9] = [
+        1, 2, 20, 4_096, 4_097, 10_000, 100_000, 1_000_000, 2_000_000,
+    ];
+
+    #[test]
+    fn par_sort_patterns() {
+        for pattern_fn in select_pattern_fns() {
+            for len in PAR_TEST_SIZES {
+                let original = pattern_fn(len);
+
+                let mut expected = original.clone();
+                expected.sort();
+
+                let mut v = original.clone();
+                ipnsort::par_sort(&mut v);
+                assert_eq!(v, expected);
+
+                let mut v = original
+                    .iter()
+                    .map(|x| *x as i64 as u64)
+                    .collect::<Vec<_>>();
+                ipnsort::par_sort(&mut v);
+                assert!(v.windows(2).all(|w| w[0] <= w[1]));
+            }
+        }
+    }
+
+    #[test]
+    fn par_sort_by_reversed() {
+        for len in PAR_TEST_SIZES {
+            let original = patterns::random::<i32>(len);
+
+            let mut expected = original.clone();
+            expected.sort_by(|a, b| b.cmp(a));
+
+            let mut v = original.clone();
+            ipnsort::par_sort_by(&mut v, |a, b| b.cmp(a));
+            assert_eq!(v, expected);
+        }
+    }
+
+    #[test]
+    fn par_sort_string() {
+        for len in PAR_TEST_SIZES {
+            let original = patterns::random_zipf::<i32>(len, 1.0)
+                .into_iter()
+                .map(|val| format!("{:010}", val.saturating_abs()))
+                .collect::<Vec<_>>();
+
+            let mut expected = original.clone();
+            expected.sort();
+
+            let mut v = original.clone();
+            ipnsort::par_sort(&mut v);
+            assert_eq!(v, expected);
+        }
+    }
+
+    #[test]
+    fn par_sort_empty_and_zst() {
+        let mut v: Vec<i32> = Vec::new();
+        ipnsort::par_sort(&mut v);
+
+        let mut v = vec![(); 100_000];
+        ipnsort::par_sort(&mut v);
+        assert_eq!(v.len(), 100_000);
+    }
+
+    #[test]
+    fn par_sort_panic_retain_original_set() {
+        for len in PAR_TEST_SIZES {
+            let original = patterns::random::<i32>(len)
+                .into_iter()
+                .map(|val| format!("{:010}", val.saturating_abs()))
+                .collect::<Vec<_>>();
+            let sum_before: i64 = original.iter().map(|x| x.parse::<i64>().unwrap()).sum();
+
+            // Panic somewhere in the middle of the sort, possibly on multiple threads.
+            let panic_threshold = len / 2;
+            let comp_count = AtomicUsize::new(0);
+
+            let mut v = original.clone();
+            let res = panic::catch_unwind(AssertUnwindSafe(|| {
+                ipnsort::par_sort_by(&mut v, |a, b| {
+                    if comp_count.fetch_add(1, AtomicOrdering::Relaxed) >= panic_threshold {
+                        panic!();
+                    }
+
+                    a.cmp(b)
+                });
+            }));
+
+            if len >= 20 {
+                assert!(res.is_err());
+            }
+
+            let sum_after: i64 = v.iter().map(|x| x.parse::<i64>().unwrap()).sum();
+            assert_eq!(sum_before, sum_after);
+        }
+    }
+
+    #[test]
+    fn par_sort_violate_ord_retain_original_set() {
+        // Every comparison result is driven by a random sequence, which violates Ord.
+        let random_orderings = patterns::random_uniform::<i32>(5_000, 0..3);
+
+        for len in PAR_TEST_SIZES {
+            let original = patterns::random::<i32>(len);
+            let sum_before: i64 = original.iter().map(|x| *x as i64).sum();
+
+            let mut v = original.clone();
+            let random_idx = AtomicUsize::new(0);
+
+            // It's ok to panic on Ord violation or to complete.
+            // In both cases the original elements must still be present.
+            let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+                ipnsort::par_sort_by(&mut v, |_, _| {
+                    let idx = random_idx.fetch_add(1, AtomicOrdering::Relaxed);
+                    [Ordering::Less, Ordering::Equal, Ordering::Greater]
+                        [random_orderings[idx % random_orderings.len()] as usize]
+                });
+            }));
+
+            let sum_after: i64 = v.iter().map(|x| *x as i64).sum();
+            assert_eq!(sum_before, sum_after);
+        }
+    }
+}
+
+// --- instrument ---
+
+#[cfg(feature = "instrument")]
+mod instrument {
+    use super::*;
+
+    use ipnsort::instrument::{observe, SortObserver, SortStats};
+
+    fn sort_stats(v: &mut [i32]) -> SortStats {
+        let mut stats = SortStats::default();
+        observe(&mut stats, || ipnsort::sort(v));
+        assert!(v.windows(2).all(|w| w[0] <= w[1]));
+
+        stats
+    }
+
+    #[test]
+    fn stats_random() {
+        let len = 100_000;
+        let stats = sort_stats(&mut patterns::random(len));
+
+        assert_eq!(stats.streaks, 1);
+        assert_eq!(stats.full_streaks, 0);
+        assert!(stats.quicksort_calls > 0);
+        assert!(stats.partitions > 0);
+        assert!(stats.small_sorts > 0);
+        assert_eq!(stats.heapsort_fallbacks, 0);
+
+        // Every element ends up as pivot or in a small-sort exactly once.
+        assert!(stats.small_sorted_elements < len);
+        assert_eq!(stats.small_sorted_elements + stats.partitions, len);
+
+        // The depth is limited by the number of allowed imbalanced partitions.
+        assert!(stats.max_depth <= 2 * len.ilog2());
+
+        let imbalance = stats.mean_partition_imbalance();
+        assert!((1.0..2.0).contains(&imbalance), "{imbalance}");
+    }
+
+    #[test]
+    fn stats_presorted() {
+        for pattern_fn in [patterns::ascending, patterns::descending] {
+            let stats = sort_stats(&mut pattern_fn(10_000));
+
+            assert_eq!(stats.streaks, 1);
+            assert_eq!(stats.full_streaks, 1);
+            assert_eq!(stats.quicksort_calls, 0);
+            assert_eq!(stats.partitions, 0);
+        }
+    }
+
+    #[test]
+    fn stats_equal_partition() {
+        let stats = sort_stats(&mut patterns::random_uniform(10_000, 0..4));
+        assert!(stats.equal_partitions > 0);
+        assert!(stats.equal_partitions <= stats.partitions);
+    }
+
+    #[test]
+    fn stats_heapsort_fallback() {
+        // Comparing everything as less leads to maximally imbalanced partitions. The second
+        // comparison is greater, to stop the streak detection from treating the slice as
+        // descending.
+        let mut v = patterns::random::<i32>(10_000);
+        let mut comp_count = 0;
+        let mut stats = SortStats::default();
+        observe(&mut stats, || {
+            ipnsort::sort_by(&mut v, |_, _| {
+                comp_count += 1;
+                if comp_count == 2 {
+                    Ordering::Greater
+                } else {
+                    Ordering::Less
+                }
+            })
+        });
+
+        assert!(stats.heapsort_fallbacks > 0);
+    }
+
+    #[test]
+    fn stats_select_nth_unstable() {
+        let mut v = patterns::random::<i32>(10_000);
+        let mut stats = SortStats::default();
+        observe(&mut stats, || {
+            ipnsort::select_nth_unstable(&mut v, 5_000);
+        });
+
+        assert!(stats.partitions > 0);
+        assert_eq!(stats.quicksort_calls, 0);
+    }
+
+    #[test]
+    fn observer_removed_after_observe() {
+        let mut stats = SortStats::default();
+        observe(&mut stats, || {
+            ipnsort::sort(&mut patterns::random::<i32>(1_000))
+        });
+        let expected = stats.clone();
+
+        ipnsort::sort(&mut patterns::random::<i32>(1_000));
+        assert_eq!(stats, expected);
+    }
+
+    #[test]
+    fn observer_removed_after_panic() {
+        let mut stats = SortStats::default();
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+            observe(&mut stats, || {
+                ipnsort::sort_by(&mut patterns::random::<i32>(1_000), |_, _| panic!("test"));
+            })
+        }));
+        let expected = stats.clone();
+
+        ipnsort::sort(&mut patterns::random::<i32>(1_000));
+        assert_eq!(stats, expected);
+    }
+
+    #[test]
+    fn custom_observer() {
+        #[derive(Default)]
+        struct PivotLog {
+            pivots: Vec<(usize, usize)>,
+            partitions: Vec<(usize, usize)>,
+        }
+
+        impl SortObserver for PivotLog {
+            fn choose_pivot(&mut self, len: usize, pivot_pos: usize) {
+                self.pivots.push((len, pivot_pos));
+            }
+
+            fn partition(&mut self, len: usize, num_lt: usize) {
+                self.partitions.push((len, num_lt));
+
+                // Sorting inside the observer must not report to it again.
+                let mut v = patterns::random::<i32>(100);
+                ipnsort::sort(&mut v);
+            }
+        }
+
+        let mut log = PivotLog::default();
+        observe(&mut log, || {
+            ipnsort::sort(&mut patterns::random::<i32>(10_000))
+        });
+
+        assert!(!log.pivots.is_empty());
+        assert_eq!(log.pivots.len(), log.partitions.len());
+        for ((pivot_len, pivot_pos), (partition_len, num_lt)) in
+            log.pivots.iter().zip(&log.partitions)
+        {
+            assert!(pivot_pos < pivot_len);
+            assert_eq!(pivot_len, partition_len);
+            assert!(num_lt < partition_len);
+        }
+    }
+}
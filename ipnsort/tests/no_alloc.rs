@@ -0,0 +1,220 @@
+//! Checks that the functions documented as not allocating don't allocate, and how much the stable
+//! sort allocates. The global allocator is shared by the whole test binary, so this lives in its
+//! own integration test.
+//!
+//! With the `check_ord` feature all sorts allocate, so there is nothing to check.
+#![cfg(not(feature = "check_ord"))]
+
+use std::cmp::Ordering;
+use std::mem::MaybeUninit;
+
+use sort_test_tools::counting_alloc::{self, CountingAllocator};
+use sort_test_tools::ffi_types::FFIString;
+use sort_test_tools::{instantiate_alloc_tests, patterns, Sort};
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Returns how often `f` allocated.
+fn count_allocations(f: impl FnOnce()) -> usize {
+    counting_alloc::track_allocations(f).count
+}
+
+const TEST_SIZES: [usize; 9] = [0, 1, 2, 20, 21, 100, 1_000, 10_000, 100_000];
+
+fn check_no_alloc<T: Ord + Clone + std::fmt::Debug>(original: &[T], buf_len: usize) {
+    let mut v = original.to_vec();
+    let mut buf = Vec::<T>::with_capacity(buf_len);
+    let buf = &mut buf.spare_capacity_mut()[..buf_len];
+
+    let alloc_count = count_allocations(|| ipnsort::stable::sort_with_buffer(&mut v, buf));
+    assert_eq!(
+        alloc_count,
+        0,
+        "len: {} buf_len: {}",
+        original.len(),
+        buf_len
+    );
+
+    let mut expected = original.to_vec();
+    expected.sort();
+    assert_eq!(v, expected);
+}
+
+#[test]
+fn sort_with_buffer_no_alloc() {
+    for len in TEST_SIZES {
+        let original = patterns::random::<i32>(len);
+
+        for buf_len in [0, 1, 35, len / 8, ipnsort::stable::buffer_len::<i32>(len)] {
+            check_no_alloc(&original, buf_len);
+        }
+    }
+}
+
+#[test]
+fn sort_with_buffer_no_alloc_ffi_string() {
+    for len in TEST_SIZES {
+        let original = patterns::random::<FFIString>(len);
+
+        for buf_len in [0, len / 8, ipnsort::stable::buffer_len::<FFIString>(len)] {
+            check_no_alloc(&original, buf_len);
+        }
+    }
+}
+
+#[test]
+fn sort_by_with_buffer_reused_buffer() {
+    let mut buf = [MaybeUninit::<u64>::uninit(); 64];
+
+    for len in TEST_SIZES {
+        let original = patterns::random::<u64>(len);
+        let mut v = original.clone();
+
+        let alloc_count = count_allocations(|| {
+            ipnsort::stable::sort_by_with_buffer(&mut v, &mut buf, |a, b| b.cmp(a));
+        });
+        assert_eq!(alloc_count, 0);
+
+        let mut expected = original;
+        expected.sort_by(|a, b| b.cmp(a));
+        assert_eq!(v, expected);
+    }
+}
+
+#[test]
+fn unstable_sort_no_alloc() {
+    for len in TEST_SIZES {
+        let original = patterns::random::<i32>(len);
+
+        let mut v = original.clone();
+        assert_eq!(count_allocations(|| ipnsort::sort(&mut v)), 0);
+
+        let mut v = original.clone();
+        assert_eq!(
+            count_allocations(|| ipnsort::sort_by(&mut v, |a, b| b.cmp(a))),
+            0
+        );
+
+        let mut v = original.clone();
+        assert_eq!(
+            count_allocations(|| {
+                ipnsort::sort_dedup(&mut v);
+            }),
+            0
+        );
+
+        let mut v = original.iter().map(|&x| x as f64).collect::<Vec<_>>();
+        assert_eq!(count_allocations(|| ipnsort::sort_floats(&mut v)), 0);
+
+        let mut v = original.clone();
+        assert_eq!(
+            count_allocations(|| {
+                ipnsort::partition3(&mut v, &0);
+            }),
+            0
+        );
+
+        let mut perm = ipnsort::argsort(&original);
+        let mut v = original.clone();
+        assert_eq!(
+            count_allocations(|| ipnsort::apply_permutation_in_place(&mut v, &mut perm)),
+            0
+        );
+
+        if len > 0 {
+            let mut v = original;
+            let alloc_count = count_allocations(|| {
+                ipnsort::select_nth_unstable(&mut v, len / 2);
+                ipnsort::partial_sort(&mut v, len / 2);
+            });
+            assert_eq!(alloc_count, 0);
+        }
+    }
+}
+
+#[test]
+fn sort_zip_no_alloc() {
+    for len in TEST_SIZES {
+        let original = patterns::random_uniform::<i32>(len, 0..100);
+
+        let mut keys = original.clone();
+        let mut values = patterns::random::<u64>(len);
+        assert_eq!(
+            count_allocations(|| ipnsort::sort_zip(&mut keys, &mut values[..])),
+            0
+        );
+
+        let mut keys = original;
+        assert_eq!(
+            count_allocations(|| {
+                ipnsort::stable::sort_zip_by(&mut keys, &mut values[..], |a, b| b.cmp(a))
+            }),
+            0
+        );
+    }
+}
+
+#[test]
+fn count_allocations_detects_stable_sort() {
+    // The regular stable sort allocates its scratch buffer, make sure that is seen.
+    let mut v = patterns::random::<i32>(1_000);
+    assert!(count_allocations(|| ipnsort::stable::sort(&mut v)) > 0);
+}
+
+mod unstable {
+    use super::*;
+
+    struct SortImpl {}
+
+    impl Sort for SortImpl {
+        fn name() -> String {
+            "rust_ipnsort_unstable".into()
+        }
+
+        fn sort<T>(arr: &mut [T])
+        where
+            T: Ord,
+        {
+            ipnsort::sort(arr);
+        }
+
+        fn sort_by<T, F>(arr: &mut [T], compare: F)
+        where
+            F: FnMut(&T, &T) -> Ordering,
+        {
+            ipnsort::sort_by(arr, compare);
+        }
+    }
+
+    instantiate_alloc_tests!(SortImpl, in_place);
+}
+
+mod stable {
+    use super::*;
+
+    struct SortImpl {}
+
+    impl Sort for SortImpl {
+        fn name() -> String {
+            "rust_ipnsort_stable".into()
+        }
+
+        fn sort<T>(arr: &mut [T])
+        where
+            T: Ord,
+        {
+            ipnsort::stable::sort(arr);
+        }
+
+        fn sort_by<T, F>(arr: &mut [T], compare: F)
+        where
+            F: FnMut(&T, &T) -> Ordering,
+        {
+            ipnsort::stable::sort_by(arr, compare);
+        }
+    }
+
+    // The scratch buffer holds half of the input, see `ipnsort::stable::buffer_len`.
+    instantiate_alloc_tests!(SortImpl, max_scratch_ratio = 0.5);
+}
@@ -0,0 +1,81 @@
+//! A global allocator that keeps track of the allocations done by each thread, to check how much
+//! memory a sort allocates. It only sees allocations if the test binary installs it:
+//!
+//! ```ignore
+//! #[global_allocator]
+//! static GLOBAL: CountingAllocator = CountingAllocator;
+//! ```
+//!
+//! Allocations are tracked per thread, so that tests running in parallel don't interfere. This
+//! also means that allocations done by threads the measured code spawns are not seen.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::hint::black_box;
+
+pub struct CountingAllocator;
+
+thread_local! {
+    static ALLOC_COUNT: Cell<usize> = const { Cell::new(0) };
+    // Signed, memory allocated by another thread may be freed by this one.
+    static LIVE_BYTES: Cell<isize> = const { Cell::new(0) };
+    static PEAK_BYTES: Cell<isize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+
+        if !ptr.is_null() {
+            ALLOC_COUNT.with(|count| count.set(count.get() + 1));
+            let live_bytes = LIVE_BYTES.with(|live| {
+                live.set(live.get() + layout.size() as isize);
+                live.get()
+            });
+            PEAK_BYTES.with(|peak| peak.set(peak.get().max(live_bytes)));
+        }
+
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        LIVE_BYTES.with(|live| live.set(live.get() - layout.size() as isize));
+    }
+}
+
+/// The allocations done by the code measured with [`track_allocations`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct AllocStats {
+    /// Number of allocations, a reallocation counts as one.
+    pub count: usize,
+    /// Largest number of bytes allocated at the same time, not counting the memory that was
+    /// already allocated before.
+    pub peak_bytes: usize,
+}
+
+/// Returns the allocations `f` did on the current thread.
+pub fn track_allocations(f: impl FnOnce()) -> AllocStats {
+    let count_before = ALLOC_COUNT.with(|count| count.get());
+    let live_bytes_before = LIVE_BYTES.with(|live| live.get());
+    let peak_bytes_before = PEAK_BYTES.with(|peak| peak.replace(live_bytes_before));
+
+    f();
+
+    let count = ALLOC_COUNT.with(|count| count.get()) - count_before;
+    let peak_bytes = PEAK_BYTES.with(|peak| peak.get()) - live_bytes_before;
+
+    // Keep the peak of an outer `track_allocations` call intact.
+    PEAK_BYTES.with(|peak| peak.set(peak.get().max(peak_bytes_before)));
+
+    AllocStats {
+        count,
+        peak_bytes: peak_bytes.max(0) as usize,
+    }
+}
+
+/// Returns `true` if [`CountingAllocator`] is the global allocator. Without it, the stats returned
+/// by [`track_allocations`] are always zero.
+pub fn is_installed() -> bool {
+    track_allocations(|| drop(black_box(Box::new(0u8)))).count != 0
+}
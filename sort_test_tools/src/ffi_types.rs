@@ -1,5 +1,6 @@
 use std::cmp::{Ord, Ordering, PartialOrd};
 use std::ffi::c_char;
+use std::mem::ManuallyDrop;
 use std::ptr;
 use std::str;
 
@@ -18,11 +19,11 @@ pub struct FFIString {
 
 impl FFIString {
     pub fn new(val: String) -> Self {
-        let (data, len, capacity) = val.into_raw_parts();
+        let mut val = ManuallyDrop::new(val);
         Self {
-            data: data as *mut c_char,
-            len,
-            capacity,
+            data: val.as_mut_ptr() as *mut c_char,
+            len: val.len(),
+            capacity: val.capacity(),
         }
     }
 
@@ -1,5 +1,3 @@
-#![feature(vec_into_raw_parts)]
-
 pub trait Sort {
     fn name() -> String;
 
@@ -10,8 +8,58 @@ pub trait Sort {
     fn sort_by<T, F>(arr: &mut [T], compare: F)
     where
         F: FnMut(&T, &T) -> std::cmp::Ordering;
+
+    fn sort_by_key<T, K, F>(arr: &mut [T], mut f: F)
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        Self::sort_by(arr, |a, b| f(a).cmp(&f(b)));
+    }
+
+    /// Implementations that compute each key only once should override this, by default it is the
+    /// same as `sort_by_key`.
+    fn sort_by_cached_key<T, K, F>(arr: &mut [T], f: F)
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        Self::sort_by_key(arr, f);
+    }
+
+    /// Implementations with a fallible sort should override this. By default the first error is
+    /// carried out of `sort_by` by unwinding, which relies on the sort being panic safe.
+    fn try_sort_by<T, E, F>(arr: &mut [T], mut compare: F) -> Result<(), E>
+    where
+        F: FnMut(&T, &T) -> Result<std::cmp::Ordering, E>,
+    {
+        use std::panic::{self, AssertUnwindSafe};
+
+        let mut error = None;
+        let res = panic::catch_unwind(AssertUnwindSafe(|| {
+            Self::sort_by(arr, |a, b| {
+                if error.is_none() {
+                    match compare(a, b) {
+                        Ok(ord) => return ord,
+                        Err(err) => error = Some(err),
+                    }
+                }
+
+                // Unlike `panic!` this doesn't run the panic hook, which would print a message.
+                panic::resume_unwind(Box::new(()));
+            });
+        }));
+
+        match (res, error) {
+            (_, Some(err)) => Err(err),
+            (Ok(()), None) => Ok(()),
+            (Err(payload), None) => panic::resume_unwind(payload),
+        }
+    }
 }
 
+pub mod counting_alloc;
 pub mod ffi_types;
+pub mod minimize;
 pub mod patterns;
 pub mod tests;
@@ -0,0 +1,257 @@
+//! Shrinking of failing test inputs, so that a failure found with a large input can be reproduced
+//! and debugged with a small one.
+
+use std::any;
+use std::env;
+use std::fmt::Debug;
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::Sort;
+
+/// Returns the smallest input derived from `input` for which `fails` still returns `true`.
+///
+/// The input is reduced in two alternating steps, until neither makes progress:
+///
+/// - Delta debugging removes chunks of elements, starting with halves and going down to single
+///   elements.
+/// - Value compression replaces all occurrences of a value with the next smaller value of the
+///   input, starting with the largest. This reduces the number of distinct values, which usually
+///   makes the remaining input much easier to follow.
+///
+/// `fails(input)` has to be `true`, and `fails` deterministic for the result to be meaningful.
+pub fn minimize<T: Ord + Clone>(input: &[T], mut fails: impl FnMut(&[T]) -> bool) -> Vec<T> {
+    let mut v = input.to_vec();
+
+    loop {
+        let removed = remove_chunks(&mut v, &mut fails);
+        let compressed = compress_values(&mut v, &mut fails);
+
+        if !removed && !compressed {
+            return v;
+        }
+    }
+}
+
+/// Minimizes `input`, for which `S` doesn't sort like the standard library, see [`minimize`].
+///
+/// Inputs for which `S` panics don't count as failing, they likely show a different bug.
+pub fn minimize_sort_mismatch<T: Ord + Clone, S: Sort>(input: &[T]) -> Vec<T> {
+    minimize(input, |candidate| {
+        let mut expected = candidate.to_vec();
+        expected.sort();
+
+        let mut v = candidate.to_vec();
+        let sort_res = panic::catch_unwind(AssertUnwindSafe(|| <S as Sort>::sort(&mut v)));
+
+        sort_res.is_ok() && v != expected
+    })
+}
+
+/// Returns a test that checks `S` against the standard library for `input`, to be pasted next to
+/// the instantiation of the tests for `S`.
+///
+/// The elements are written with their `Debug` output, which is valid Rust for integers and
+/// tuples of them, but may need to be adjusted for other types.
+pub fn regression_test<T: Debug, S: Sort>(input: &[T]) -> String {
+    regression_test_for_crate::<T, S>(input, &test_crate_name())
+}
+
+/// See [`regression_test`], for a test pasted into the crate `test_crate`.
+fn regression_test_for_crate<T: Debug, S: Sort>(input: &[T], test_crate: &str) -> String {
+    let test_name = <S as Sort>::name().replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+
+    format!(
+        r#"#[test]
+fn {test_name}_regression_len_{len}() {{
+    let original: Vec<{elem_type}> = vec!{input:?};
+
+    let mut expected = original.clone();
+    expected.sort();
+
+    let mut v = original;
+    <{sort_path} as sort_test_tools::Sort>::sort(&mut v);
+    assert_eq!(v, expected);
+}}
+"#,
+        len = input.len(),
+        elem_type = local_type_name(any::type_name::<T>(), test_crate),
+        sort_path = local_type_name(any::type_name::<S>(), test_crate),
+    )
+}
+
+/// Returns the name of the crate of the running test binary. Cargo names test binaries after
+/// their crate, followed by a hash, e.g. `main-1a2b3c4d5e6f7a8b` for `tests/main.rs`.
+fn test_crate_name() -> String {
+    let exe = env::current_exe().unwrap_or_default();
+    let stem = exe.file_stem().and_then(|stem| stem.to_str()).unwrap_or("");
+    let name = stem.rsplit_once('-').map_or(stem, |(name, _hash)| name);
+
+    name.replace('-', "_")
+}
+
+/// Rewrites the paths in `type_name` that point into `test_crate` to start with `crate::`, which
+/// is how a test in that crate has to name them. Paths into other crates are kept as they are.
+fn local_type_name(type_name: &str, test_crate: &str) -> String {
+    let is_ident_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let prefix = format!("{test_crate}::");
+
+    let mut result = String::with_capacity(type_name.len());
+    let mut rest = type_name;
+    while !rest.is_empty() {
+        let at_path_start = !result.ends_with(is_ident_char) && !result.ends_with(':');
+
+        if at_path_start && !test_crate.is_empty() && rest.starts_with(&prefix) {
+            result.push_str("crate::");
+            rest = &rest[prefix.len()..];
+        } else {
+            let c = rest.chars().next().unwrap();
+            result.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+
+    result
+}
+
+/// Delta debugging, returns `true` if elements were removed.
+fn remove_chunks<T: Clone>(v: &mut Vec<T>, fails: &mut impl FnMut(&[T]) -> bool) -> bool {
+    let len_before = v.len();
+    let mut chunk_count = 2;
+
+    while v.len() >= 2 {
+        let chunk_len = v.len().div_ceil(chunk_count);
+
+        let reduced = (0..v.len()).step_by(chunk_len).find_map(|start| {
+            let end = (start + chunk_len).min(v.len());
+            let candidate = [&v[..start], &v[end..]].concat();
+
+            fails(&candidate).then_some(candidate)
+        });
+
+        if let Some(candidate) = reduced {
+            *v = candidate;
+            // Keep the chunk length about the same for the next round.
+            chunk_count = (chunk_count - 1).max(2);
+        } else if chunk_len == 1 {
+            break;
+        } else {
+            chunk_count = (chunk_count * 2).min(v.len());
+        }
+    }
+
+    v.len() != len_before
+}
+
+/// Value compression, returns `true` if values were replaced.
+fn compress_values<T: Ord + Clone>(v: &mut Vec<T>, fails: &mut impl FnMut(&[T]) -> bool) -> bool {
+    let mut distinct = v.clone();
+    distinct.sort();
+    distinct.dedup();
+
+    let mut compressed = false;
+    for i in (1..distinct.len()).rev() {
+        let (value, smaller) = (&distinct[i], &distinct[i - 1]);
+
+        let candidate = v
+            .iter()
+            .map(|elem| if elem == value { smaller } else { elem }.clone())
+            .collect::<Vec<_>>();
+
+        if fails(&candidate) {
+            *v = candidate;
+            compressed = true;
+        }
+    }
+
+    compressed
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use super::*;
+
+    /// Sorts all but the last element.
+    struct SkipLastSort;
+
+    impl Sort for SkipLastSort {
+        fn name() -> String {
+            "skip_last".into()
+        }
+
+        fn sort<T: Ord>(arr: &mut [T]) {
+            Self::sort_by(arr, T::cmp);
+        }
+
+        fn sort_by<T, F>(arr: &mut [T], compare: F)
+        where
+            F: FnMut(&T, &T) -> Ordering,
+        {
+            if let Some((_last, init)) = arr.split_last_mut() {
+                init.sort_by(compare);
+            }
+        }
+    }
+
+    fn has_inverted_pair(v: &[i32]) -> bool {
+        v.iter()
+            .enumerate()
+            .any(|(i, a)| v[i + 1..].iter().any(|b| b < a))
+    }
+
+    #[test]
+    fn minimize_inverted_pair() {
+        let input = [3, 8, 8, 1, 9, 4, 4, 12, 0, 7, 5];
+        assert!(has_inverted_pair(&input));
+
+        // Removing chunks leaves a single inverted pair. Compressing 7 to 5, the next
+        // smaller value of the input, would make the two equal.
+        assert_eq!(minimize(&input, has_inverted_pair), [7, 5]);
+    }
+
+    #[test]
+    fn minimize_sort_mismatch_skip_last() {
+        let input = [5, 1, 4, 2, 3, 0, 6, 2];
+
+        // The smallest input that isn't sorted after sorting all but its last element.
+        assert_eq!(minimize_sort_mismatch::<i32, SkipLastSort>(&input), [6, 2]);
+    }
+
+    #[test]
+    fn regression_test_external_sort() {
+        let test = regression_test_for_crate::<i32, SkipLastSort>(&[1, 0], "main");
+
+        assert!(test.contains("fn skip_last_regression_len_2() {"));
+        assert!(test.contains("let original: Vec<i32> = vec![1, 0];"));
+        assert!(test.contains(
+            "<sort_test_tools::minimize::tests::SkipLastSort as sort_test_tools::Sort>::sort"
+        ));
+    }
+
+    #[test]
+    fn regression_test_local_sort() {
+        // This test binary is the crate `sort_test_tools`, which `SkipLastSort` is defined in.
+        let test = regression_test::<i32, SkipLastSort>(&[1, 0]);
+
+        assert!(test.contains("<crate::minimize::tests::SkipLastSort as sort_test_tools::Sort>"));
+    }
+
+    #[test]
+    fn local_type_name_paths() {
+        assert_eq!(
+            local_type_name("sort_comp::unstable::rust_ipnsort::SortImpl", "main"),
+            "sort_comp::unstable::rust_ipnsort::SortImpl"
+        );
+        assert_eq!(local_type_name("main::SortImpl", "main"), "crate::SortImpl");
+        assert_eq!(
+            local_type_name("(main::Key, alloc::string::String)", "main"),
+            "(crate::Key, alloc::string::String)"
+        );
+        // Only whole crate names at the start of a path are replaced.
+        assert_eq!(
+            local_type_name("domain::x::main::Key", "main"),
+            "domain::x::main::Key"
+        );
+    }
+}
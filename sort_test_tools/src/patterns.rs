@@ -1,50 +1,84 @@
 use std::env;
+use std::ops::{Bound, RangeBounds};
 use std::str::FromStr;
 use std::sync::atomic::{AtomicBool, Ordering};
 
+use rand::distributions::Uniform;
 use rand::prelude::*;
 
 use zipf::ZipfDistribution;
 
 use once_cell::sync::OnceCell;
 
+use crate::ffi_types::{FFIOneKiloByte, FFIString, F128};
+
 /// Provides a set of patterns useful for testing and benchmarking sorting algorithms.
-/// Currently limited to i32 values.
+/// Generic over the element type, see [`PatternValue`].
 
 // --- Public ---
 
-pub fn random(size: usize) -> Vec<i32> {
+/// Element types patterns can be generated for.
+///
+/// The patterns are generated as `i64` values and converted with `from_i64`. Values that already
+/// lie in `MIN..=MAX` are converted as is, which keeps for example the few distinct values of
+/// `random_uniform(len, 0..4)` intact, even for `u8`. Otherwise they are scaled linearly onto
+/// `MIN..=MAX`. The exception is `random_zipf`, which clamps its ranks instead, so that the frequent
+/// values stay distinct.
+pub trait PatternValue: Sized {
+    /// The smallest value passed to `from_i64`.
+    const MIN: i64;
+
+    /// The largest value passed to `from_i64`.
+    const MAX: i64;
+
+    /// Has to be strictly increasing in `MIN..=MAX`, so that patterns keep their order and
+    /// distinct values.
+    fn from_i64(val: i64) -> Self;
+}
+
+pub fn random<T: PatternValue>(size: usize) -> Vec<T> {
     //     .
     // : . : :
     // :.:::.::
 
-    random_vec(size)
+    map_values(random_vec::<T>(size), T::MIN, T::MAX)
 }
 
-pub fn random_uniform<R>(size: usize, range: R) -> Vec<i32>
-where
-    R: Into<rand::distributions::Uniform<i32>>,
-{
+pub fn random_uniform<T: PatternValue>(size: usize, range: impl RangeBounds<i32>) -> Vec<T> {
     // :.:.:.::
     let mut rng = rand::rngs::StdRng::from(new_seed());
 
     // Abstracting over ranges in Rust :(
-    let dist: rand::distributions::Uniform<i32> = range.into();
-
-    (0..size).map(|_| dist.sample(&mut rng)).collect()
+    let min = match range.start_bound() {
+        Bound::Included(&start) => start as i64,
+        Bound::Excluded(&start) => start as i64 + 1,
+        Bound::Unbounded => i32::MIN as i64,
+    };
+    let max = match range.end_bound() {
+        Bound::Included(&end) => end as i64,
+        Bound::Excluded(&end) => end as i64 - 1,
+        Bound::Unbounded => i32::MAX as i64,
+    };
+    let dist = Uniform::new_inclusive(min, max);
+
+    let vals = (0..size).map(|_| dist.sample(&mut rng)).collect();
+    map_values(vals, min, max)
 }
 
-pub fn random_zipf(size: usize, exponent: f64) -> Vec<i32> {
+pub fn random_zipf<T: PatternValue>(size: usize, exponent: f64) -> Vec<T> {
     // https://en.wikipedia.org/wiki/Zipf's_law
     let mut rng = rand::rngs::StdRng::from(new_seed());
 
     // Abstracting over ranges in Rust :(
     let dist = ZipfDistribution::new(size, exponent).unwrap();
 
-    (0..size).map(|_| dist.sample(&mut rng) as i32).collect()
+    // Scaling would merge the most frequent ranks, clamping only merges the rarest ones.
+    (0..size)
+        .map(|_| T::from_i64((dist.sample(&mut rng) as i64).clamp(T::MIN, T::MAX)))
+        .collect()
 }
 
-pub fn random_sorted(size: usize, sorted_percent: f64) -> Vec<i32> {
+pub fn random_sorted<T: PatternValue>(size: usize, sorted_percent: f64) -> Vec<T> {
     //     .:
     //   .:::. :
     // .::::::.::
@@ -56,48 +90,50 @@ pub fn random_sorted(size: usize, sorted_percent: f64) -> Vec<i32> {
 
     // Simulate pre-existing sorted slice, where len - sorted_percent are the new unsorted values
     // and part of the overall distribution.
-    let mut v = random_vec(size);
+    let mut v = random_vec::<T>(size);
     let sorted_len = ((size as f64) * (sorted_percent / 100.0)).round() as usize;
 
     v[0..sorted_len].sort_unstable();
 
-    v
+    map_values(v, T::MIN, T::MAX)
 }
 
-pub fn random_random_size(max_size: usize) -> Vec<i32> {
+pub fn random_random_size<T: PatternValue>(max_size: usize) -> Vec<T> {
     //     .
     // : . : :
     // :.:::.::
     // < size > is random from call to call, with max_size as maximum size.
 
-    let random_size = random_uniform(1, 0..=(max_size as i32));
+    let random_size = random_uniform::<i32>(1, 0..=(max_size as i32));
     random(random_size[0] as usize)
 }
 
-pub fn all_equal(size: usize) -> Vec<i32> {
+pub fn all_equal<T: PatternValue>(size: usize) -> Vec<T> {
     // ......
     // ::::::
 
-    (0..size).map(|_| 66).collect::<Vec<_>>()
+    (0..size)
+        .map(|_| T::from_i64(66.clamp(T::MIN, T::MAX)))
+        .collect::<Vec<_>>()
 }
 
-pub fn ascending(size: usize) -> Vec<i32> {
+pub fn ascending<T: PatternValue>(size: usize) -> Vec<T> {
     //     .:
     //   .:::
     // .:::::
 
-    (0..size as i32).collect::<Vec<_>>()
+    map_values((0..size as i64).collect(), 0, size as i64 - 1)
 }
 
-pub fn descending(size: usize) -> Vec<i32> {
+pub fn descending<T: PatternValue>(size: usize) -> Vec<T> {
     // :.
     // :::.
     // :::::.
 
-    (0..size as i32).rev().collect::<Vec<_>>()
+    map_values((0..size as i64).rev().collect(), 0, size as i64 - 1)
 }
 
-pub fn saw_ascending(size: usize, saw_count: usize) -> Vec<i32> {
+pub fn saw_ascending<T: PatternValue>(size: usize, saw_count: usize) -> Vec<T> {
     //   .:  .:
     // .:::.:::
 
@@ -105,17 +141,17 @@ pub fn saw_ascending(size: usize, saw_count: usize) -> Vec<i32> {
         return Vec::new();
     }
 
-    let mut vals = random_vec(size);
+    let mut vals = random_vec::<T>(size);
     let chunks_size = size / saw_count.max(1);
 
     for chunk in vals.chunks_mut(chunks_size) {
         chunk.sort();
     }
 
-    vals
+    map_values(vals, T::MIN, T::MAX)
 }
 
-pub fn saw_descending(size: usize, saw_count: usize) -> Vec<i32> {
+pub fn saw_descending<T: PatternValue>(size: usize, saw_count: usize) -> Vec<T> {
     // :.  :.
     // :::.:::.
 
@@ -123,17 +159,17 @@ pub fn saw_descending(size: usize, saw_count: usize) -> Vec<i32> {
         return Vec::new();
     }
 
-    let mut vals = random_vec(size);
+    let mut vals = random_vec::<T>(size);
     let chunks_size = size / saw_count.max(1);
 
     for chunk in vals.chunks_mut(chunks_size) {
         chunk.sort_by_key(|&e| std::cmp::Reverse(e));
     }
 
-    vals
+    map_values(vals, T::MIN, T::MAX)
 }
 
-pub fn saw_mixed(size: usize, saw_count: usize) -> Vec<i32> {
+pub fn saw_mixed<T: PatternValue>(size: usize, saw_count: usize) -> Vec<T> {
     // :.  :.    .::.    .:
     // :::.:::..::::::..:::
 
@@ -141,9 +177,9 @@ pub fn saw_mixed(size: usize, saw_count: usize) -> Vec<i32> {
         return Vec::new();
     }
 
-    let mut vals = random_vec(size);
+    let mut vals = random_vec::<T>(size);
     let chunks_size = size / saw_count.max(1);
-    let saw_directions = random_uniform((size / chunks_size) + 1, 0..=1);
+    let saw_directions = random_uniform::<i32>((size / chunks_size) + 1, 0..=1);
 
     for (i, chunk) in vals.chunks_mut(chunks_size).enumerate() {
         if saw_directions[i] == 0 {
@@ -155,10 +191,10 @@ pub fn saw_mixed(size: usize, saw_count: usize) -> Vec<i32> {
         }
     }
 
-    vals
+    map_values(vals, T::MIN, T::MAX)
 }
 
-pub fn saw_mixed_range(size: usize, range: std::ops::Range<usize>) -> Vec<i32> {
+pub fn saw_mixed_range<T: PatternValue>(size: usize, range: std::ops::Range<usize>) -> Vec<T> {
     //     :.
     // :.  :::.    .::.      .:
     // :::.:::::..::::::..:.:::
@@ -169,11 +205,12 @@ pub fn saw_mixed_range(size: usize, range: std::ops::Range<usize>) -> Vec<i32> {
         return Vec::new();
     }
 
-    let mut vals = random_vec(size);
+    let mut vals = random_vec::<T>(size);
 
     let max_chunks = size / range.start;
-    let saw_directions = random_uniform(max_chunks + 1, 0..=1);
-    let chunk_sizes = random_uniform(max_chunks + 1, (range.start as i32)..(range.end as i32));
+    let saw_directions = random_uniform::<i32>(max_chunks + 1, 0..=1);
+    let chunk_sizes =
+        random_uniform::<i32>(max_chunks + 1, (range.start as i32)..(range.end as i32));
 
     let mut i = 0;
     let mut l = 0;
@@ -194,14 +231,14 @@ pub fn saw_mixed_range(size: usize, range: std::ops::Range<usize>) -> Vec<i32> {
         l += chunk_size;
     }
 
-    vals
+    map_values(vals, T::MIN, T::MAX)
 }
 
-pub fn pipe_organ(size: usize) -> Vec<i32> {
+pub fn pipe_organ<T: PatternValue>(size: usize) -> Vec<T> {
     //   .:.
     // .:::::.
 
-    let mut vals = random_vec(size);
+    let mut vals = random_vec::<T>(size);
 
     let first_half = &mut vals[0..(size / 2)];
     first_half.sort();
@@ -209,7 +246,17 @@ pub fn pipe_organ(size: usize) -> Vec<i32> {
     let second_half = &mut vals[(size / 2)..size];
     second_half.sort_by_key(|&e| std::cmp::Reverse(e));
 
-    vals
+    map_values(vals, T::MIN, T::MAX)
+}
+
+/// Converts the values of an `i32` pattern, for code that has to work with the `i32` values
+/// before converting them. Whether they are scaled depends on the smallest and largest value
+/// present, see [`PatternValue`].
+pub fn convert_i32<T: PatternValue>(values: Vec<i32>) -> Vec<T> {
+    let min = values.iter().min().map_or(0, |&val| val as i64);
+    let max = values.iter().max().map_or(0, |&val| val as i64);
+
+    map_values(values.into_iter().map(|val| val as i64).collect(), min, max)
 }
 
 static USE_FIXED_SEED: AtomicBool = AtomicBool::new(true);
@@ -240,6 +287,111 @@ pub fn random_init_seed() -> u64 {
     }
 }
 
+macro_rules! impl_pattern_value_int {
+    ($($t:ty: $min:expr, $max:expr;)*) => {
+        $(
+            impl PatternValue for $t {
+                const MIN: i64 = $min;
+                const MAX: i64 = $max;
+
+                fn from_i64(val: i64) -> Self {
+                    val as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_pattern_value_int! {
+    i8: i8::MIN as i64, i8::MAX as i64;
+    i16: i16::MIN as i64, i16::MAX as i64;
+    i32: i32::MIN as i64, i32::MAX as i64;
+    i64: i64::MIN, i64::MAX;
+    isize: isize::MIN as i64, isize::MAX as i64;
+    u8: 0, u8::MAX as i64;
+    u16: 0, u16::MAX as i64;
+    u32: 0, u32::MAX as i64;
+    u64: 0, i64::MAX;
+    usize: 0, if usize::BITS < 64 { usize::MAX as i64 } else { i64::MAX };
+}
+
+impl PatternValue for i128 {
+    const MIN: i64 = i64::MIN;
+    const MAX: i64 = i64::MAX;
+
+    fn from_i64(val: i64) -> Self {
+        // The upper half keeps the order, repeating the value in the lower half fills all bits.
+        ((val as i128) << 64) | (val as u64 as i128)
+    }
+}
+
+impl PatternValue for u128 {
+    const MIN: i64 = 0;
+    const MAX: i64 = i64::MAX;
+
+    fn from_i64(val: i64) -> Self {
+        // See the impl for `i128`.
+        ((val as u128) << 64) | (val as u64 as u128)
+    }
+}
+
+impl PatternValue for f32 {
+    // Integers in this range are exactly representable.
+    const MIN: i64 = -(1 << f32::MANTISSA_DIGITS);
+    const MAX: i64 = 1 << f32::MANTISSA_DIGITS;
+
+    fn from_i64(val: i64) -> Self {
+        val as f32
+    }
+}
+
+impl PatternValue for f64 {
+    const MIN: i64 = -(1 << f64::MANTISSA_DIGITS);
+    const MAX: i64 = 1 << f64::MANTISSA_DIGITS;
+
+    fn from_i64(val: i64) -> Self {
+        val as f64
+    }
+}
+
+impl PatternValue for String {
+    const MIN: i64 = 0;
+    const MAX: i64 = u32::MAX as i64;
+
+    fn from_i64(val: i64) -> Self {
+        // Strings are compared lexicographically, so we zero extend them to maintain the input
+        // order.
+        format!("{val:010}")
+    }
+}
+
+impl PatternValue for FFIString {
+    const MIN: i64 = String::MIN;
+    const MAX: i64 = String::MAX;
+
+    fn from_i64(val: i64) -> Self {
+        FFIString::new(String::from_i64(val))
+    }
+}
+
+impl PatternValue for FFIOneKiloByte {
+    const MIN: i64 = i32::MIN as i64;
+    const MAX: i64 = i32::MAX as i64;
+
+    fn from_i64(val: i64) -> Self {
+        FFIOneKiloByte::new(val as i32)
+    }
+}
+
+impl PatternValue for F128 {
+    const MIN: i64 = i32::MIN as i64;
+    const MAX: i64 = i32::MAX as i64;
+
+    fn from_i64(val: i64) -> Self {
+        F128::new(val as i32)
+    }
+}
+
 // --- Private ---
 
 fn new_seed() -> StdRng {
@@ -247,8 +399,28 @@ fn new_seed() -> StdRng {
     rand::SeedableRng::seed_from_u64(random_init_seed())
 }
 
-fn random_vec(size: usize) -> Vec<i32> {
+fn random_vec<T: PatternValue>(size: usize) -> Vec<i64> {
     let mut rng = rand::rngs::StdRng::from(new_seed());
+    let dist = Uniform::new_inclusive(T::MIN, T::MAX);
+
+    (0..size).map(|_| dist.sample(&mut rng)).collect()
+}
+
+// Converts `vals`, which all lie in `min..=max`, as is if that fits into `T::MIN..=T::MAX`, and
+// scaled linearly onto it otherwise. Both keep the order of the values.
+fn map_values<T: PatternValue>(vals: Vec<i64>, min: i64, max: i64) -> Vec<T> {
+    if min >= T::MIN && max <= T::MAX {
+        return vals.into_iter().map(T::from_i64).collect();
+    }
+
+    // Both factors are smaller than 2^64, so the product fits.
+    let src_range = (max as i128 - min as i128).max(1) as u128;
+    let dst_range = (T::MAX as i128 - T::MIN as i128) as u128;
 
-    (0..size).map(|_| rng.gen::<i32>()).collect()
+    vals.into_iter()
+        .map(|val| {
+            let offset = (val as i128 - min as i128) as u128 * dst_range / src_range;
+            T::from_i64((T::MIN as i128 + offset as i128) as i64)
+        })
+        .collect()
 }
@@ -1,14 +1,18 @@
+use std::any;
 use std::cell::Cell;
 use std::cmp::Ordering;
 use std::env;
 use std::fmt::Debug;
 use std::fs;
 use std::io::{self, Write};
+use std::mem;
 use std::panic::{self, AssertUnwindSafe};
 use std::rc::Rc;
 use std::sync::Mutex;
 
+use crate::counting_alloc::{self, AllocStats};
 use crate::ffi_types::{FFIOneKiloByte, FFIString, F128};
+use crate::minimize;
 use crate::patterns;
 use crate::Sort;
 
@@ -17,20 +21,20 @@ use crate::Sort;
 // use sort_comp::unstable::rust_ipn as test_sort;
 
 #[cfg(miri)]
-const TEST_SIZES: [usize; 18] = [
+pub const TEST_SIZES: [usize; 18] = [
     0, 1, 2, 3, 4, 5, 6, 7, 8, 10, 15, 20, 24, 33, 50, 100, 280, 400,
 ];
 
 #[cfg(feature = "large_test_sizes")]
 #[cfg(not(miri))]
-const TEST_SIZES: [usize; 30] = [
+pub const TEST_SIZES: [usize; 30] = [
     0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 15, 16, 17, 20, 24, 30, 32, 33, 35, 50, 100, 200, 500, 1_000,
     2_048, 5_000, 10_000, 100_000, 1_000_000,
 ];
 
 #[cfg(not(feature = "large_test_sizes"))]
 #[cfg(not(miri))]
-const TEST_SIZES: [usize; 28] = [
+pub const TEST_SIZES: [usize; 28] = [
     0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 15, 16, 17, 20, 24, 30, 32, 33, 35, 50, 100, 200, 500, 1_000,
     2_048, 5_000, 10_000,
 ];
@@ -95,6 +99,13 @@ fn sort_comp<T: Ord + Clone + Debug, S: Sort>(v: &mut [T]) {
                 }
             }
 
+            let minimized = minimize::minimize_sort_mismatch::<T, S>(&original_clone);
+            eprintln!(
+                "Minimized failing input of len {}, as regression test:\n\n{}",
+                minimized.len(),
+                minimize::regression_test::<T, S>(&minimized)
+            );
+
             panic!("Test assertion failed!")
         }
     }
@@ -209,32 +220,16 @@ pub fn random<S: Sort>() {
     test_impl::<i32, S>(patterns::random);
 }
 
+pub fn random_type_u8<S: Sort>() {
+    test_impl::<u8, S>(patterns::random);
+}
+
 pub fn random_type_u64<S: Sort>() {
-    test_impl::<u64, S>(|size| {
-        patterns::random(size)
-            .iter()
-            .map(|val| -> u64 {
-                // Extends the value into the 64 bit range,
-                // while preserving input order.
-                let x = ((*val as i64) + (i32::MAX as i64) + 1) as u64;
-                x.checked_mul(i32::MAX as u64).unwrap()
-            })
-            .collect()
-    });
+    test_impl::<u64, S>(patterns::random);
 }
 
 pub fn random_type_u128<S: Sort>() {
-    test_impl::<u128, S>(|size| {
-        patterns::random(size)
-            .iter()
-            .map(|val| -> u128 {
-                // Extends the value into the 128 bit range,
-                // while preserving input order.
-                let x = ((*val as i128) + (i64::MAX as i128) + 1) as u128;
-                x.checked_mul(i64::MAX as u128).unwrap()
-            })
-            .collect()
-    });
+    test_impl::<u128, S>(patterns::random);
 }
 
 pub fn random_cell_i32<S: Sort>() {
@@ -547,26 +542,16 @@ pub fn stability_with_patterns<S: Sort>() {
 }
 
 pub fn random_ffi_str<S: Sort>() {
-    test_impl::<FFIString, S>(|test_len| {
-        patterns::random(test_len)
-            .into_iter()
-            .map(|val| FFIString::new(format!("{:010}", val.saturating_abs())))
-            .collect::<Vec<_>>()
-    });
+    test_impl::<FFIString, S>(patterns::random);
 }
 
 pub fn random_f128<S: Sort>() {
-    test_impl::<F128, S>(|test_len| {
-        patterns::random(test_len)
-            .into_iter()
-            .map(|val| F128::new(val))
-            .collect::<Vec<_>>()
-    });
+    test_impl::<F128, S>(patterns::random);
 }
 
 pub fn random_str<S: Sort>() {
     test_impl::<String, S>(|test_len| {
-        patterns::random(test_len)
+        patterns::random::<i32>(test_len)
             .into_iter()
             .map(|val| format!("{}", val))
             .collect::<Vec<_>>()
@@ -581,9 +566,6 @@ pub fn random_large_val<S: Sort>() {
         }
 
         patterns::random(test_len)
-            .into_iter()
-            .map(|val| FFIOneKiloByte::new(val))
-            .collect::<Vec<_>>()
     });
 }
 
@@ -884,7 +866,7 @@ pub fn panic_retain_original_set_impl<S: Sort, T: Ord + Clone>(
         // Ensure that it can be any of the possible comparisons and that it always panics.
         let required_comps = calc_comps_required::<T, S>(&test_data, |a, b| a.cmp(b));
         let panic_threshold =
-            patterns::random_uniform(1, 1..=required_comps as i32)[0] as usize - 1;
+            patterns::random_uniform::<usize>(1, 1..=required_comps as i32)[0] - 1;
 
         let mut comp_counter = 0;
 
@@ -971,7 +953,7 @@ fn panic_observable_is_less_impl<S: Sort, T: Ord + Clone>(
         let required_comps =
             calc_comps_required::<CompCount<T>, S>(&test_input, |a, b| a.val.cmp(&b.val));
 
-        let panic_threshold = patterns::random_uniform(1, 1..=required_comps as i32)[0] as u64 - 1;
+        let panic_threshold = patterns::random_uniform::<u64>(1, 1..=required_comps as i32)[0] - 1;
 
         let mut comp_count_global = 0;
 
@@ -1038,7 +1020,7 @@ fn violate_ord_retain_original_set_impl<S: Sort, T: Ord>(
 
     // Make sure we get a good distribution of random orderings, that are repeatable with the seed.
     // Just using random_uniform with the same size and range will always yield the same value.
-    let random_orderings = patterns::random_uniform(5_000, 0..2);
+    let random_orderings = patterns::random_uniform::<i32>(5_000, 0..2);
 
     let get_random_0_1_or_2 = |random_idx: &mut usize| {
         let ridx = *random_idx;
@@ -1225,6 +1207,181 @@ pub fn sort_vs_sort_by<S: Sort>() {
     assert_eq!(input_sort_by, expected);
 }
 
+fn assert_sorted_by_key<T: Ord + Clone + Debug, K: Ord, S: Sort>(
+    original: &[T],
+    sorted: &[T],
+    key_fn: impl Fn(&T) -> K,
+) {
+    assert!(sorted.windows(2).all(|w| key_fn(&w[0]) <= key_fn(&w[1])));
+
+    if !<S as Sort>::name().contains("unstable") {
+        let mut expected = original.to_vec();
+        expected.sort_by_key(&key_fn);
+        assert_eq!(sorted, expected);
+    }
+
+    // The result must be a permutation of the original.
+    let mut original_sorted = original.to_vec();
+    original_sorted.sort();
+    let mut sorted_sorted = sorted.to_vec();
+    sorted_sorted.sort();
+    assert_eq!(original_sorted, sorted_sorted);
+}
+
+pub fn sort_by_key<S: Sort>() {
+    let _seed = get_or_init_random_seed::<S>();
+
+    // Reduce the key space to get many elements with equal keys but different values.
+    let key_fn = |val: &i32| val.saturating_abs() % 1_000;
+
+    let test_fn = |test_len: usize, pattern_fn: fn(usize) -> Vec<i32>| {
+        let original = pattern_fn(test_len);
+        let mut v = original.clone();
+        <S as Sort>::sort_by_key(&mut v, key_fn);
+
+        assert_sorted_by_key::<i32, i32, S>(&original, &v, key_fn);
+    };
+
+    test_impl_custom(test_fn);
+}
+
+pub fn sort_by_cached_key<S: Sort>() {
+    let _seed = get_or_init_random_seed::<S>();
+
+    // Key types that are not Copy and comparatively expensive to create are the main use-case.
+    let key_fn_i32 = |val: &i32| format!("{:03}", val.saturating_abs() % 1_000);
+    let key_fn_ffi_str = |val: &FFIString| val.as_str()[7..].to_owned();
+
+    let test_fn = |test_len: usize, pattern_fn: fn(usize) -> Vec<i32>| {
+        let original = pattern_fn(test_len);
+        let mut v = original.clone();
+        <S as Sort>::sort_by_cached_key(&mut v, key_fn_i32);
+
+        assert_sorted_by_key::<i32, String, S>(&original, &v, key_fn_i32);
+
+        let original = original
+            .into_iter()
+            .map(|val| FFIString::new(format!("{:010}", val.saturating_abs())))
+            .collect::<Vec<_>>();
+        let mut v = original.clone();
+        <S as Sort>::sort_by_cached_key(&mut v, key_fn_ffi_str);
+
+        assert_sorted_by_key::<FFIString, String, S>(&original, &v, key_fn_ffi_str);
+    };
+
+    test_impl_custom(test_fn);
+}
+
+pub fn sort_by_key_panic_retain_original_set<S: Sort>() {
+    let _seed = get_or_init_random_seed::<S>();
+
+    // A panic in the key function must leave the input with its original set of elements, no
+    // matter if the key is called as part of comparisons or upfront for caching.
+
+    let test_fn = |test_len: usize, pattern_fn: fn(usize) -> Vec<i32>| {
+        for use_cached_key in [false, true] {
+            let mut test_data: Vec<FFIString> = pattern_fn(test_len)
+                .into_iter()
+                .map(|val| FFIString::new(format!("{:010}", val.saturating_abs())))
+                .collect();
+
+            let sum_before: i64 = test_data
+                .iter()
+                .map(|x| x.as_str().parse::<i64>().unwrap())
+                .sum();
+
+            // Every implementation calls the key function at least `test_len` times, so this
+            // always panics.
+            let panic_threshold = patterns::random_uniform::<usize>(1, 0..(test_len as i32))[0];
+            let mut key_calls = 0;
+
+            let mut key_fn = |val: &FFIString| -> String {
+                if key_calls == panic_threshold {
+                    panic!();
+                }
+                key_calls += 1;
+
+                val.as_str().to_owned()
+            };
+
+            let res = panic::catch_unwind(AssertUnwindSafe(|| {
+                if use_cached_key {
+                    <S as Sort>::sort_by_cached_key(&mut test_data, &mut key_fn);
+                } else {
+                    <S as Sort>::sort_by_key(&mut test_data, &mut key_fn);
+                }
+            }));
+
+            assert!(res.is_err());
+
+            // If the sum before and after don't match, it means the set of elements hasn't
+            // remained the same.
+            let sum_after: i64 = test_data
+                .iter()
+                .map(|x| x.as_str().parse::<i64>().unwrap())
+                .sum();
+            assert_eq!(sum_before, sum_after);
+        }
+    };
+
+    test_impl_custom(test_fn);
+}
+
+pub fn try_sort_by_retain_original_set<S: Sort>() {
+    let _seed = get_or_init_random_seed::<S>();
+
+    // Every comparison gets to return the error once, which is quadratic in the number of
+    // comparisons. So only the shorter test sizes are used.
+    const MAX_TEST_LEN: usize = 100;
+
+    let test_fn = |test_len: usize, pattern_fn: fn(usize) -> Vec<i32>| {
+        if test_len > MAX_TEST_LEN {
+            return;
+        }
+
+        let original = pattern_fn(test_len);
+
+        let mut expected = original.clone();
+        expected.sort();
+
+        let mut v = original.clone();
+        let mut comps_required = 0;
+        let res = <S as Sort>::try_sort_by(&mut v, |a, b| -> Result<Ordering, usize> {
+            comps_required += 1;
+            Ok(a.cmp(b))
+        });
+        assert_eq!(res, Ok(()));
+        assert_eq!(v, expected);
+
+        for error_at in 0..comps_required {
+            let mut v = original.clone();
+            let mut comp_counter = 0;
+
+            let res = <S as Sort>::try_sort_by(&mut v, |a, b| {
+                assert!(
+                    comp_counter <= error_at,
+                    "compare called after returning an error. test_len: {test_len}"
+                );
+                comp_counter += 1;
+
+                if comp_counter > error_at {
+                    return Err(error_at);
+                }
+
+                Ok(a.cmp(b))
+            });
+
+            assert_eq!(res, Err(error_at));
+
+            // The elements may be in any order, but have to be the original ones.
+            v.sort();
+            assert_eq!(v, expected);
+        }
+    };
+
+    test_impl_custom(test_fn);
+}
+
 pub fn int_edge<S: Sort>() {
     let _seed = get_or_init_random_seed::<S>();
 
@@ -1264,6 +1421,66 @@ pub fn int_edge<S: Sort>() {
     sort_comp::<i32, S>(&mut large);
 }
 
+fn track_sort_allocations<T: Ord, S: Sort>(v: &mut [T]) -> AllocStats {
+    assert!(
+        counting_alloc::is_installed(),
+        "The allocation tests need CountingAllocator as global allocator"
+    );
+
+    counting_alloc::track_allocations(|| <S as Sort>::sort(v))
+}
+
+pub fn no_alloc<S: Sort>() {
+    let _seed = get_or_init_random_seed::<S>();
+
+    fn check<T: Ord, S: Sort>(mut v: Vec<T>) {
+        let len = v.len();
+        let stats = track_sort_allocations::<T, S>(&mut v);
+        assert_eq!(stats, AllocStats::default(), "len: {len}");
+    }
+
+    for len in TEST_SIZES {
+        check::<i32, S>(patterns::random(len));
+        check::<i32, S>(patterns::random_uniform(len, 0..=1));
+        check::<i32, S>(patterns::descending(len));
+        check::<FFIString, S>(patterns::random(len));
+    }
+}
+
+// Shorter inputs are often sorted with fixed size buffers, which say little about how the scratch
+// space grows with the input.
+const SCRATCH_RATIO_MIN_LEN: usize = 1_000;
+
+pub fn scratch_ratio<S: Sort>(max_ratio: f64) {
+    let _seed = get_or_init_random_seed::<S>();
+
+    fn check<T: Ord, S: Sort>(mut v: Vec<T>, max_ratio: f64) {
+        let len = v.len();
+        let stats = track_sort_allocations::<T, S>(&mut v);
+        let ratio = stats.peak_bytes as f64 / (len * mem::size_of::<T>()) as f64;
+
+        println!(
+            "{} {} len: {len} allocations: {} peak bytes: {} scratch ratio: {ratio:.3}",
+            <S as Sort>::name(),
+            any::type_name::<T>(),
+            stats.count,
+            stats.peak_bytes
+        );
+        assert!(
+            ratio <= max_ratio,
+            "len: {len} scratch ratio: {ratio:.3} max: {max_ratio}"
+        );
+    }
+
+    for len in TEST_SIZES
+        .into_iter()
+        .filter(|&len| len >= SCRATCH_RATIO_MIN_LEN)
+    {
+        check::<i32, S>(patterns::random(len), max_ratio);
+        check::<FFIString, S>(patterns::random(len), max_ratio);
+    }
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! instantiate_sort_test_impl_inner {
@@ -1338,18 +1555,43 @@ macro_rules! instantiate_sort_tests {
             [miri_no, random_str],
             [miri_yes, random_type_u128],
             [miri_yes, random_type_u64],
+            [miri_yes, random_type_u8],
             [miri_yes, random_cell_i32],
             [miri_yes, random_z1],
             [miri_no, random_z1_03],
             [miri_no, random_z2],
             [miri_yes, saw_mixed],
             [miri_yes, saw_mixed_range],
+            [miri_yes, sort_by_cached_key],
+            [miri_yes, sort_by_key],
+            [miri_no, sort_by_key_panic_retain_original_set],
             [miri_yes, sort_vs_sort_by],
             [miri_yes, stability],
             [miri_no, stability_with_patterns],
+            [miri_no, try_sort_by_retain_original_set],
             [miri_yes, violate_ord_retain_original_set_i32],
             [miri_no, violate_ord_retain_original_set_ffi_string],
             [miri_no, violate_ord_retain_original_set_cell_i32]
         );
     };
 }
+
+/// Instantiates the allocation tests for `$sort_impl`, which need
+/// [`CountingAllocator`](crate::counting_alloc::CountingAllocator) as global allocator of the test
+/// binary.
+///
+/// Sorts declared `in_place` must not allocate. For the others, the most memory allocated at once
+/// may be at most `max_scratch_ratio` times the size of the input.
+#[macro_export]
+macro_rules! instantiate_alloc_tests {
+    ($sort_impl:ty, in_place) => {
+        sort_test_tools::instantiate_sort_test_impl!($sort_impl, [miri_yes, no_alloc]);
+    };
+    ($sort_impl:ty, max_scratch_ratio = $max_scratch_ratio:expr) => {
+        #[test]
+        #[cfg_attr(miri, ignore)]
+        fn scratch_ratio() {
+            sort_test_tools::tests::scratch_ratio::<$sort_impl>($max_scratch_ratio);
+        }
+    };
+}
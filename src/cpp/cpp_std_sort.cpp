@@ -35,6 +35,24 @@ uint32_t sort_unstable_by_impl(T* data,
   return 0;
 }
 
+template <typename T>
+uint32_t partial_sort_by_impl(T* data,
+                              size_t len,
+                              size_t k,
+                              CompResult (*cmp_fn)(const T&,
+                                                   const T&,
+                                                   uint8_t*),
+                              uint8_t* ctx) noexcept {
+  try {
+    std::partial_sort(data, data + k, data + len,
+                      make_compare_fn(cmp_fn, ctx));
+  } catch (...) {
+    return 1;
+  }
+
+  return 0;
+}
+
 #if defined(STD_LIB_SYS)
 #define MAKE_FUNC_NAME(name, suffix) name##_sys_##suffix
 #elif defined(STD_LIB_LIBCXX)
@@ -68,6 +86,19 @@ uint32_t MAKE_FUNC_NAME(sort_unstable, i32_by)(
   return sort_unstable_by_impl(data, len, cmp_fn, ctx);
 }
 
+void MAKE_FUNC_NAME(partial_sort, i32)(int32_t* data, size_t len, size_t k) {
+  std::partial_sort(data, data + k, data + len);
+}
+
+uint32_t MAKE_FUNC_NAME(partial_sort, i32_by)(
+    int32_t* data,
+    size_t len,
+    size_t k,
+    CompResult (*cmp_fn)(const int32_t&, const int32_t&, uint8_t*),
+    uint8_t* ctx) {
+  return partial_sort_by_impl(data, len, k, cmp_fn, ctx);
+}
+
 // --- u64 ---
 
 void MAKE_FUNC_NAME(sort_stable, u64)(uint64_t* data, size_t len) {
@@ -94,6 +125,19 @@ uint32_t MAKE_FUNC_NAME(sort_unstable, u64_by)(
   return sort_unstable_by_impl(data, len, cmp_fn, ctx);
 }
 
+void MAKE_FUNC_NAME(partial_sort, u64)(uint64_t* data, size_t len, size_t k) {
+  std::partial_sort(data, data + k, data + len);
+}
+
+uint32_t MAKE_FUNC_NAME(partial_sort, u64_by)(
+    uint64_t* data,
+    size_t len,
+    size_t k,
+    CompResult (*cmp_fn)(const uint64_t&, const uint64_t&, uint8_t*),
+    uint8_t* ctx) {
+  return partial_sort_by_impl(data, len, k, cmp_fn, ctx);
+}
+
 // --- FFIString ---
 
 void MAKE_FUNC_NAME(sort_stable, ffi_string)(FFIString* data, size_t len) {
@@ -122,6 +166,23 @@ uint32_t MAKE_FUNC_NAME(sort_unstable, ffi_string_by)(
   return sort_unstable_by_impl(data, len, cmp_fn, ctx);
 }
 
+void MAKE_FUNC_NAME(partial_sort, ffi_string)(FFIString* data,
+                                              size_t len,
+                                              size_t k) {
+  std::partial_sort(reinterpret_cast<FFIStringCpp*>(data),
+                    reinterpret_cast<FFIStringCpp*>(data) + k,
+                    reinterpret_cast<FFIStringCpp*>(data) + len);
+}
+
+uint32_t MAKE_FUNC_NAME(partial_sort, ffi_string_by)(
+    FFIString* data,
+    size_t len,
+    size_t k,
+    CompResult (*cmp_fn)(const FFIString&, const FFIString&, uint8_t*),
+    uint8_t* ctx) {
+  return partial_sort_by_impl(data, len, k, cmp_fn, ctx);
+}
+
 // --- f128 ---
 
 void MAKE_FUNC_NAME(sort_stable, f128)(F128* data, size_t len) {
@@ -151,6 +212,21 @@ uint32_t MAKE_FUNC_NAME(sort_unstable, f128_by)(
   return sort_unstable_by_impl(data, len, cmp_fn, ctx);
 }
 
+void MAKE_FUNC_NAME(partial_sort, f128)(F128* data, size_t len, size_t k) {
+  std::partial_sort(reinterpret_cast<F128Cpp*>(data),
+                    reinterpret_cast<F128Cpp*>(data) + k,
+                    reinterpret_cast<F128Cpp*>(data) + len);
+}
+
+uint32_t MAKE_FUNC_NAME(partial_sort, f128_by)(
+    F128* data,
+    size_t len,
+    size_t k,
+    CompResult (*cmp_fn)(const F128&, const F128&, uint8_t*),
+    uint8_t* ctx) {
+  return partial_sort_by_impl(data, len, k, cmp_fn, ctx);
+}
+
 // --- 1k ---
 
 void MAKE_FUNC_NAME(sort_stable, 1k)(FFIOneKiloByte* data, size_t len) {
@@ -182,4 +258,23 @@ uint32_t MAKE_FUNC_NAME(sort_unstable,
                                uint8_t* ctx) {
   return sort_unstable_by_impl(data, len, cmp_fn, ctx);
 }
+
+void MAKE_FUNC_NAME(partial_sort, 1k)(FFIOneKiloByte* data,
+                                      size_t len,
+                                      size_t k) {
+  std::partial_sort(reinterpret_cast<FFIOneKiloByteCpp*>(data),
+                    reinterpret_cast<FFIOneKiloByteCpp*>(data) + k,
+                    reinterpret_cast<FFIOneKiloByteCpp*>(data) + len);
+}
+
+uint32_t MAKE_FUNC_NAME(partial_sort,
+                        1k_by)(FFIOneKiloByte* data,
+                               size_t len,
+                               size_t k,
+                               CompResult (*cmp_fn)(const FFIOneKiloByte&,
+                                                    const FFIOneKiloByte&,
+                                                    uint8_t*),
+                               uint8_t* ctx) {
+  return partial_sort_by_impl(data, len, k, cmp_fn, ctx);
+}
 }  // extern "C"
@@ -53,6 +53,30 @@ macro_rules! make_cpp_sort_by {
     };
 }
 
+macro_rules! make_cpp_partial_sort_by {
+    ($name:ident, $data:expr, $k:expr, $compare:expr, $type:ty) => {
+        unsafe {
+            let cmp_fn_ctx =
+                std::mem::transmute::<*mut F, *mut u8>(Box::into_raw(Box::new($compare)));
+            let ret_code = $name(
+                $data.as_mut_ptr(),
+                $data.len(),
+                $k,
+                crate::ffi_util::rust_fn_cmp::<$type, F>,
+                cmp_fn_ctx,
+            );
+
+            // drop the compare function.
+            let cmp_fn_ptr = std::mem::transmute::<*mut u8, *mut F>(cmp_fn_ctx);
+            let _cmp_fn_box = Box::from_raw(cmp_fn_ptr);
+
+            if ret_code != 0 {
+                panic!("Panic in comparison function");
+            }
+        }
+    };
+}
+
 macro_rules! ffi_sort_impl {
     (
         $name:expr,
@@ -188,3 +212,224 @@ macro_rules! ffi_sort_impl {
         } // paste
     };
 }
+
+macro_rules! ffi_partial_sort_impl {
+    (
+        $name:expr,
+        $partial_sort_name_prefix:ident
+    ) => {
+        use std::cmp::Ordering;
+
+        use sort_test_tools::ffi_types::{CompResult, FFIOneKiloByte, FFIString, F128};
+
+        partial_sort_impl!($name);
+
+        paste::paste! {
+            extern "C" {
+                fn [<$partial_sort_name_prefix _i32>](data: *mut i32, len: usize, k: usize);
+                fn [<$partial_sort_name_prefix _i32_by>](
+                    data: *mut i32,
+                    len: usize,
+                    k: usize,
+                    cmp_fn: unsafe extern "C" fn(&i32, &i32, *mut u8) -> CompResult,
+                    cmp_fn_ctx: *mut u8,
+                ) -> u32;
+                fn [<$partial_sort_name_prefix _u64>](data: *mut u64, len: usize, k: usize);
+                fn [<$partial_sort_name_prefix _u64_by>](
+                    data: *mut u64,
+                    len: usize,
+                    k: usize,
+                    cmp_fn: unsafe extern "C" fn(&u64, &u64, *mut u8) -> CompResult,
+                    cmp_fn_ctx: *mut u8,
+                ) -> u32;
+                fn [<$partial_sort_name_prefix _ffi_string>](data: *mut FFIString, len: usize, k: usize);
+                fn [<$partial_sort_name_prefix _ffi_string_by>](
+                    data: *mut FFIString,
+                    len: usize,
+                    k: usize,
+                    cmp_fn: unsafe extern "C" fn(&FFIString, &FFIString, *mut u8) -> CompResult,
+                    cmp_fn_ctx: *mut u8,
+                ) -> u32;
+                fn [<$partial_sort_name_prefix _f128>](data: *mut F128, len: usize, k: usize);
+                fn [<$partial_sort_name_prefix _f128_by>](
+                    data: *mut F128,
+                    len: usize,
+                    k: usize,
+                    cmp_fn: unsafe extern "C" fn(&F128, &F128, *mut u8) -> CompResult,
+                    cmp_fn_ctx: *mut u8,
+                ) -> u32;
+                fn [<$partial_sort_name_prefix _1k>](data: *mut FFIOneKiloByte, len: usize, k: usize);
+                fn [<$partial_sort_name_prefix _1k_by>](
+                    data: *mut FFIOneKiloByte,
+                    len: usize,
+                    k: usize,
+                    cmp_fn: unsafe extern "C" fn(&FFIOneKiloByte, &FFIOneKiloByte, *mut u8) -> CompResult,
+                    cmp_fn_ctx: *mut u8,
+                ) -> u32;
+            }
+
+            // std::partial_sort has undefined behavior for k > len, match the Rust implementations
+            // instead.
+            fn check_k<T>(data: &[T], k: usize) {
+                if k > data.len() {
+                    panic!("partial_sort k {} greater than length of slice {}", k, data.len());
+                }
+            }
+
+            trait CppPartialSort: Sized {
+                fn partial_sort(data: &mut [Self], k: usize);
+                fn partial_sort_by<F: FnMut(&Self, &Self) -> Ordering>(
+                    data: &mut [Self],
+                    k: usize,
+                    compare: F,
+                );
+            }
+
+            impl<T> CppPartialSort for T {
+                default fn partial_sort(_data: &mut [T], _k: usize) {
+                    panic!("Type not supported");
+                }
+
+                default fn partial_sort_by<F: FnMut(&T, &T) -> Ordering>(
+                    _data: &mut [T],
+                    _k: usize,
+                    _compare: F,
+                ) {
+                    panic!("Type not supported");
+                }
+            }
+
+            impl CppPartialSort for i32 {
+                fn partial_sort(data: &mut [Self], k: usize) {
+                    check_k(data, k);
+                    unsafe {
+                        [<$partial_sort_name_prefix _i32>](data.as_mut_ptr(), data.len(), k);
+                    }
+                }
+
+                fn partial_sort_by<F: FnMut(&Self, &Self) -> Ordering>(
+                    data: &mut [Self],
+                    k: usize,
+                    compare: F,
+                ) {
+                    check_k(data, k);
+                    make_cpp_partial_sort_by!(
+                        [<$partial_sort_name_prefix _i32_by>],
+                        data,
+                        k,
+                        compare,
+                        Self
+                    );
+                }
+            }
+
+            impl CppPartialSort for u64 {
+                fn partial_sort(data: &mut [Self], k: usize) {
+                    check_k(data, k);
+                    unsafe {
+                        [<$partial_sort_name_prefix _u64>](data.as_mut_ptr(), data.len(), k);
+                    }
+                }
+
+                fn partial_sort_by<F: FnMut(&Self, &Self) -> Ordering>(
+                    data: &mut [Self],
+                    k: usize,
+                    compare: F,
+                ) {
+                    check_k(data, k);
+                    make_cpp_partial_sort_by!(
+                        [<$partial_sort_name_prefix _u64_by>],
+                        data,
+                        k,
+                        compare,
+                        Self
+                    );
+                }
+            }
+
+            impl CppPartialSort for FFIString {
+                fn partial_sort(data: &mut [Self], k: usize) {
+                    check_k(data, k);
+                    unsafe {
+                        [<$partial_sort_name_prefix _ffi_string>](data.as_mut_ptr(), data.len(), k);
+                    }
+                }
+
+                fn partial_sort_by<F: FnMut(&Self, &Self) -> Ordering>(
+                    data: &mut [Self],
+                    k: usize,
+                    compare: F,
+                ) {
+                    check_k(data, k);
+                    make_cpp_partial_sort_by!(
+                        [<$partial_sort_name_prefix _ffi_string_by>],
+                        data,
+                        k,
+                        compare,
+                        Self
+                    );
+                }
+            }
+
+            impl CppPartialSort for F128 {
+                fn partial_sort(data: &mut [Self], k: usize) {
+                    check_k(data, k);
+                    unsafe {
+                        [<$partial_sort_name_prefix _f128>](data.as_mut_ptr(), data.len(), k);
+                    }
+                }
+
+                fn partial_sort_by<F: FnMut(&Self, &Self) -> Ordering>(
+                    data: &mut [Self],
+                    k: usize,
+                    compare: F,
+                ) {
+                    check_k(data, k);
+                    make_cpp_partial_sort_by!(
+                        [<$partial_sort_name_prefix _f128_by>],
+                        data,
+                        k,
+                        compare,
+                        Self
+                    );
+                }
+            }
+
+            impl CppPartialSort for FFIOneKiloByte {
+                fn partial_sort(data: &mut [Self], k: usize) {
+                    check_k(data, k);
+                    unsafe {
+                        [<$partial_sort_name_prefix _1k>](data.as_mut_ptr(), data.len(), k);
+                    }
+                }
+
+                fn partial_sort_by<F: FnMut(&Self, &Self) -> Ordering>(
+                    data: &mut [Self],
+                    k: usize,
+                    compare: F,
+                ) {
+                    check_k(data, k);
+                    make_cpp_partial_sort_by!(
+                        [<$partial_sort_name_prefix _1k_by>],
+                        data,
+                        k,
+                        compare,
+                        Self
+                    );
+                }
+            }
+
+            pub fn partial_sort<T: Ord>(data: &mut [T], k: usize) {
+                CppPartialSort::partial_sort(data, k);
+            }
+
+            pub fn partial_sort_by<T, F: FnMut(&T, &T) -> Ordering>(
+                data: &mut [T],
+                k: usize,
+                compare: F,
+            ) {
+                CppPartialSort::partial_sort_by(data, k, compare);
+            }
+        } // paste
+    };
+}
@@ -19,8 +19,10 @@
     generic_const_exprs
 )]
 
+// Sorts with a fallible sort pass its name as `try_sort_by`, the others use the default of
+// `sort_test_tools::Sort::try_sort_by`.
 macro_rules! sort_impl {
-    ($name:expr) => {
+    ($name:expr $(, $try_sort_by:ident)?) => {
         pub struct SortImpl;
 
         impl sort_test_tools::Sort for SortImpl {
@@ -43,6 +45,16 @@ macro_rules! sort_impl {
             {
                 sort_by(arr, compare);
             }
+
+            $(
+                #[inline]
+                fn try_sort_by<T, E, F>(arr: &mut [T], compare: F) -> Result<(), E>
+                where
+                    F: FnMut(&T, &T) -> Result<Ordering, E>,
+                {
+                    $try_sort_by(arr, compare)
+                }
+            )?
         }
     };
 }
@@ -0,0 +1,46 @@
+//! Various argsort implementations, which return the permutation that sorts a slice.
+
+pub trait Argsort {
+    fn name() -> String;
+
+    fn argsort<T>(arr: &[T]) -> Vec<usize>
+    where
+        T: Ord;
+
+    fn argsort_by<T, F>(arr: &[T], compare: F) -> Vec<usize>
+    where
+        F: FnMut(&T, &T) -> std::cmp::Ordering;
+}
+
+macro_rules! argsort_impl {
+    ($name:expr) => {
+        pub struct ArgsortImpl;
+
+        impl crate::other::argsort::Argsort for ArgsortImpl {
+            fn name() -> String {
+                $name.into()
+            }
+
+            #[inline]
+            fn argsort<T>(arr: &[T]) -> Vec<usize>
+            where
+                T: Ord,
+            {
+                argsort(arr)
+            }
+
+            #[inline]
+            fn argsort_by<T, F>(arr: &[T], compare: F) -> Vec<usize>
+            where
+                F: FnMut(&T, &T) -> std::cmp::Ordering,
+            {
+                argsort_by(arr, compare)
+            }
+        }
+    };
+}
+
+pub mod rust_ipnsort;
+pub mod rust_ipnsort_stable;
+pub mod rust_std;
+pub mod rust_std_stable;
@@ -0,0 +1,13 @@
+use std::cmp::Ordering;
+
+use ipnsort;
+
+argsort_impl!("rust_ipnsort_argsort_stable");
+
+pub fn argsort<T: Ord>(data: &[T]) -> Vec<usize> {
+    ipnsort::stable::argsort(data)
+}
+
+pub fn argsort_by<T, F: FnMut(&T, &T) -> Ordering>(data: &[T], compare: F) -> Vec<usize> {
+    ipnsort::stable::argsort_by(data, compare)
+}
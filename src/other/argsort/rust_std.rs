@@ -0,0 +1,17 @@
+use std::cmp::Ordering;
+
+argsort_impl!("rust_std_argsort_unstable");
+
+pub fn argsort<T: Ord>(arr: &[T]) -> Vec<usize> {
+    argsort_by(arr, T::cmp)
+}
+
+pub fn argsort_by<T, F>(arr: &[T], mut compare: F) -> Vec<usize>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    // The std library has no argsort, sort the indices by the elements they point to.
+    let mut indices = (0..arr.len()).collect::<Vec<_>>();
+    indices.sort_unstable_by(|&a, &b| compare(&arr[a], &arr[b]));
+    indices
+}
@@ -28,3 +28,9 @@ pub mod partition_point;
 
 #[cfg(feature = "partition")]
 pub mod partition;
+
+#[cfg(feature = "partial_sort")]
+pub mod partial_sort;
+
+#[cfg(feature = "argsort")]
+pub mod argsort;
@@ -0,0 +1 @@
+ffi_partial_sort_impl!("cpp_std_libcxx_partial_sort", partial_sort_libcxx);
@@ -0,0 +1,52 @@
+//! Various partial sort implementations.
+
+pub trait PartialSort {
+    fn name() -> String;
+
+    fn partial_sort<T>(arr: &mut [T], k: usize)
+    where
+        T: Ord;
+
+    fn partial_sort_by<T, F>(arr: &mut [T], k: usize, compare: F)
+    where
+        F: FnMut(&T, &T) -> std::cmp::Ordering;
+}
+
+macro_rules! partial_sort_impl {
+    ($name:expr) => {
+        pub struct PartialSortImpl;
+
+        impl crate::other::partial_sort::PartialSort for PartialSortImpl {
+            fn name() -> String {
+                $name.into()
+            }
+
+            #[inline]
+            fn partial_sort<T>(arr: &mut [T], k: usize)
+            where
+                T: Ord,
+            {
+                partial_sort(arr, k);
+            }
+
+            #[inline]
+            fn partial_sort_by<T, F>(arr: &mut [T], k: usize, compare: F)
+            where
+                F: FnMut(&T, &T) -> std::cmp::Ordering,
+            {
+                partial_sort_by(arr, k, compare);
+            }
+        }
+    };
+}
+
+pub mod rust_ipnsort;
+pub mod rust_std;
+
+// Call C++ std::partial_sort via FFI.
+#[cfg(feature = "cpp_std_sys")]
+pub mod cpp_std_sys;
+
+// Call C++ std::partial_sort via FFI, using libc++.
+#[cfg(feature = "cpp_std_libcxx")]
+pub mod cpp_std_libcxx;
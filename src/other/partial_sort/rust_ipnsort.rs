@@ -0,0 +1,13 @@
+use std::cmp::Ordering;
+
+use ipnsort;
+
+partial_sort_impl!("rust_ipnsort_partial_sort");
+
+pub fn partial_sort<T: Ord>(data: &mut [T], k: usize) {
+    ipnsort::partial_sort(data, k);
+}
+
+pub fn partial_sort_by<T, F: FnMut(&T, &T) -> Ordering>(data: &mut [T], k: usize, compare: F) {
+    ipnsort::partial_sort_by(data, k, compare);
+}
@@ -0,0 +1,19 @@
+use std::cmp::Ordering;
+
+partial_sort_impl!("rust_std_partial_sort");
+
+pub fn partial_sort<T: Ord>(arr: &mut [T], k: usize) {
+    partial_sort_by(arr, k, T::cmp);
+}
+
+pub fn partial_sort_by<T, F>(arr: &mut [T], k: usize, mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    // The closest equivalent with the std library, select the k-th element and sort the prefix.
+    if k < arr.len() {
+        arr.select_nth_unstable_by(k, &mut compare);
+    }
+
+    arr[..k].sort_unstable_by(compare);
+}
@@ -0,0 +1,77 @@
+//! Three-way partition by Jon Bentley and M. Douglas McIlroy, from "Engineering a Sort Function".
+//!
+//! A Hoare style partition, that additionally swaps elements equal to the pivot to the two ends of
+//! the slice as it encounters them, and swaps both blocks of equal elements into the middle at the
+//! end. With few equal elements this costs little more than a regular Hoare partition.
+
+use core::cmp;
+use core::ptr;
+
+partition3_impl!("bentley_mcilroy_3way");
+
+fn partition3<T, F>(v: &mut [T], pivot: &T, is_less: &mut F) -> (usize, usize)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let len = v.len();
+    let arr_ptr = v.as_mut_ptr();
+
+    // Layout while scanning:
+    //
+    // [ eq | lt | unknown | gt | eq ]
+    //      ^    ^         ^    ^
+    //   eq_l    l         r    eq_r
+    let mut eq_l = 0;
+    let mut l = 0;
+    let mut r = len;
+    let mut eq_r = len;
+
+    // SAFETY: All indices stay within `0..len`, `eq_l <= l` and `r <= eq_r` hold throughout, and
+    // the final block swaps are limited to the shorter of the two blocks so they don't overlap.
+    unsafe {
+        loop {
+            while l < r && !is_less(pivot, &*arr_ptr.add(l)) {
+                if !is_less(&*arr_ptr.add(l), pivot) {
+                    ptr::swap(arr_ptr.add(eq_l), arr_ptr.add(l));
+                    eq_l += 1;
+                }
+                l += 1;
+            }
+
+            while l < r && !is_less(&*arr_ptr.add(r - 1), pivot) {
+                if !is_less(pivot, &*arr_ptr.add(r - 1)) {
+                    eq_r -= 1;
+                    ptr::swap(arr_ptr.add(r - 1), arr_ptr.add(eq_r));
+                }
+                r -= 1;
+            }
+
+            if l >= r {
+                break;
+            }
+
+            // v[l] is greater and v[r - 1] less than the pivot.
+            ptr::swap(arr_ptr.add(l), arr_ptr.add(r - 1));
+            l += 1;
+            r -= 1;
+        }
+
+        // With an inconsistent `is_less` the last swap can leave `l` one past `r`, but all
+        // elements before `l` are still considered less and all from `l` on greater.
+        let lt_len = l - eq_l;
+        let gt_len = eq_r - l;
+        let eq_r_len = len - eq_r;
+
+        let left_swap_len = cmp::min(eq_l, lt_len);
+        ptr::swap_nonoverlapping(arr_ptr, arr_ptr.add(l - left_swap_len), left_swap_len);
+
+        let right_swap_len = cmp::min(gt_len, eq_r_len);
+        ptr::swap_nonoverlapping(
+            arr_ptr.add(l),
+            arr_ptr.add(len - right_swap_len),
+            right_swap_len,
+        );
+
+        (lt_len, eq_l + eq_r_len)
+    }
+}
@@ -29,9 +29,16 @@ where
             while l < r && !is_less(&*r.sub(1), pivot) {
                 r = r.sub(1);
             }
+
+            // Are we done? Checked before moving `r`, which must not go below the start of an
+            // empty slice.
+            if l >= r {
+                break;
+            }
             r = r.sub(1);
 
-            // Are we done?
+            // With an inconsistent `is_less` both scans can stop on the same element, which must
+            // not be swapped with itself.
             if l >= r {
                 break;
             }
@@ -52,11 +52,17 @@ where
             while l_ptr < r_ptr && !is_less(&*r_ptr.sub(1), pivot) {
                 r_ptr = r_ptr.sub(1);
             }
+
+            // Are we done? Checked before moving `r_ptr`, which must not go below the start of an
+            // empty slice.
+            if l_ptr >= r_ptr {
+                break;
+            }
             r_ptr = r_ptr.sub(1);
 
-            // Are we done?
+            // With an inconsistent `is_less` both scans can stop on the same element, which must
+            // not be swapped with itself.
             if l_ptr >= r_ptr {
-                assert!(l_ptr != r_ptr);
                 break;
             }
 
@@ -45,6 +45,10 @@ where
 {
     let len = v.len();
 
+    if len == 0 {
+        return 0;
+    }
+
     let arr_ptr = v.as_mut_ptr();
 
     // SAFETY: TODO
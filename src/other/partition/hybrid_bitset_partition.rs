@@ -392,8 +392,9 @@ where
         cyclic_permutation_swap_loop!(
             {
                 // continue_check
+                // Only the first `ge_count` entries of `ge_idx_ptr` are written by the loop above.
                 i = i.wrapping_add(1);
-                i < lt_count && (*ge_idx_ptr.add(i) as usize) < lt_count
+                i < ge_count && (*ge_idx_ptr.add(i) as usize) < lt_count
             },
             {
                 // next_left
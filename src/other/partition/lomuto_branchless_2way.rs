@@ -1,3 +1,4 @@
+use core::cmp;
 use core::mem::MaybeUninit;
 use core::ptr;
 
@@ -29,6 +30,21 @@ pub unsafe fn branchless_swap_overlapping<T>(a_ptr: *mut T, b_ptr: *mut T, shoul
     ptr::copy(tmp.as_ptr(), b_ptr, 1);
 }
 
+/// Moves the `lt_len` elements at `block_ptr` down to `fill_ptr`, where only elements that are not
+/// less than the pivot are in between. The two ranges may overlap, so only as many elements as
+/// needed are swapped.
+///
+/// SAFETY: The caller must ensure that `fill_ptr <= block_ptr` and that
+/// `fill_ptr..block_ptr.add(lt_len)` is valid for reads and writes.
+#[inline(always)]
+pub unsafe fn swap_lt_block_down<T>(fill_ptr: *mut T, block_ptr: *mut T, lt_len: usize) {
+    let ge_len = block_ptr.sub_ptr(fill_ptr);
+    let swap_len = cmp::min(ge_len, lt_len);
+
+    // With more less than elements, the ge elements are swapped with the last of them.
+    ptr::swap_nonoverlapping(fill_ptr, block_ptr.add(lt_len - swap_len), swap_len);
+}
+
 fn partition<T, F>(v: &mut [T], pivot: &T, is_less: &mut F) -> usize
 where
     F: FnMut(&T, &T) -> bool,
@@ -65,7 +81,7 @@ where
         let is_less_count_a = fill_ptr_a.sub_ptr(arr_ptr);
         let is_less_count_b = fill_ptr_b.sub_ptr(arr_ptr) - len_div_n;
 
-        ptr::swap_nonoverlapping(
+        swap_lt_block_down(
             arr_ptr.add(is_less_count_a),
             arr_ptr.add(len_div_n),
             is_less_count_b,
@@ -0,0 +1,50 @@
+//! Branchless Lomuto style three-way partition.
+//!
+//! Grows the regions of elements less than and equal to the pivot at the front of the slice in a
+//! single pass. Each element is swapped to the end of the eq region, and from there over the eq
+//! region if it's less than the pivot. Both swaps happen unconditionally, only their positions
+//! depend on the comparison results, which avoids branch misprediction on random inputs.
+
+use core::ptr;
+
+partition3_impl!("lomuto_branchless_3way");
+
+fn partition3<T, F>(v: &mut [T], pivot: &T, is_less: &mut F) -> (usize, usize)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let len = v.len();
+    let arr_ptr = v.as_mut_ptr();
+
+    // Layout while scanning:
+    //
+    // [ lt | eq | gt | unknown ]
+    //      ^    ^    ^
+    //     lt    le   i
+    let mut lt = 0;
+    let mut le = 0;
+
+    // SAFETY: `lt <= le <= i < len` holds throughout, `ptr::swap` allows equal pointers.
+    unsafe {
+        for i in 0..len {
+            let elem_ptr = arr_ptr.add(i);
+
+            // Both comparisons are done unconditionally. Or-ing in `is_lt` keeps `lt <= le` even
+            // if `is_less` is inconsistent.
+            let is_lt = is_less(&*elem_ptr, pivot);
+            let is_le = is_lt | !is_less(pivot, &*elem_ptr);
+
+            // For gt elements this swaps two gt elements, otherwise it moves the element to `le`.
+            ptr::swap(arr_ptr.add(le), elem_ptr);
+
+            // For lt elements this swaps the first eq element to `le`, otherwise it's a no-op.
+            let lt_target = if is_lt { lt } else { le };
+            ptr::swap(arr_ptr.add(le), arr_ptr.add(lt_target));
+
+            lt += is_lt as usize;
+            le += is_le as usize;
+        }
+    }
+
+    (lt, le - lt)
+}
@@ -1,6 +1,8 @@
 use core::mem::MaybeUninit;
 use core::ptr;
 
+use crate::other::partition::lomuto_branchless_2way::swap_lt_block_down;
+
 partition_impl!("lomuto_branchless_4way");
 
 /// Swap two values in array pointed to by a_ptr and b_ptr if b is less than a.
@@ -85,21 +87,21 @@ where
 
         let mut is_less_count = is_less_count_a;
 
-        ptr::swap_nonoverlapping(
+        swap_lt_block_down(
             arr_ptr.add(is_less_count),
             arr_ptr.add(len_div_n),
             is_less_count_b,
         );
         is_less_count += is_less_count_b;
 
-        ptr::swap_nonoverlapping(
+        swap_lt_block_down(
             arr_ptr.add(is_less_count),
             arr_ptr.add(len_div_n * 2),
             is_less_count_c,
         );
         is_less_count += is_less_count_c;
 
-        ptr::swap_nonoverlapping(
+        swap_lt_block_down(
             arr_ptr.add(is_less_count),
             arr_ptr.add(len_div_n * 3),
             is_less_count_d,
@@ -44,6 +44,48 @@ macro_rules! partition_impl {
     };
 }
 
+/// Three-way partition, into the elements less than, equal to and greater than the pivot.
+pub trait Partition3 {
+    fn name() -> String;
+
+    /// Returns the number of elements less than and equal to `pivot`, as `(lt_len, eq_len)`.
+    fn partition3<T>(arr: &mut [T], pivot: &T) -> (usize, usize)
+    where
+        T: Ord;
+
+    fn partition3_by<T, F>(arr: &mut [T], pivot: &T, is_less: &mut F) -> (usize, usize)
+    where
+        F: FnMut(&T, &T) -> bool;
+}
+
+macro_rules! partition3_impl {
+    ($name:expr) => {
+        pub struct Partition3Impl;
+
+        impl crate::other::partition::Partition3 for Partition3Impl {
+            fn name() -> String {
+                $name.into()
+            }
+
+            #[inline]
+            fn partition3<T>(arr: &mut [T], pivot: &T) -> (usize, usize)
+            where
+                T: Ord,
+            {
+                partition3(arr, pivot, &mut |a, b| a.lt(b))
+            }
+
+            #[inline]
+            fn partition3_by<T, F>(arr: &mut [T], pivot: &T, is_less: &mut F) -> (usize, usize)
+            where
+                F: FnMut(&T, &T) -> bool,
+            {
+                partition3(arr, pivot, is_less)
+            }
+        }
+    };
+}
+
 /// Returns a guaranteed non-null pointer to an allocation suitable for `layout`.
 ///
 /// As long as this function is called consecutively with the same `layout`, it will re-use the same
@@ -92,6 +134,7 @@ pub fn get_or_alloc_tls_scratch(layout: alloc::Layout) -> NonNull<u8> {
     })
 }
 
+pub mod bentley_mcilroy_3way;
 pub mod hoare_block;
 pub mod hoare_block_butterfly;
 pub mod hoare_block_opt;
@@ -105,11 +148,15 @@ pub mod hybrid_bitset_partition;
 pub mod hybrid_block_partition;
 pub mod lomuto_branchless;
 pub mod lomuto_branchless_2way;
+pub mod lomuto_branchless_3way;
 pub mod lomuto_branchless_4way;
 pub mod lomuto_branchless_cyclic;
 pub mod lomuto_branchless_cyclic_opt;
 pub mod lomuto_branchy;
 pub mod lomuto_iterleaved;
+#[cfg(feature = "partition_simd")]
+pub mod simd_compress;
 pub mod small_partition;
 pub mod stable_2side_fill;
 pub mod sum_is_less;
+pub mod two_pass_3way;
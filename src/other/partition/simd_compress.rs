@@ -0,0 +1,51 @@
+//! Partition with comparison-mask compress-stores, built on `core::simd`, see `ipnsort::simd`.
+//!
+//! A vector of elements is compared with the pivot at once, and the resulting mask selects a
+//! shuffle that moves the lesser elements to the front of the vector and the others to the back.
+//! The shuffled vector is stored at both ends of the partitioned area.
+//!
+//! Only `partition` of `i32`, `u32` and `u64` uses SIMD, `partition_by` can't know if `is_less`
+//! is the natural order. Everything else falls back to `lomuto_branchless_cyclic`. `f32` is
+//! supported by the kernel, but can't be passed to the `Ord` based `Partition` trait.
+
+use crate::other::partition::{lomuto_branchless_cyclic, Partition};
+
+pub struct PartitionImpl;
+
+impl Partition for PartitionImpl {
+    fn name() -> String {
+        "simd_compress".into()
+    }
+
+    #[inline]
+    fn partition<T>(arr: &mut [T], pivot: &T) -> usize
+    where
+        T: Ord,
+    {
+        <T as SimdCompress>::partition(arr, pivot)
+    }
+
+    #[inline]
+    fn partition_by<T, F>(arr: &mut [T], pivot: &T, is_less: &mut F) -> usize
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        lomuto_branchless_cyclic::PartitionImpl::partition_by(arr, pivot, is_less)
+    }
+}
+
+trait SimdCompress: Sized {
+    fn partition(v: &mut [Self], pivot: &Self) -> usize;
+}
+
+impl<T: Ord> SimdCompress for T {
+    default fn partition(v: &mut [T], pivot: &T) -> usize {
+        lomuto_branchless_cyclic::PartitionImpl::partition(v, pivot)
+    }
+}
+
+impl<T: Ord + ipnsort::simd::SimdPartition> SimdCompress for T {
+    fn partition(v: &mut [T], pivot: &T) -> usize {
+        ipnsort::simd::partition(v, pivot)
+    }
+}
@@ -130,15 +130,68 @@ where
         ptr::copy_nonoverlapping(scratch_ptr, arr_ptr, lt_count);
 
         // Copy the elements that were equal or more from the buf into v and reverse them.
-        let rev_buf_ptr = scratch_ptr.add(len - 1);
         for i in 0..len - lt_count {
-            ptr::copy_nonoverlapping(rev_buf_ptr.sub(i), arr_ptr.add(lt_count + i), 1);
+            ptr::copy_nonoverlapping(scratch_ptr.add(len - 1 - i), arr_ptr.add(lt_count + i), 1);
         }
 
         lt_count
     }
 }
 
+/// Same as [`PartitionImpl`], but uses `buf` as scratch memory instead of the thread-local scratch
+/// allocation, and never allocates.
+///
+/// `buf` may have any length. If it can't hold all of `v`, both halves are partitioned
+/// recursively and the inner pieces rotated, which is *O*(*n* \* log(*n*)) instead of *O*(*n*).
+pub fn partition_with_buffer<T: Ord>(v: &mut [T], buf: &mut [MaybeUninit<T>], pivot: &T) -> usize {
+    stable_partition_with_buffer(v, buf, pivot, &mut |a, b| a.lt(b))
+}
+
+/// Same as [`partition_with_buffer`], with the comparison function `is_less`.
+pub fn partition_by_with_buffer<T, F>(
+    v: &mut [T],
+    buf: &mut [MaybeUninit<T>],
+    pivot: &T,
+    is_less: &mut F,
+) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    stable_partition_with_buffer(v, buf, pivot, is_less)
+}
+
+fn stable_partition_with_buffer<T, F>(
+    v: &mut [T],
+    buf: &mut [MaybeUninit<T>],
+    pivot: &T,
+    is_less: &mut F,
+) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let len = v.len();
+
+    if len <= buf.len() {
+        return stable_partition(v, buf, pivot, is_less);
+    }
+
+    if len == 1 {
+        // Only reachable with an empty `buf`.
+        return is_less(&v[0], pivot) as usize;
+    }
+
+    let mid = len / 2;
+    let (left, right) = v.split_at_mut(mid);
+    let left_lt_count = stable_partition_with_buffer(left, buf, pivot, is_less);
+    let right_lt_count = stable_partition_with_buffer(right, buf, pivot, is_less);
+
+    // Move the elements less than the pivot of the right half in front of the other elements of
+    // the left half, without changing the order within either of them.
+    v[left_lt_count..(mid + right_lt_count)].rotate_left(mid - left_lt_count);
+
+    left_lt_count + right_lt_count
+}
+
 fn partition<T, F>(v: &mut [T], pivot: &T, is_less: &mut F) -> usize
 where
     F: FnMut(&T, &T) -> bool,
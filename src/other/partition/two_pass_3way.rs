@@ -0,0 +1,25 @@
+//! Three-way partition as two passes of a regular two-way partition, like `ipnsort::partition3`.
+//!
+//! The first pass separates the elements less than the pivot, the second pass separates the
+//! remaining elements equal to the pivot from the greater ones. The greater elements are visited
+//! twice, but each pass only does one comparison per element and can use a branchless partition.
+
+use crate::other::partition::{lomuto_branchless_cyclic, Partition};
+
+partition3_impl!("two_pass_3way");
+
+fn partition3<T, F>(v: &mut [T], pivot: &T, is_less: &mut F) -> (usize, usize)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let lt_len = lomuto_branchless_cyclic::PartitionImpl::partition_by(v, pivot, is_less);
+
+    // Of the remaining elements, the ones not greater than `pivot` are equal to it.
+    let eq_len = lomuto_branchless_cyclic::PartitionImpl::partition_by(
+        &mut v[lt_len..],
+        pivot,
+        &mut |elem, pivot| !is_less(pivot, elem),
+    );
+
+    (lt_len, eq_len)
+}
@@ -1,9 +1,21 @@
+use core::mem;
+
 partition_point_impl!("branchless_clean");
 
 pub fn partition_point<T, P>(arr: &[T], mut pred: P) -> usize
 where
     P: FnMut(&T) -> bool,
 {
+    // Only slices of zero-sized types can be `usize::MAX` long, which would overflow `n` below.
+    // All their elements are the same, so `pred` has to give the same answer for each of them.
+    if mem::size_of::<T>() == 0 {
+        return if !arr.is_empty() && pred(&arr[0]) {
+            arr.len()
+        } else {
+            0
+        };
+    }
+
     // There are arr.len() + 1 possible outcomes of our search.
     // Invariant: [i+1, i+1+n) contains our desired result.
     let mut n = arr.len() + 1;
@@ -36,6 +48,6 @@ where
         n -= n / 2;
     }
 
-    // [i+1, i+1+n) contains our result, and n == 1.
-    i + 1
+    // [i+1, i+1+n) contains our result, and n == 1. `i` is still -1 if the result is 0.
+    i.wrapping_add(1)
 }
@@ -0,0 +1,33 @@
+use std::cmp::Ordering;
+use std::mem::MaybeUninit;
+
+use ipnsort;
+
+sort_impl!("rust_ipnsort_stable", try_sort_by);
+
+pub fn sort<T: Ord>(data: &mut [T]) {
+    ipnsort::stable::sort(data);
+}
+
+pub fn sort_by<T, F: FnMut(&T, &T) -> Ordering>(data: &mut [T], compare: F) {
+    ipnsort::stable::sort_by(data, compare);
+}
+
+pub fn sort_with_buffer<T: Ord>(data: &mut [T], buf: &mut [MaybeUninit<T>]) {
+    ipnsort::stable::sort_with_buffer(data, buf);
+}
+
+pub fn sort_by_with_buffer<T, F: FnMut(&T, &T) -> Ordering>(
+    data: &mut [T],
+    buf: &mut [MaybeUninit<T>],
+    compare: F,
+) {
+    ipnsort::stable::sort_by_with_buffer(data, buf, compare);
+}
+
+pub fn try_sort_by<T, E, F: FnMut(&T, &T) -> Result<Ordering, E>>(
+    data: &mut [T],
+    compare: F,
+) -> Result<(), E> {
+    ipnsort::stable::try_sort_by(data, compare)
+}
@@ -1,10 +1,11 @@
 #![allow(unused_unsafe)]
 
-use std::cmp::Ordering;
-use std::mem::{self, size_of};
+use std::cmp::{self, Ordering};
+use std::convert::Infallible;
+use std::mem::{self, size_of, MaybeUninit};
 use std::ptr;
 
-sort_impl!("rust_std_stable");
+sort_impl!("rust_std_stable", try_sort_by);
 
 /// Sorts the slice.
 ///
@@ -37,7 +38,7 @@ pub fn sort<T>(arr: &mut [T])
 where
     T: Ord,
 {
-    merge_sort(arr, |a, b| a.lt(b));
+    into_ok(merge_sort(arr, |a, b| Ok(a.lt(b))));
 }
 
 /// Sorts the slice with a comparator function.
@@ -90,7 +91,69 @@ pub fn sort_by<T, F>(arr: &mut [T], mut compare: F)
 where
     F: FnMut(&T, &T) -> Ordering,
 {
-    merge_sort(arr, |a, b| compare(a, b) == Ordering::Less);
+    into_ok(merge_sort(arr, |a, b| Ok(compare(a, b) == Ordering::Less)));
+}
+
+/// Sorts the slice with a fallible comparator function.
+///
+/// Same as [`sort_by`], but stops at the first error returned by `compare` and returns it. The
+/// slice then still holds all of its original elements, in an unspecified order.
+#[inline]
+pub fn try_sort_by<T, E, F>(arr: &mut [T], mut compare: F) -> Result<(), E>
+where
+    F: FnMut(&T, &T) -> Result<Ordering, E>,
+{
+    merge_sort(arr, |a, b| Ok(compare(a, b)? == Ordering::Less))
+}
+
+/// Sorts the slice using `buf` as scratch memory, preserving the order of equal elements.
+///
+/// This sort is stable (i.e., does not reorder equal elements) and does not allocate. It is
+/// *O*(*n* \* log(*n*)) worst-case if `buf` can hold at least half of the slice. With a shorter, or
+/// even empty, `buf` the merges that don't fit are done in-place, which is
+/// *O*(*n* \* log<sup>2</sup>(*n*)) worst-case.
+///
+/// # Examples
+///
+/// ```
+/// use std::mem::MaybeUninit;
+///
+/// let mut v = [-5, 4, 1, -3, 2];
+/// let mut buf = [MaybeUninit::uninit(); 2];
+///
+/// sort_comp::stable::rust_std::sort_with_buffer(&mut v, &mut buf);
+/// assert!(v == [-5, -3, 1, 2, 4]);
+/// ```
+#[inline]
+pub fn sort_with_buffer<T>(arr: &mut [T], buf: &mut [MaybeUninit<T>])
+where
+    T: Ord,
+{
+    into_ok(merge_sort_with_buffer(arr, buf, |a, b| Ok(a.lt(b))));
+}
+
+/// Sorts the slice with a comparator function using `buf` as scratch memory, preserving the order
+/// of equal elements.
+///
+/// See [`sort_by`] for the requirements on the comparator function and [`sort_with_buffer`] for
+/// the requirements on `buf`.
+#[inline]
+pub fn sort_by_with_buffer<T, F>(arr: &mut [T], buf: &mut [MaybeUninit<T>], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    into_ok(merge_sort_with_buffer(arr, buf, |a, b| {
+        Ok(compare(a, b) == Ordering::Less)
+    }));
+}
+
+/// Unwraps the result of a sort that can't fail.
+#[inline(always)]
+fn into_ok(res: Result<(), Infallible>) {
+    match res {
+        Ok(()) => {}
+        Err(never) => match never {},
+    }
 }
 
 /// This merge sort borrows some (but not all) ideas from TimSort, which is described in detail
@@ -105,19 +168,55 @@ where
 /// 2. for every `i` in `2..runs.len()`: `runs[i - 2].len > runs[i - 1].len + runs[i].len`
 ///
 /// The invariants ensure that the total running time is *O*(*n* \* log(*n*)) worst-case.
+///
+/// The first error returned by `is_less` stops the sort, and is returned.
 #[cfg(not(no_global_oom_handling))]
-fn merge_sort<T, F>(v: &mut [T], mut is_less: F)
+fn merge_sort<T, E, F>(v: &mut [T], is_less: F) -> Result<(), E>
 where
-    F: FnMut(&T, &T) -> bool,
+    F: FnMut(&T, &T) -> Result<bool, E>,
 {
-    // Slices of up to this length get sorted using insertion sort.
-    const MAX_INSERTION: usize = 20;
-    // Very short runs are extended using insertion sort to span at least this many elements.
-    const MIN_RUN: usize = 10;
+    // Allocate a buffer to use as scratch memory. We keep the length 0 so we can keep in it
+    // shallow copies of the contents of `v` without risking the dtors running on copies if
+    // `is_less` panics. When merging two sorted runs, this buffer holds a copy of the shorter run,
+    // which will always have length at most `len / 2`. Short arrays get sorted in-place via
+    // insertion sort, and `Vec::with_capacity(0)` doesn't allocate.
+    let buf_len = if v.len() <= MAX_INSERTION {
+        0
+    } else {
+        v.len() / 2
+    };
+    let mut buf = Vec::with_capacity(buf_len);
+
+    merge_sort_with_buffer(v, buf.spare_capacity_mut(), is_less)
+}
+
+// Slices of up to this length get sorted using insertion sort.
+const MAX_INSERTION: usize = 20;
 
+// Very short runs are extended using insertion sort to span at least this many elements.
+const MIN_RUN: usize = 10;
+
+// Upper bound for the number of pending runs. The invariants enforced by `merge_sort` make the run
+// lengths grow at least as fast as the Fibonacci sequence, from the top to the bottom of the
+// stack, and the 94th Fibonacci number already exceeds `u64::MAX`.
+const MAX_PENDING_RUNS: usize = 96;
+
+/// See [`merge_sort`], with `buf` as scratch memory instead of an allocation.
+///
+/// `buf` may have any length, but if it can't hold `v.len() / 2` elements some of the merges have
+/// to be done in-place, which is slower. The pending runs are kept on the stack, so this function
+/// never allocates.
+fn merge_sort_with_buffer<T, E, F>(
+    v: &mut [T],
+    buf: &mut [MaybeUninit<T>],
+    mut is_less: F,
+) -> Result<(), E>
+where
+    F: FnMut(&T, &T) -> Result<bool, E>,
+{
     // Sorting has no meaningful behavior on zero-sized types.
     if size_of::<T>() == 0 {
-        return;
+        return Ok(());
     }
 
     let len = v.len();
@@ -126,23 +225,18 @@ where
     if len <= MAX_INSERTION {
         if len >= 2 {
             for i in (0..len - 1).rev() {
-                insert_head(&mut v[i..], &mut is_less);
+                insert_head(&mut v[i..], &mut is_less)?;
             }
         }
-        return;
+        return Ok(());
     }
 
-    // Allocate a buffer to use as scratch memory. We keep the length 0 so we can keep in it
-    // shallow copies of the contents of `v` without risking the dtors running on copies if
-    // `is_less` panics. When merging two sorted runs, this buffer holds a copy of the shorter run,
-    // which will always have length at most `len / 2`.
-    let mut buf = Vec::with_capacity(len / 2);
-
     // In order to identify natural runs in `v`, we traverse it backwards. That might seem like a
     // strange decision, but consider the fact that merges more often go in the opposite direction
     // (forwards). According to benchmarks, merging forwards is slightly faster than merging
     // backwards. To conclude, identifying runs by traversing backwards improves performance.
-    let mut runs = vec![];
+    let mut runs = [Run { start: 0, len: 0 }; MAX_PENDING_RUNS];
+    let mut runs_len = 0;
     let mut end = len;
     while end > 0 {
         // Find the next natural run, and reverse it if it's strictly descending.
@@ -150,13 +244,14 @@ where
         if start > 0 {
             start -= 1;
             unsafe {
-                if is_less(v.get_unchecked(start + 1), v.get_unchecked(start)) {
-                    while start > 0 && is_less(v.get_unchecked(start), v.get_unchecked(start - 1)) {
+                if is_less(v.get_unchecked(start + 1), v.get_unchecked(start))? {
+                    while start > 0 && is_less(v.get_unchecked(start), v.get_unchecked(start - 1))?
+                    {
                         start -= 1;
                     }
                     v[start..end].reverse();
                 } else {
-                    while start > 0 && !is_less(v.get_unchecked(start), v.get_unchecked(start - 1))
+                    while start > 0 && !is_less(v.get_unchecked(start), v.get_unchecked(start - 1))?
                     {
                         start -= 1;
                     }
@@ -168,38 +263,40 @@ where
         // merge sort on short sequences, so this significantly improves performance.
         while start > 0 && end - start < MIN_RUN {
             start -= 1;
-            insert_head(&mut v[start..end], &mut is_less);
+            insert_head(&mut v[start..end], &mut is_less)?;
         }
 
         // Push this run onto the stack.
-        runs.push(Run {
+        runs[runs_len] = Run {
             start,
             len: end - start,
-        });
+        };
+        runs_len += 1;
         end = start;
 
         // Merge some pairs of adjacent runs to satisfy the invariants.
-        while let Some(r) = collapse(&runs) {
+        while let Some(r) = collapse(&runs[..runs_len]) {
             let left = runs[r + 1];
             let right = runs[r];
-            unsafe {
-                merge(
-                    &mut v[left.start..right.start + right.len],
-                    left.len,
-                    buf.as_mut_ptr(),
-                    &mut is_less,
-                );
-            }
+            merge_with_scratch(
+                &mut v[left.start..right.start + right.len],
+                left.len,
+                buf,
+                &mut is_less,
+            )?;
             runs[r] = Run {
                 start: left.start,
                 len: left.len + right.len,
             };
-            runs.remove(r + 1);
+            runs.copy_within((r + 2)..runs_len, r + 1);
+            runs_len -= 1;
         }
     }
 
     // Finally, exactly one run must remain in the stack.
-    debug_assert!(runs.len() == 1 && runs[0].start == 0 && runs[0].len == len);
+    debug_assert!(runs_len == 1 && runs[0].start == 0 && runs[0].len == len);
+
+    return Ok(());
 
     // Examines the stack of runs and identifies the next pair of runs to merge. More specifically,
     // if `Some(r)` is returned, that means `runs[r]` and `runs[r + 1]` must be merged next. If the
@@ -244,12 +341,11 @@ where
 /// Inserts `v[0]` into pre-sorted sequence `v[1..]` so that whole `v[..]` becomes sorted.
 ///
 /// This is the integral subroutine of insertion sort.
-#[cfg(not(no_global_oom_handling))]
-fn insert_head<T, F>(v: &mut [T], is_less: &mut F)
+fn insert_head<T, E, F>(v: &mut [T], is_less: &mut F) -> Result<(), E>
 where
-    F: FnMut(&T, &T) -> bool,
+    F: FnMut(&T, &T) -> Result<bool, E>,
 {
-    if v.len() >= 2 && is_less(&v[1], &v[0]) {
+    if v.len() >= 2 && is_less(&v[1], &v[0])? {
         unsafe {
             // There are three ways to implement insertion here:
             //
@@ -287,7 +383,7 @@ where
             ptr::copy_nonoverlapping(&v[1], &mut v[0], 1);
 
             for i in 2..v.len() {
-                if !is_less(&v[i], &*tmp) {
+                if !is_less(&v[i], &*tmp)? {
                     break;
                 }
                 ptr::copy_nonoverlapping(&v[i], &mut v[i - 1], 1);
@@ -297,6 +393,8 @@ where
         }
     }
 
+    return Ok(());
+
     // When dropped, copies from `src` into `dest`.
     struct InsertionHole<T> {
         src: *const T,
@@ -312,6 +410,84 @@ where
     }
 }
 
+/// Merges non-decreasing runs `v[..mid]` and `v[mid..]`, using `buf` as temporary storage.
+///
+/// If `buf` can't hold the shorter run, the longer run is split in half and the matching split
+/// point of the shorter run is found with a binary search. Rotating the two inner pieces leaves two
+/// independent smaller merges, which are repeated until the pieces fit into `buf`.
+fn merge_with_scratch<T, E, F>(
+    v: &mut [T],
+    mid: usize,
+    buf: &mut [MaybeUninit<T>],
+    is_less: &mut F,
+) -> Result<(), E>
+where
+    F: FnMut(&T, &T) -> Result<bool, E>,
+{
+    let len = v.len();
+
+    if mid == 0 || mid == len {
+        return Ok(());
+    }
+
+    if cmp::min(mid, len - mid) <= buf.len() {
+        // SAFETY: Both runs are non-empty, and `buf` can hold the shorter of the two runs.
+        return unsafe { merge(v, mid, buf.as_mut_ptr() as *mut T, is_less) };
+    }
+
+    if len == 2 {
+        // Only reachable with an empty `buf`, splitting a run of length 1 makes no progress.
+        if is_less(&v[1], &v[0])? {
+            v.swap(0, 1);
+        }
+        return Ok(());
+    }
+
+    let (left_cut, right_cut) = if mid >= len - mid {
+        // All elements of `v[mid..right_cut]` are less than `v[left_cut..mid]`, moving them in
+        // front of it preserves stability.
+        let left_cut = mid / 2;
+        let (left, right) = v.split_at(mid);
+        let right_cut = mid + try_partition_point(right, |x| is_less(x, &left[left_cut]))?;
+        (left_cut, right_cut)
+    } else {
+        // All elements of `v[left_cut..mid]` are greater than `v[mid..right_cut]`, moving them
+        // behind it preserves stability.
+        let right_cut = mid + (len - mid) / 2;
+        let (left, right) = v.split_at(mid);
+        let left_cut = try_partition_point(left, |x| Ok(!is_less(&right[right_cut - mid], x)?))?;
+        (left_cut, right_cut)
+    };
+
+    v[left_cut..right_cut].rotate_left(mid - left_cut);
+
+    let new_mid = left_cut + (right_cut - mid);
+    let (left, right) = v.split_at_mut(new_mid);
+    merge_with_scratch(left, left_cut, buf, is_less)?;
+    merge_with_scratch(right, mid - left_cut, buf, is_less)
+}
+
+/// Returns the index of the first element of `v` for which `pred` returns false, `v` must be
+/// partitioned by `pred`. Same as [`slice::partition_point`], but stops at the first error returned
+/// by `pred`.
+fn try_partition_point<T, E, P>(v: &[T], mut pred: P) -> Result<usize, E>
+where
+    P: FnMut(&T) -> Result<bool, E>,
+{
+    let mut left = 0;
+    let mut right = v.len();
+    while left < right {
+        let mid = left + (right - left) / 2;
+        if pred(&v[mid])? {
+            left = mid + 1;
+        } else {
+            right = mid;
+        }
+    }
+
+    Ok(left)
+}
+
 /// Merges non-decreasing runs `v[..mid]` and `v[mid..]` using `buf` as temporary storage, and
 /// stores the result into `v[..]`.
 ///
@@ -319,10 +495,9 @@ where
 ///
 /// The two slices must be non-empty and `mid` must be in bounds. Buffer `buf` must be long enough
 /// to hold a copy of the shorter slice. Also, `T` must not be a zero-sized type.
-#[cfg(not(no_global_oom_handling))]
-unsafe fn merge<T, F>(v: &mut [T], mid: usize, buf: *mut T, is_less: &mut F)
+unsafe fn merge<T, E, F>(v: &mut [T], mid: usize, buf: *mut T, is_less: &mut F) -> Result<(), E>
 where
-    F: FnMut(&T, &T) -> bool,
+    F: FnMut(&T, &T) -> Result<bool, E>,
 {
     let len = v.len();
     let v = v.as_mut_ptr();
@@ -373,7 +548,7 @@ where
 
             // SAFETY: left and right must be valid and part of v same for out.
             unsafe {
-                let is_l = is_less(&*right, &**left);
+                let is_l = is_less(&*right, &**left)?;
                 let to_copy = if is_l { right } else { *left };
                 ptr::copy_nonoverlapping(to_copy, *out, 1);
                 *out = out.add(1);
@@ -405,7 +580,7 @@ where
 
             // SAFETY: left and right must be valid and part of v same for out.
             unsafe {
-                let is_l = is_less(&*right.sub(1), &*left.sub(1));
+                let is_l = is_less(&*right.sub(1), &*left.sub(1))?;
                 *left = left.sub(is_l as usize);
                 *right = right.sub(!is_l as usize);
                 let to_copy = if is_l { *left } else { *right };
@@ -416,6 +591,7 @@ where
     }
     // Finally, `hole` gets dropped. If the shorter run was not fully consumed, whatever remains of
     // it will now be copied into the hole in `v`.
+    return Ok(());
 
     // When dropped, copies the range `start..end` into `dest..`.
     struct MergeHole<T> {
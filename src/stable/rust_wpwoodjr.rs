@@ -5,10 +5,11 @@
 // Sorting
 ////////////////////////////////////////////////////////////////////////////////
 use std::cmp::Ordering;
+use std::convert::Infallible;
 use std::mem::{self, size_of};
 use std::ptr;
 
-sort_impl!("rust_wpwoodjr_stable");
+sort_impl!("rust_wpwoodjr_stable", try_sort_by);
 
 /// Sorts the slice.
 ///
@@ -41,7 +42,7 @@ pub fn sort<T>(arr: &mut [T])
 where
     T: Ord,
 {
-    merge_sort(arr, |a, b| a.lt(b));
+    into_ok(merge_sort(arr, |a, b| Ok(a.lt(b))));
 }
 
 /// Sorts the slice with a comparator function.
@@ -94,7 +95,28 @@ pub fn sort_by<T, F>(arr: &mut [T], mut compare: F)
 where
     F: FnMut(&T, &T) -> Ordering,
 {
-    merge_sort(arr, |a, b| compare(a, b) == Ordering::Less);
+    into_ok(merge_sort(arr, |a, b| Ok(compare(a, b) == Ordering::Less)));
+}
+
+/// Sorts the slice with a fallible comparator function.
+///
+/// Same as [`sort_by`], but stops at the first error returned by `compare` and returns it. The
+/// slice then still holds all of its original elements, in an unspecified order.
+#[inline]
+pub fn try_sort_by<T, E, F>(arr: &mut [T], mut compare: F) -> Result<(), E>
+where
+    F: FnMut(&T, &T) -> Result<Ordering, E>,
+{
+    merge_sort(arr, |a, b| Ok(compare(a, b)? == Ordering::Less))
+}
+
+/// Unwraps the result of a sort that can't fail.
+#[inline(always)]
+fn into_ok(res: Result<(), Infallible>) {
+    match res {
+        Ok(()) => {}
+        Err(never) => match never {},
+    }
 }
 
 /// Inserts `v[v.len() - 1]` into pre-sorted sequence `v[..v.len() - 1]` so that whole `v[..]` becomes sorted.
@@ -103,12 +125,12 @@ where
 #[cfg(not(no_global_oom_handling))]
 // benchmarking indicated that inlining makes a substantial improvement, yet only requires a couple of hundred bytes
 #[inline(always)]
-fn insert_end<T, F>(v: &mut [T], is_less: &mut F)
+fn insert_end<T, E, F>(v: &mut [T], is_less: &mut F) -> Result<(), E>
 where
-    F: FnMut(&T, &T) -> bool,
+    F: FnMut(&T, &T) -> Result<bool, E>,
 {
     let end = v.len().saturating_sub(1);
-    if end > 0 && is_less(&v[end], &v[end - 1]) {
+    if end > 0 && is_less(&v[end], &v[end - 1])? {
         unsafe {
             // There are three ways to implement insertion here:
             //
@@ -146,7 +168,7 @@ where
             ptr::copy_nonoverlapping(hole.dest, v.get_unchecked_mut(end), 1);
 
             let mut i = end - 1;
-            while i > 0 && is_less(&*tmp, v.get_unchecked(i - 1)) {
+            while i > 0 && is_less(&*tmp, v.get_unchecked(i - 1))? {
                 hole.dest = v.get_unchecked_mut(i - 1);
                 ptr::copy_nonoverlapping(hole.dest, v.get_unchecked_mut(i), 1);
                 i -= 1;
@@ -155,6 +177,8 @@ where
         }
     }
 
+    return Ok(());
+
     // When dropped, copies from `src` into `dest`.
     struct InsertionHole<T> {
         src: *const T,
@@ -179,9 +203,9 @@ where
 /// to hold a copy of the shorter slice.
 #[allow(unused_unsafe)]
 #[cfg(not(no_global_oom_handling))]
-unsafe fn merge<T, F>(v: &mut [T], mid: usize, buf: *mut T, is_less: &mut F)
+unsafe fn merge<T, E, F>(v: &mut [T], mid: usize, buf: *mut T, is_less: &mut F) -> Result<(), E>
 where
-    F: FnMut(&T, &T) -> bool,
+    F: FnMut(&T, &T) -> Result<bool, E>,
 {
     let len = v.len();
     let v = v.as_mut_ptr();
@@ -226,7 +250,7 @@ where
             // Consume the lesser side.
             // If equal, prefer the left run to maintain stability.
             unsafe {
-                let to_copy = if is_less(&*right, &**left) {
+                let to_copy = if is_less(&*right, &**left)? {
                     get_and_increment(&mut right)
                 } else {
                     get_and_increment(left)
@@ -254,7 +278,7 @@ where
             // Consume the greater side.
             // If equal, prefer the right run to maintain stability.
             unsafe {
-                let to_copy = if is_less(&*right.offset(-1), &*left.offset(-1)) {
+                let to_copy = if is_less(&*right.offset(-1), &*left.offset(-1))? {
                     decrement_and_get(left)
                 } else {
                     decrement_and_get(right)
@@ -265,6 +289,7 @@ where
     }
     // Finally, `hole` gets dropped. If the shorter run was not fully consumed, whatever remains of
     // it will now be copied into the hole in `v`.
+    return Ok(());
 
     #[inline(always)]
     unsafe fn get_and_increment<T>(ptr: &mut *mut T) -> *mut T {
@@ -303,10 +328,12 @@ where
 /// 2) Small slices sort using a fast insertion sort, then merge
 ///
 /// The total running time is *O*(*n* \* log(*n*)) worst-case.
+///
+/// The first error returned by `is_less` stops the sort, and is returned.
 #[cfg(not(no_global_oom_handling))]
-fn merge_sort<T, F>(v: &mut [T], mut is_less: F)
+fn merge_sort<T, E, F>(v: &mut [T], mut is_less: F) -> Result<(), E>
 where
-    F: FnMut(&T, &T) -> bool,
+    F: FnMut(&T, &T) -> Result<bool, E>,
 {
     // `gt!` macro centralizes and clarifies the logic.
     // Unchecked array access gives an approximate 20% performance improvement.
@@ -319,7 +346,7 @@ where
             // $is_less(&$v[$right], &$v[$left])
             $is_less(unsafe { &$v.get_unchecked($right) }, unsafe {
                 &$v.get_unchecked($left)
-            })
+            })?
         };
     }
 
@@ -331,16 +358,16 @@ where
 
     // Sorting has no meaningful behavior on zero-sized types.
     if size_of::<T>() == 0 {
-        return;
+        return Ok(());
     }
 
     let len = v.len();
     // Short arrays get sorted in-place via insertion sort to avoid allocations.
     if len <= MAX_INSERTION {
         for i in 1..len {
-            insert_end(&mut v[..=i], &mut is_less);
+            insert_end(&mut v[..=i], &mut is_less)?;
         }
-        return;
+        return Ok(());
     }
 
     // Allocate a buffer to use as scratch memory. We keep the length 0 so we can keep in it
@@ -348,14 +375,19 @@ where
     // `is_less` panics. When merging two slices, this buffer holds a copy of the right-hand slice,
     // which will always have length at most `(len + 1) / 2`.
     let mut buf = Vec::with_capacity((len + 1) / 2);
-    slice_merge_sort(v, 0, buf.as_mut_ptr(), &mut is_less);
+    return slice_merge_sort(v, 0, buf.as_mut_ptr(), &mut is_less);
 
     // Do a recursive depth-first merge while slice's length is greater than SMALL_SLICE_LEN*2.
     // Below that length use a combination of insertion sort and merging.
     // For optimization, `sorted` tracks how much of the slice's prefix is already sorted.
-    fn slice_merge_sort<T, F>(v: &mut [T], mut sorted: usize, buf_ptr: *mut T, is_less: &mut F)
+    fn slice_merge_sort<T, E, F>(
+        v: &mut [T],
+        mut sorted: usize,
+        buf_ptr: *mut T,
+        is_less: &mut F,
+    ) -> Result<(), E>
     where
-        F: FnMut(&T, &T) -> bool,
+        F: FnMut(&T, &T) -> Result<bool, E>,
     {
         let len = v.len();
         // find length of sorted prefix
@@ -385,42 +417,44 @@ where
         if sorted < len {
             if len <= SMALL_SLICE_LEN + 2 {
                 for i in sorted..len {
-                    insert_end(&mut v[..=i], is_less);
+                    insert_end(&mut v[..=i], is_less)?;
                 }
             } else {
                 let mid;
                 if len > SMALL_SLICE_LEN * 2 {
                     mid = sorted.max(len / 2);
                     if sorted < mid {
-                        slice_merge_sort(&mut v[..mid], sorted, buf_ptr, is_less);
+                        slice_merge_sort(&mut v[..mid], sorted, buf_ptr, is_less)?;
                     }
-                    slice_merge_sort(&mut v[mid..], 0, buf_ptr, is_less);
+                    slice_merge_sort(&mut v[mid..], 0, buf_ptr, is_less)?;
                     if !gt!(v, mid - 1, mid, is_less) {
-                        return;
+                        return Ok(());
                     } else if gt!(v, 0, len - 1, is_less) {
                         // strictly reverse sorted
                         unsafe {
                             swap_slices(v, mid, buf_ptr);
                         }
-                        return;
+                        return Ok(());
                     }
                 } else {
                     for i in sorted..SMALL_SLICE_LEN {
-                        insert_end(&mut v[..=i], is_less);
+                        insert_end(&mut v[..=i], is_less)?;
                     }
                     for i in SMALL_SLICE_LEN + 1..len {
-                        insert_end(&mut v[SMALL_SLICE_LEN..=i], is_less);
+                        insert_end(&mut v[SMALL_SLICE_LEN..=i], is_less)?;
                     }
                     if !gt!(v, SMALL_SLICE_LEN - 1, SMALL_SLICE_LEN, is_less) {
-                        return;
+                        return Ok(());
                     }
                     mid = SMALL_SLICE_LEN;
                 }
                 unsafe {
-                    merge(v, mid, buf_ptr, is_less);
+                    merge(v, mid, buf_ptr, is_less)?;
                 }
             }
         }
+
+        Ok(())
     }
 
     /// swap contents of left-hand and right-hand slices divided at `mid`
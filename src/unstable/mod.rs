@@ -1,6 +1,9 @@
 pub mod rust_ipnsort;
 pub mod rust_std;
 
+#[cfg(feature = "rust_ipnsort_parallel")]
+pub mod rust_ipnsort_parallel;
+
 #[cfg(feature = "rust_dmsort")]
 pub mod rust_dmsort;
 
@@ -2,7 +2,16 @@ use std::cmp::Ordering;
 
 use ipnsort;
 
-sort_impl!("rust_ipnsort_unstable");
+sort_impl!(
+    if cfg!(feature = "rust_ipnsort_radix") {
+        "rust_ipnsort_radix_unstable"
+    } else if cfg!(feature = "partition_simd") {
+        "rust_ipnsort_simd_unstable"
+    } else {
+        "rust_ipnsort_unstable"
+    },
+    try_sort_by
+);
 
 pub fn sort<T: Ord>(data: &mut [T]) {
     ipnsort::sort(data);
@@ -11,3 +20,10 @@ pub fn sort<T: Ord>(data: &mut [T]) {
 pub fn sort_by<T, F: FnMut(&T, &T) -> Ordering>(data: &mut [T], compare: F) {
     ipnsort::sort_by(data, compare);
 }
+
+pub fn try_sort_by<T, E, F: FnMut(&T, &T) -> Result<Ordering, E>>(
+    data: &mut [T],
+    compare: F,
+) -> Result<(), E> {
+    ipnsort::try_sort_by(data, compare)
+}
@@ -0,0 +1,50 @@
+use std::cmp::Ordering;
+
+sort_impl!(if cfg!(feature = "rust_ipnsort_parallel_rayon") {
+    "rust_ipnsort_parallel_rayon_unstable"
+} else {
+    "rust_ipnsort_parallel_unstable"
+});
+
+// Types that can't be sent to other threads and comparison functions that can't be shared between
+// threads fall back to the sequential sort, instead of panicking with "Type not supported.".
+
+trait IpnsortParallelSort: Sized {
+    fn sort(data: &mut [Self]);
+}
+
+impl<T: Ord> IpnsortParallelSort for T {
+    default fn sort(data: &mut [Self]) {
+        ipnsort::sort(data);
+    }
+}
+
+impl<T: Send + Ord> IpnsortParallelSort for T {
+    fn sort(data: &mut [Self]) {
+        ipnsort::par_sort(data);
+    }
+}
+
+trait IpnsortParallelSortBy<F>: Sized {
+    fn sort_by(data: &mut [Self], compare: F);
+}
+
+impl<T, F: FnMut(&T, &T) -> Ordering> IpnsortParallelSortBy<F> for T {
+    default fn sort_by(data: &mut [T], compare: F) {
+        ipnsort::sort_by(data, compare);
+    }
+}
+
+impl<T: Send, F: Fn(&T, &T) -> Ordering + Sync> IpnsortParallelSortBy<F> for T {
+    fn sort_by(data: &mut [T], compare: F) {
+        ipnsort::par_sort_by(data, compare);
+    }
+}
+
+pub fn sort<T: Ord>(data: &mut [T]) {
+    <T as IpnsortParallelSort>::sort(data);
+}
+
+pub fn sort_by<T, F: FnMut(&T, &T) -> Ordering>(data: &mut [T], compare: F) {
+    <T as IpnsortParallelSortBy<F>>::sort_by(data, compare);
+}
@@ -0,0 +1,189 @@
+//! Checks how much the sort implementations allocate. The global allocator is shared by the whole
+//! test binary, so this lives in its own integration test.
+
+use std::mem::MaybeUninit;
+
+use sort_test_tools::counting_alloc::{self, CountingAllocator};
+use sort_test_tools::{instantiate_alloc_tests, patterns};
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+mod unstable_rust_std {
+    use super::*;
+
+    instantiate_alloc_tests!(sort_comp::unstable::rust_std::SortImpl, in_place);
+}
+
+mod unstable_rust_ipnsort {
+    use super::*;
+
+    instantiate_alloc_tests!(sort_comp::unstable::rust_ipnsort::SortImpl, in_place);
+}
+
+// The merge sorts below use a scratch buffer of half or all of the input, and a growing stack of
+// pending runs on top of that.
+
+mod stable_rust_std {
+    use super::*;
+
+    instantiate_alloc_tests!(
+        sort_comp::stable::rust_std::SortImpl,
+        max_scratch_ratio = 0.75
+    );
+}
+
+mod stable_rust_ipnsort {
+    use super::*;
+
+    instantiate_alloc_tests!(
+        sort_comp::stable::rust_ipnsort::SortImpl,
+        max_scratch_ratio = 0.5
+    );
+}
+
+#[cfg(feature = "evolution")]
+mod evolution {
+    use super::*;
+
+    // quicksort_evo0 is left out, its recursion overflows the stack for some of the large inputs.
+    use sort_comp::other::sort_evolution::stable;
+
+    mod timsort_evo0 {
+        use super::*;
+
+        instantiate_alloc_tests!(stable::timsort_evo0::SortImpl, max_scratch_ratio = 0.75);
+    }
+
+    mod timsort_evo1 {
+        use super::*;
+
+        instantiate_alloc_tests!(stable::timsort_evo1::SortImpl, max_scratch_ratio = 0.75);
+    }
+
+    mod timsort_evo2 {
+        use super::*;
+
+        instantiate_alloc_tests!(stable::timsort_evo2::SortImpl, max_scratch_ratio = 0.75);
+    }
+
+    mod timsort_evo3 {
+        use super::*;
+
+        instantiate_alloc_tests!(stable::timsort_evo3::SortImpl, max_scratch_ratio = 1.25);
+    }
+
+    mod timsort_evo4 {
+        use super::*;
+
+        instantiate_alloc_tests!(stable::timsort_evo4::SortImpl, max_scratch_ratio = 1.25);
+    }
+}
+
+#[cfg(feature = "partition")]
+#[test]
+fn partition_tls_scratch_reused() {
+    use std::alloc::Layout;
+
+    use sort_comp::other::partition::get_or_alloc_tls_scratch;
+    use sort_test_tools::counting_alloc;
+
+    let layout = Layout::array::<u64>(1_000).unwrap();
+    let larger_layout = Layout::array::<u64>(2_000).unwrap();
+
+    // The first call may or may not allocate, depending on the tests that ran on this thread.
+    get_or_alloc_tls_scratch(layout);
+
+    let stats = counting_alloc::track_allocations(|| {
+        get_or_alloc_tls_scratch(layout);
+        get_or_alloc_tls_scratch(Layout::array::<u64>(10).unwrap());
+    });
+    assert_eq!(stats.count, 0);
+
+    let stats = counting_alloc::track_allocations(|| {
+        get_or_alloc_tls_scratch(larger_layout);
+        get_or_alloc_tls_scratch(larger_layout);
+    });
+    assert_eq!(stats.count, 1);
+}
+
+// The `_with_buffer` functions must not allocate, no matter how short the buffer is.
+
+const WITH_BUFFER_TEST_SIZES: [usize; 8] = [0, 1, 2, 20, 21, 100, 1_000, 10_000];
+
+/// Returns `len` random keys with many duplicates, each paired with its original position, so that
+/// a stable result is fully determined by the keys.
+fn keys_with_pos(len: usize) -> Vec<(i32, usize)> {
+    patterns::random_uniform::<i32>(len, 0..=(len as i32 / 8))
+        .into_iter()
+        .zip(0..)
+        .collect()
+}
+
+fn check_stable_sort_with_buffer(
+    sort_fn: impl Fn(&mut [(i32, usize)], &mut [MaybeUninit<(i32, usize)>]),
+) {
+    for len in WITH_BUFFER_TEST_SIZES {
+        let original = keys_with_pos(len);
+
+        let mut expected = original.clone();
+        expected.sort_by_key(|&(key, _)| key);
+
+        for buf_len in [0, 1, len / 8, len / 2] {
+            let mut v = original.clone();
+            let mut buf = vec![MaybeUninit::uninit(); buf_len];
+
+            let stats = counting_alloc::track_allocations(|| sort_fn(&mut v, &mut buf));
+            assert_eq!(stats.count, 0, "len: {len} buf_len: {buf_len}");
+            assert_eq!(v, expected, "len: {len} buf_len: {buf_len}");
+        }
+    }
+}
+
+#[test]
+fn stable_rust_std_sort_with_buffer_no_alloc() {
+    check_stable_sort_with_buffer(|v, buf| {
+        sort_comp::stable::rust_std::sort_by_with_buffer(v, buf, |a, b| a.0.cmp(&b.0));
+    });
+}
+
+#[test]
+fn stable_rust_ipnsort_sort_with_buffer_no_alloc() {
+    check_stable_sort_with_buffer(|v, buf| {
+        sort_comp::stable::rust_ipnsort::sort_by_with_buffer(v, buf, |a, b| a.0.cmp(&b.0));
+    });
+}
+
+#[cfg(feature = "partition")]
+#[test]
+fn partition_stable_2side_fill_with_buffer_no_alloc() {
+    use sort_comp::other::partition::stable_2side_fill;
+
+    for len in WITH_BUFFER_TEST_SIZES {
+        let original = keys_with_pos(len);
+        let pivot = (len as i32 / 16, 0);
+
+        let (mut expected, ge): (Vec<_>, Vec<_>) =
+            original.iter().partition(|elem| elem.0 < pivot.0);
+        let expected_lt_count = expected.len();
+        expected.extend(ge);
+
+        for buf_len in [0, 1, len / 8, len] {
+            let mut v = original.clone();
+            let mut buf = vec![MaybeUninit::uninit(); buf_len];
+
+            let mut lt_count = 0;
+            let stats = counting_alloc::track_allocations(|| {
+                lt_count = stable_2side_fill::partition_by_with_buffer(
+                    &mut v,
+                    &mut buf,
+                    &pivot,
+                    &mut |a, b| a.0 < b.0,
+                );
+            });
+            assert_eq!(stats.count, 0, "len: {len} buf_len: {buf_len}");
+            assert_eq!(lt_count, expected_lt_count, "len: {len} buf_len: {buf_len}");
+            assert_eq!(v, expected, "len: {len} buf_len: {buf_len}");
+        }
+    }
+}
@@ -0,0 +1,159 @@
+//! Correctness tests for the `PartitionPoint` implementations, which are otherwise only exercised
+//! by the partition_point benchmarks.
+
+#![cfg(feature = "partition_point")]
+
+use core::cmp::Ordering;
+use core::fmt::Debug;
+use core::ptr::NonNull;
+use core::slice;
+
+use sort_comp::other::partition_point::{self, PartitionPoint};
+
+use sort_test_tools::ffi_types::FFIString;
+use sort_test_tools::patterns::{self, PatternValue};
+use sort_test_tools::tests::TEST_SIZES;
+
+fn sorted<T: Ord>(mut v: Vec<T>) -> Vec<T> {
+    v.sort();
+    v
+}
+
+fn sorted_pattern_fns<T: PatternValue + Ord>() -> Vec<fn(usize) -> Vec<T>> {
+    vec![
+        |len| sorted(patterns::random(len)),
+        |len| sorted(patterns::random_uniform(len, 0..16)),
+        patterns::all_equal,
+        patterns::ascending,
+    ]
+}
+
+/// Returns some of the elements of `v`, the smallest and largest value of `T` and a few random
+/// values, which are likely in between the elements or outside of their range.
+fn search_values<T: PatternValue + Clone>(v: &[T]) -> Vec<T> {
+    let mut vals = vec![T::from_i64(T::MIN), T::from_i64(T::MAX)];
+    vals.extend(patterns::random(8));
+
+    if !v.is_empty() {
+        let sample_len = v.len().min(16);
+        let indices = patterns::random_uniform::<usize>(sample_len, 0..(v.len() as i32));
+        vals.extend(indices.into_iter().map(|i| v[i].clone()));
+    }
+
+    vals
+}
+
+fn linear_partition_point<T: Ord>(v: &[T], val: &T) -> usize {
+    v.iter().take_while(|elem| *elem < val).count()
+}
+
+/// ⌈log2(len + 1)⌉, the number of comparisons needed to pick one of the `len + 1` possible results.
+fn log2_ceil_len_plus_one(len: usize) -> usize {
+    (usize::BITS - len.leading_zeros()) as usize
+}
+
+fn partition_point_sorted<T, P>()
+where
+    T: PatternValue + Ord + Clone + Debug,
+    P: PartitionPoint,
+{
+    for len in TEST_SIZES {
+        for pattern_fn in sorted_pattern_fns::<T>() {
+            let v = pattern_fn(len);
+
+            for val in search_values(&v) {
+                assert_eq!(
+                    P::partition_point(&v, &val),
+                    linear_partition_point(&v, &val),
+                    "len: {len} val: {val:?}"
+                );
+            }
+        }
+    }
+}
+
+fn comp_count<P: PartitionPoint>(max_comps: fn(usize) -> usize) {
+    for len in TEST_SIZES {
+        let v = patterns::ascending::<i32>(len);
+
+        for val in search_values(&v) {
+            let mut comps = 0;
+            P::partition_point_by(&v, &val, |a, b| {
+                comps += 1;
+                a.cmp(b)
+            });
+
+            assert!(comps <= max_comps(len), "len: {len} comps: {comps}");
+        }
+    }
+}
+
+fn zst_max_len<P: PartitionPoint>(max_comps: fn(usize) -> usize) {
+    for len in [0, 1, 2, usize::MAX / 2, usize::MAX - 1, usize::MAX] {
+        // SAFETY: Slices of zero-sized types take up no memory, no matter their length.
+        let v = unsafe { slice::from_raw_parts(NonNull::<()>::dangling().as_ptr(), len) };
+
+        for (ordering, expected) in [(Ordering::Less, len), (Ordering::Greater, 0)] {
+            let mut comps = 0;
+            let result = P::partition_point_by(v, &(), |_, _| {
+                comps += 1;
+                ordering
+            });
+
+            assert_eq!(result, expected, "len: {len}");
+            assert!(comps <= max_comps(len), "len: {len} comps: {comps}");
+        }
+    }
+}
+
+/// Instantiates the tests for the `PartitionPointImpl` of each of the given `partition_point`
+/// modules, with a function returning the maximum number of comparisons it may do for a length.
+macro_rules! instantiate_partition_point_tests {
+    ($($partition_point_mod:ident => $max_comps:expr),* $(,)?) => {
+        $(
+            mod $partition_point_mod {
+                use super::*;
+
+                type TestPartitionPoint =
+                    partition_point::$partition_point_mod::PartitionPointImpl;
+
+                #[test]
+                fn partition_point_i32() {
+                    partition_point_sorted::<i32, TestPartitionPoint>();
+                }
+
+                #[test]
+                fn partition_point_u8() {
+                    partition_point_sorted::<u8, TestPartitionPoint>();
+                }
+
+                #[test]
+                fn partition_point_u64() {
+                    partition_point_sorted::<u64, TestPartitionPoint>();
+                }
+
+                #[test]
+                fn partition_point_ffi_string() {
+                    partition_point_sorted::<FFIString, TestPartitionPoint>();
+                }
+
+                #[test]
+                fn comp_count() {
+                    super::comp_count::<TestPartitionPoint>($max_comps);
+                }
+
+                #[test]
+                fn zst_max_len() {
+                    super::zst_max_len::<TestPartitionPoint>($max_comps);
+                }
+            }
+        )*
+    };
+}
+
+instantiate_partition_point_tests!(
+    branchless_bitwise => log2_ceil_len_plus_one,
+    branchless_clean => log2_ceil_len_plus_one,
+    // The binary search of the standard library may check the last remaining element again.
+    std => |len| log2_ceil_len_plus_one(len) + 1,
+);
@@ -7,3 +7,15 @@ hyperfine --min-runs 3 --prepare 'cargo clean' 'cargo build --release'
 
 # Does not show a large difference. Probably not representative of real world lto impact.
 # CARGO_PROFILE_release_LTO=thin hyperfine --prepare 'cargo clean' 'cargo build --release'
+
+# Instructions executed by the compiler and size of the resulting binary, for the standard library
+# sort and ipnsort with and without the opt_size feature. Needs perf.
+for FEATURES in "" "ipnsort" "ipnsort_opt_size"; do
+    echo "Features: ${FEATURES:-none}"
+
+    cargo clean
+    perf stat -x, -e instructions:u cargo build --release --features "$FEATURES" 2>&1 >/dev/null \
+        | grep instructions | cut -d, -f1 | xargs printf "Instructions: %s\n"
+
+    stat --format "Binary size: %s bytes" target/release/compile_time_impact
+done
@@ -1,4 +1,9 @@
 fn sort<T: Ord>(v: &mut [T]) {
     // glidesort::sort(v);
-    v.sort()
+
+    #[cfg(feature = "ipnsort")]
+    ipnsort::sort(v);
+
+    #[cfg(not(feature = "ipnsort"))]
+    v.sort();
 }